@@ -24,6 +24,12 @@
 #define GB_CAMERA_WIDTH 128
 #define GB_CAMERA_HEIGHT 112
 
+// Photo slot error codes (returned negated by gb_try_*_camera_photo)
+#define GB_PHOTO_ERR_INVALID_SLOT 1
+#define GB_PHOTO_ERR_EMPTY 2
+#define GB_PHOTO_ERR_BAD_LENGTH 3
+#define GB_PHOTO_ERR_NO_CAMERA 4
+
 // Opaque handle to GameBoy emulator instance
 typedef void* GBHandle;
 
@@ -33,9 +39,11 @@ void gb_destroy(GBHandle handle);
 
 // ROM loading
 bool gb_load_rom(GBHandle handle, const uint8_t* data, size_t len);
+bool gb_load_boot_rom(GBHandle handle, const uint8_t* data, size_t len);
 
 // Emulation
 void gb_step_frame(GBHandle handle);
+void gb_set_paused(GBHandle handle, bool paused);
 uint32_t gb_get_frame_count(GBHandle handle);
 
 // Frame buffer
@@ -43,9 +51,13 @@ const uint8_t* gb_get_frame_buffer(GBHandle handle);
 size_t gb_get_frame_buffer_size(void);
 uint32_t gb_get_screen_width(void);
 uint32_t gb_get_screen_height(void);
+bool gb_set_output_lut(GBHandle handle, const uint8_t* lut, size_t len);
+void gb_set_lcd_ghosting(GBHandle handle, float factor);
 
 // Input
 void gb_set_button(GBHandle handle, uint8_t button, bool pressed);
+void gb_set_analog_dpad(GBHandle handle, float x, float y, float deadzone);
+void gb_set_turbo(GBHandle handle, uint8_t button, float rate_hz);
 
 // Camera
 void gb_set_camera_image(GBHandle handle, const uint8_t* data, size_t len);
@@ -59,14 +71,26 @@ int32_t gb_camera_contrast(GBHandle handle);
 void gb_set_camera_exposure(GBHandle handle, int32_t exposure);
 bool gb_encode_camera_photo(GBHandle handle, uint8_t slot, const uint8_t* rgba, size_t len);
 void gb_clear_camera_photo_slot(GBHandle handle, uint8_t slot);
+void gb_clear_camera_roll(GBHandle handle);
 uint8_t gb_camera_photo_count(GBHandle handle);
+int32_t gb_try_decode_camera_photo(GBHandle handle, uint8_t slot, uint8_t* buffer, size_t buffer_len);
+int32_t gb_try_encode_camera_photo(GBHandle handle, uint8_t slot, const uint8_t* rgba, size_t len);
 
 // Memory
 uint8_t gb_read_memory(GBHandle handle, uint16_t addr);
 
 // Save data
+bool gb_has_battery(GBHandle handle);
 size_t gb_get_save_size(GBHandle handle);
 size_t gb_get_save_data(GBHandle handle, uint8_t* buffer, size_t buffer_len);
 bool gb_load_save_data(GBHandle handle, const uint8_t* data, size_t len);
+bool gb_is_rumbling(GBHandle handle);
+size_t gb_get_rtc_data(GBHandle handle, uint8_t* buffer, size_t buffer_len);
+bool gb_load_rtc_data(GBHandle handle, const uint8_t* data, size_t len);
+
+// Game Boy Printer on the link port
+void gb_enable_printer(GBHandle handle, bool enable);
+size_t gb_printer_image_height(GBHandle handle);
+size_t gb_printer_image(GBHandle handle, uint8_t* buffer, size_t buffer_len);
 
 #endif /* GB_EMU_H */
@@ -0,0 +1,787 @@
+//! Game Boy audio processing unit (0xFF10-0xFF3F).
+//!
+//! Four channels — two pulse waves (channel 1 with a frequency sweep), a
+//! 32-sample wave channel playing wave RAM, and an LFSR noise channel — are
+//! clocked every T-cycle by [`Apu::tick`]. The frame sequencer that drives
+//! length counters (256 Hz), the sweep (128 Hz) and volume envelopes (64 Hz)
+//! is stepped from outside on each falling edge of DIV bit 4 (bit 5 in
+//! double speed), see [`Apu::clock_frame_sequencer`]. NR50/NR51 mix the
+//! channels into a stereo pair, sampled at a configurable rate into a
+//! bounded ring of interleaved (L, R) samples emptied by [`Apu::drain_into`].
+
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+
+/// T-cycles per second of the (normal speed) master clock.
+const CLOCK_HZ: u32 = 4_194_304;
+pub const DEFAULT_SAMPLE_RATE: u32 = 48_000;
+/// Stereo frames kept for the frontend (~340 ms at 48 kHz); on overrun the
+/// oldest are dropped.
+const MAX_BUFFERED_FRAMES: usize = 16_384;
+
+/// Pulse waveforms for duty 12.5%, 25%, 50% and 75%.
+const DUTY_TABLE: [[u8; 8]; 4] = [
+    [0, 0, 0, 0, 0, 0, 0, 1],
+    [1, 0, 0, 0, 0, 0, 0, 1],
+    [1, 0, 0, 0, 0, 1, 1, 1],
+    [0, 1, 1, 1, 1, 1, 1, 0],
+];
+
+/// Noise channel base periods selected by NR43 bits 0-2.
+const NOISE_DIVISORS: [u32; 8] = [8, 16, 32, 48, 64, 80, 96, 112];
+
+/// Bits that read back as 1 for 0xFF10-0xFF2F (write-only and unused bits).
+const READ_MASKS: [u8; 0x20] = [
+    0x80, 0x3F, 0x00, 0xFF, 0xBF, // NR10-NR14
+    0xFF, 0x3F, 0x00, 0xFF, 0xBF, // unused, NR21-NR24
+    0x7F, 0xFF, 0x9F, 0xFF, 0xBF, // NR30-NR34
+    0xFF, 0xFF, 0x00, 0x00, 0xBF, // unused, NR41-NR44
+    0x00, 0x00, 0x70, // NR50-NR52
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, // unused
+];
+
+/// Volume envelope (NRx2) shared by the pulse and noise channels.
+#[derive(Clone, Copy, Default, Hash)]
+struct Envelope {
+    initial: u8,
+    increase: bool,
+    period: u8,
+    volume: u8,
+    timer: u8,
+}
+
+impl Envelope {
+    fn write(&mut self, value: u8) {
+        self.initial = value >> 4;
+        self.increase = value & 0x08 != 0;
+        self.period = value & 0x07;
+    }
+
+    fn trigger(&mut self) {
+        self.volume = self.initial;
+        self.timer = self.period;
+    }
+
+    fn clock(&mut self) {
+        if self.period == 0 {
+            return;
+        }
+        self.timer = self.timer.saturating_sub(1);
+        if self.timer == 0 {
+            self.timer = self.period;
+            if self.increase && self.volume < 15 {
+                self.volume += 1;
+            } else if !self.increase && self.volume > 0 {
+                self.volume -= 1;
+            }
+        }
+    }
+}
+
+/// Channel 1's frequency sweep (NR10).
+#[derive(Clone, Copy, Default, Hash)]
+struct Sweep {
+    period: u8,
+    negate: bool,
+    shift: u8,
+    timer: u8,
+    enabled: bool,
+    shadow: u16,
+}
+
+/// Channels 1 and 2.
+#[derive(Clone, Copy, Default, Hash)]
+struct Pulse {
+    enabled: bool,
+    dac_enabled: bool,
+    duty: u8,
+    duty_step: u8,
+    frequency: u16,
+    timer: u32,
+    length: u16,
+    length_enabled: bool,
+    envelope: Envelope,
+    sweep: Sweep,
+}
+
+impl Pulse {
+    fn period(&self) -> u32 {
+        (2048 - self.frequency as u32) * 4
+    }
+
+    fn trigger(&mut self) {
+        self.enabled = self.dac_enabled;
+        if self.length == 0 {
+            self.length = 64;
+        }
+        self.timer = self.period();
+        self.envelope.trigger();
+
+        let sweep = &mut self.sweep;
+        sweep.shadow = self.frequency;
+        sweep.timer = if sweep.period == 0 { 8 } else { sweep.period };
+        sweep.enabled = sweep.period != 0 || sweep.shift != 0;
+        if sweep.shift != 0 {
+            self.sweep_target();
+        }
+    }
+
+    /// Next swept frequency; disables the channel on overflow past 2047.
+    fn sweep_target(&mut self) -> u16 {
+        let delta = self.sweep.shadow >> self.sweep.shift;
+        let target = if self.sweep.negate {
+            self.sweep.shadow - delta
+        } else {
+            self.sweep.shadow + delta
+        };
+        if target > 2047 {
+            self.enabled = false;
+        }
+        target
+    }
+
+    fn clock_sweep(&mut self) {
+        self.sweep.timer = self.sweep.timer.saturating_sub(1);
+        if self.sweep.timer != 0 {
+            return;
+        }
+        self.sweep.timer = if self.sweep.period == 0 {
+            8
+        } else {
+            self.sweep.period
+        };
+        if !self.sweep.enabled || self.sweep.period == 0 {
+            return;
+        }
+        let target = self.sweep_target();
+        if target <= 2047 && self.sweep.shift != 0 {
+            self.sweep.shadow = target;
+            self.frequency = target;
+            self.sweep_target();
+        }
+    }
+
+    fn tick(&mut self, mut cycles: u32) {
+        if !self.enabled {
+            return;
+        }
+        while cycles >= self.timer {
+            cycles -= self.timer;
+            self.timer = self.period();
+            self.duty_step = (self.duty_step + 1) & 7;
+        }
+        self.timer -= cycles;
+    }
+
+    fn output(&self) -> u8 {
+        if !self.enabled {
+            return 0;
+        }
+        DUTY_TABLE[self.duty as usize][self.duty_step as usize] * self.envelope.volume
+    }
+}
+
+/// Channel 3.
+#[derive(Clone, Copy, Default, Hash)]
+struct Wave {
+    enabled: bool,
+    dac_enabled: bool,
+    frequency: u16,
+    timer: u32,
+    length: u16,
+    length_enabled: bool,
+    /// NR32 bits 5-6: mute, 100%, 50%, 25%.
+    volume_code: u8,
+    /// Nibble index 0-31 into wave RAM.
+    position: u8,
+    ram: [u8; 16],
+}
+
+impl Wave {
+    fn period(&self) -> u32 {
+        (2048 - self.frequency as u32) * 2
+    }
+
+    fn trigger(&mut self) {
+        self.enabled = self.dac_enabled;
+        if self.length == 0 {
+            self.length = 256;
+        }
+        self.timer = self.period();
+        self.position = 0;
+    }
+
+    fn tick(&mut self, mut cycles: u32) {
+        if !self.enabled {
+            return;
+        }
+        while cycles >= self.timer {
+            cycles -= self.timer;
+            self.timer = self.period();
+            self.position = (self.position + 1) & 31;
+        }
+        self.timer -= cycles;
+    }
+
+    fn output(&self) -> u8 {
+        if !self.enabled || self.volume_code == 0 {
+            return 0;
+        }
+        let byte = self.ram[(self.position / 2) as usize];
+        let sample = if self.position & 1 == 0 {
+            byte >> 4
+        } else {
+            byte & 0x0F
+        };
+        sample >> (self.volume_code - 1)
+    }
+}
+
+/// Channel 4.
+#[derive(Clone, Copy, Default, Hash)]
+struct Noise {
+    enabled: bool,
+    dac_enabled: bool,
+    timer: u32,
+    length: u16,
+    length_enabled: bool,
+    envelope: Envelope,
+    clock_shift: u8,
+    /// NR43 bit 3: 7-bit LFSR instead of 15-bit.
+    short_mode: bool,
+    divisor_code: u8,
+    lfsr: u16,
+}
+
+impl Noise {
+    fn period(&self) -> u32 {
+        NOISE_DIVISORS[self.divisor_code as usize] << self.clock_shift
+    }
+
+    fn trigger(&mut self) {
+        self.enabled = self.dac_enabled;
+        if self.length == 0 {
+            self.length = 64;
+        }
+        self.timer = self.period();
+        self.envelope.trigger();
+        self.lfsr = 0x7FFF;
+    }
+
+    fn tick(&mut self, mut cycles: u32) {
+        // Shifts 14 and 15 stop the LFSR
+        if !self.enabled || self.clock_shift >= 14 {
+            return;
+        }
+        while cycles >= self.timer {
+            cycles -= self.timer;
+            self.timer = self.period();
+            let bit = (self.lfsr ^ (self.lfsr >> 1)) & 1;
+            self.lfsr = (self.lfsr >> 1) | (bit << 14);
+            if self.short_mode {
+                self.lfsr = (self.lfsr & !0x40) | (bit << 6);
+            }
+        }
+        self.timer -= cycles;
+    }
+
+    fn output(&self) -> u8 {
+        if !self.enabled || self.lfsr & 1 != 0 {
+            return 0;
+        }
+        self.envelope.volume
+    }
+}
+
+/// Decrement a length counter, silencing the channel when it expires.
+fn clock_length(length: &mut u16, length_enabled: bool, enabled: &mut bool) {
+    if length_enabled && *length > 0 {
+        *length -= 1;
+        if *length == 0 {
+            *enabled = false;
+        }
+    }
+}
+
+pub struct Apu {
+    /// Last values written to 0xFF10-0xFF2F, read back through `READ_MASKS`.
+    regs: [u8; 0x20],
+    /// NR52 bit 7: while off, every other register is held at zero.
+    powered: bool,
+    ch1: Pulse,
+    ch2: Pulse,
+    ch3: Wave,
+    ch4: Noise,
+    /// Step 0-7 of the 512 Hz frame sequencer.
+    frame_step: u8,
+
+    sample_rate: u32,
+    /// T-cycles elapsed toward the next output sample, scaled by `sample_rate`.
+    sample_clock: u64,
+    /// High-pass filter capacitor charge (L, R), as the DMG's output stage.
+    capacitor: [f32; 2],
+    /// Interleaved L, R samples in -1.0..=1.0 waiting to be drained, at
+    /// most `MAX_BUFFERED_FRAMES` frames.
+    samples: VecDeque<f32>,
+}
+
+impl Apu {
+    pub fn new() -> Self {
+        let mut apu = Apu {
+            regs: [0; 0x20],
+            powered: true,
+            ch1: Pulse::default(),
+            ch2: Pulse::default(),
+            ch3: Wave::default(),
+            ch4: Noise::default(),
+            frame_step: 0,
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            sample_clock: 0,
+            capacitor: [0.0; 2],
+            samples: VecDeque::new(),
+        };
+        // Post-boot register values, without triggering anything
+        let post_boot = [
+            (0xFF10, 0x80),
+            (0xFF11, 0xBF),
+            (0xFF12, 0xF3),
+            (0xFF14, 0x3F),
+            (0xFF16, 0x3F),
+            (0xFF19, 0x3F),
+            (0xFF1A, 0x7F),
+            (0xFF1B, 0xFF),
+            (0xFF1C, 0x9F),
+            (0xFF1E, 0x3F),
+            (0xFF20, 0xFF),
+            (0xFF23, 0x3F),
+            (0xFF24, 0x77),
+            (0xFF25, 0xF3),
+        ];
+        for (addr, value) in post_boot {
+            apu.write(addr, value);
+        }
+        apu
+    }
+
+    /// Power-on state for a new ROM; the sample rate survives.
+    pub fn reset(&mut self) {
+        let sample_rate = self.sample_rate;
+        *self = Self::new();
+        self.sample_rate = sample_rate;
+    }
+
+    /// Output samples per second per channel (L and R).
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Change the output rate (clamped to at least 1 Hz). Samples already
+    /// buffered are kept.
+    pub fn set_sample_rate(&mut self, rate: u32) {
+        self.sample_rate = rate.max(1);
+        self.sample_clock = 0;
+    }
+
+    /// Take every buffered sample, interleaved L, R.
+    #[cfg(test)]
+    pub fn drain_samples(&mut self) -> Vec<f32> {
+        self.samples.drain(..).collect()
+    }
+
+    /// Move the oldest buffered stereo frames into `out` (interleaved L, R;
+    /// a trailing odd slot is left alone). Returns the frames written.
+    pub fn drain_into(&mut self, out: &mut [f32]) -> usize {
+        let frames = (out.len() / 2).min(self.samples.len() / 2);
+        for (slot, sample) in out.iter_mut().zip(self.samples.drain(..frames * 2)) {
+            *slot = sample;
+        }
+        frames
+    }
+
+    pub fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0xFF26 => {
+                let status = self.ch1.enabled as u8
+                    | (self.ch2.enabled as u8) << 1
+                    | (self.ch3.enabled as u8) << 2
+                    | (self.ch4.enabled as u8) << 3;
+                (self.powered as u8) << 7 | 0x70 | status
+            }
+            0xFF10..=0xFF2F => {
+                let i = (addr - 0xFF10) as usize;
+                self.regs[i] | READ_MASKS[i]
+            }
+            0xFF30..=0xFF3F => self.ch3.ram[(addr - 0xFF30) as usize],
+            _ => 0xFF,
+        }
+    }
+
+    pub fn write(&mut self, addr: u16, value: u8) {
+        match addr {
+            0xFF26 => self.set_power(value & 0x80 != 0),
+            // Wave RAM stays writable with the APU off
+            0xFF30..=0xFF3F => self.ch3.ram[(addr - 0xFF30) as usize] = value,
+            0xFF10..=0xFF2F if self.powered => {
+                self.regs[(addr - 0xFF10) as usize] = value;
+                self.write_channel(addr, value);
+            }
+            _ => {}
+        }
+    }
+
+    fn write_channel(&mut self, addr: u16, value: u8) {
+        match addr {
+            0xFF10 => {
+                self.ch1.sweep.period = (value >> 4) & 0x07;
+                self.ch1.sweep.negate = value & 0x08 != 0;
+                self.ch1.sweep.shift = value & 0x07;
+            }
+            0xFF11 | 0xFF16 => {
+                let ch = if addr == 0xFF11 {
+                    &mut self.ch1
+                } else {
+                    &mut self.ch2
+                };
+                ch.duty = value >> 6;
+                ch.length = 64 - (value & 0x3F) as u16;
+            }
+            0xFF12 | 0xFF17 => {
+                let ch = if addr == 0xFF12 {
+                    &mut self.ch1
+                } else {
+                    &mut self.ch2
+                };
+                ch.envelope.write(value);
+                ch.dac_enabled = value & 0xF8 != 0;
+                ch.enabled &= ch.dac_enabled;
+            }
+            0xFF13 | 0xFF18 => {
+                let ch = if addr == 0xFF13 {
+                    &mut self.ch1
+                } else {
+                    &mut self.ch2
+                };
+                ch.frequency = (ch.frequency & 0x700) | value as u16;
+            }
+            0xFF14 | 0xFF19 => {
+                let ch = if addr == 0xFF14 {
+                    &mut self.ch1
+                } else {
+                    &mut self.ch2
+                };
+                ch.frequency = (ch.frequency & 0xFF) | ((value & 0x07) as u16) << 8;
+                ch.length_enabled = value & 0x40 != 0;
+                if value & 0x80 != 0 {
+                    ch.trigger();
+                }
+            }
+            0xFF1A => {
+                self.ch3.dac_enabled = value & 0x80 != 0;
+                self.ch3.enabled &= self.ch3.dac_enabled;
+            }
+            0xFF1B => self.ch3.length = 256 - value as u16,
+            0xFF1C => self.ch3.volume_code = (value >> 5) & 0x03,
+            0xFF1D => self.ch3.frequency = (self.ch3.frequency & 0x700) | value as u16,
+            0xFF1E => {
+                let high = ((value & 0x07) as u16) << 8;
+                self.ch3.frequency = (self.ch3.frequency & 0xFF) | high;
+                self.ch3.length_enabled = value & 0x40 != 0;
+                if value & 0x80 != 0 {
+                    self.ch3.trigger();
+                }
+            }
+            0xFF20 => self.ch4.length = 64 - (value & 0x3F) as u16,
+            0xFF21 => {
+                self.ch4.envelope.write(value);
+                self.ch4.dac_enabled = value & 0xF8 != 0;
+                self.ch4.enabled &= self.ch4.dac_enabled;
+            }
+            0xFF22 => {
+                self.ch4.clock_shift = value >> 4;
+                self.ch4.short_mode = value & 0x08 != 0;
+                self.ch4.divisor_code = value & 0x07;
+            }
+            0xFF23 => {
+                self.ch4.length_enabled = value & 0x40 != 0;
+                if value & 0x80 != 0 {
+                    self.ch4.trigger();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// NR52 bit 7. Switching off clears every register and silences all
+    /// channels; wave RAM is kept.
+    fn set_power(&mut self, on: bool) {
+        if on == self.powered {
+            return;
+        }
+        if !on {
+            let ram = self.ch3.ram;
+            self.regs = [0; 0x20];
+            self.ch1 = Pulse::default();
+            self.ch2 = Pulse::default();
+            self.ch3 = Wave {
+                ram,
+                ..Wave::default()
+            };
+            self.ch4 = Noise::default();
+        } else {
+            self.frame_step = 0;
+        }
+        self.powered = on;
+    }
+
+    /// Advance the frame sequencer one step; called on each falling edge of
+    /// DIV bit 4 (bit 5 in double speed), i.e. at 512 Hz.
+    pub fn clock_frame_sequencer(&mut self) {
+        if !self.powered {
+            return;
+        }
+        if self.frame_step & 1 == 0 {
+            clock_length(
+                &mut self.ch1.length,
+                self.ch1.length_enabled,
+                &mut self.ch1.enabled,
+            );
+            clock_length(
+                &mut self.ch2.length,
+                self.ch2.length_enabled,
+                &mut self.ch2.enabled,
+            );
+            clock_length(
+                &mut self.ch3.length,
+                self.ch3.length_enabled,
+                &mut self.ch3.enabled,
+            );
+            clock_length(
+                &mut self.ch4.length,
+                self.ch4.length_enabled,
+                &mut self.ch4.enabled,
+            );
+        }
+        if self.frame_step == 2 || self.frame_step == 6 {
+            self.ch1.clock_sweep();
+        }
+        if self.frame_step == 7 {
+            self.ch1.envelope.clock();
+            self.ch2.envelope.clock();
+            self.ch4.envelope.clock();
+        }
+        self.frame_step = (self.frame_step + 1) & 7;
+    }
+
+    /// Run the channels for `cycles` T-cycles of the normal-speed clock and
+    /// emit any output samples that fall due.
+    pub fn tick(&mut self, cycles: u32) {
+        if self.powered {
+            self.ch1.tick(cycles);
+            self.ch2.tick(cycles);
+            self.ch3.tick(cycles);
+            self.ch4.tick(cycles);
+        }
+
+        self.sample_clock += cycles as u64 * self.sample_rate as u64;
+        while self.sample_clock >= CLOCK_HZ as u64 {
+            self.sample_clock -= CLOCK_HZ as u64;
+            let [left, right] = self.mix();
+            if self.samples.len() == MAX_BUFFERED_FRAMES * 2 {
+                self.samples.drain(..2);
+            }
+            self.samples.push_back(left);
+            self.samples.push_back(right);
+        }
+    }
+
+    /// One stereo sample: each channel's DAC maps 0-15 to -1.0..=1.0 (or 0
+    /// with the DAC off), NR51 routes them, NR50 scales each side, and the
+    /// high-pass filter removes the DC offset.
+    fn mix(&mut self) -> [f32; 2] {
+        let outputs = [
+            (self.ch1.dac_enabled, self.ch1.output()),
+            (self.ch2.dac_enabled, self.ch2.output()),
+            (self.ch3.dac_enabled, self.ch3.output()),
+            (self.ch4.dac_enabled, self.ch4.output()),
+        ];
+        let nr50 = self.regs[0x14];
+        let nr51 = self.regs[0x15];
+        let mut mixed = [0.0f32; 2];
+        let mut any_dac = false;
+        for (i, &(dac, level)) in outputs.iter().enumerate() {
+            if !dac {
+                continue;
+            }
+            any_dac = true;
+            let analog = 1.0 - level as f32 / 7.5;
+            if nr51 & (0x10 << i) != 0 {
+                mixed[0] += analog;
+            }
+            if nr51 & (0x01 << i) != 0 {
+                mixed[1] += analog;
+            }
+        }
+        let volume = [((nr50 >> 4) & 7) as f32 + 1.0, (nr50 & 7) as f32 + 1.0];
+
+        let charge = 0.999958f32.powf(CLOCK_HZ as f32 / self.sample_rate as f32);
+        let mut out = [0.0f32; 2];
+        for side in 0..2 {
+            let input = mixed[side] / 4.0 * volume[side] / 8.0;
+            if any_dac {
+                out[side] = input - self.capacitor[side];
+                self.capacitor[side] = input - out[side] * charge;
+            }
+        }
+        out
+    }
+
+    /// Feed the register and channel state into `h` (see `GameBoyCore::state_checksum`).
+    pub fn hash_state<H: Hasher>(&self, h: &mut H) {
+        self.regs.hash(h);
+        self.powered.hash(h);
+        self.ch1.hash(h);
+        self.ch2.hash(h);
+        self.ch3.hash(h);
+        self.ch4.hash(h);
+        self.frame_step.hash(h);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Enough T-cycles for `n` frame sequencer steps at 512 Hz.
+    fn run_steps(apu: &mut Apu, n: u32) {
+        for _ in 0..n {
+            apu.tick(CLOCK_HZ / 512);
+            apu.clock_frame_sequencer();
+        }
+    }
+
+    /// Channel 2 at ~1 kHz, 50% duty, full volume, both sides.
+    fn trigger_ch2(apu: &mut Apu, length_enabled: bool) {
+        apu.write(0xFF24, 0x77);
+        apu.write(0xFF25, 0x22);
+        apu.write(0xFF16, 0x80);
+        apu.write(0xFF17, 0xF0);
+        apu.write(0xFF18, 0x83);
+        apu.write(0xFF19, 0x87 | (length_enabled as u8) << 6);
+    }
+
+    #[test]
+    fn test_pulse_channel_produces_audio() {
+        let mut apu = Apu::new();
+        apu.drain_samples();
+        trigger_ch2(&mut apu, false);
+        assert_eq!(apu.read(0xFF26) & 0x02, 0x02);
+
+        apu.tick(CLOCK_HZ / 64);
+        let samples = apu.drain_samples();
+        assert_eq!(samples.len(), 2 * DEFAULT_SAMPLE_RATE as usize / 64);
+        let peak = samples.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!(peak > 0.1, "peak {}", peak);
+        assert!(
+            samples.chunks(2).all(|lr| lr[0] == lr[1]),
+            "NR51 routes ch2 to both"
+        );
+        assert!(apu.drain_samples().is_empty());
+    }
+
+    #[test]
+    fn test_sample_ring_drops_oldest_on_overrun() {
+        let mut apu = Apu::new();
+        trigger_ch2(&mut apu, false);
+        apu.tick(CLOCK_HZ); // a second: far more than the ring holds
+        assert_eq!(apu.samples.len(), MAX_BUFFERED_FRAMES * 2);
+
+        // The newest sample survives the overrun
+        let newest = *apu.samples.back().unwrap();
+        let mut out = vec![0.0; MAX_BUFFERED_FRAMES * 2 + 3];
+        assert_eq!(apu.drain_into(&mut out), MAX_BUFFERED_FRAMES);
+        assert_eq!(out[MAX_BUFFERED_FRAMES * 2 - 1], newest);
+        assert_eq!(apu.drain_into(&mut out), 0);
+    }
+
+    #[test]
+    fn test_length_counter_silences_channel() {
+        let mut apu = Apu::new();
+        trigger_ch2(&mut apu, true);
+        apu.write(0xFF16, 0x80 | 60); // 4 length clocks
+
+        run_steps(&mut apu, 6); // steps 0, 2, 4 clock length
+        assert_eq!(apu.read(0xFF26) & 0x02, 0x02);
+        run_steps(&mut apu, 1); // step 6
+        assert_eq!(apu.read(0xFF26) & 0x02, 0x00);
+    }
+
+    #[test]
+    fn test_sweep_overflow_disables_channel_1() {
+        let mut apu = Apu::new();
+        apu.write(0xFF12, 0xF0);
+        apu.write(0xFF10, 0x11); // period 1, increase, shift 1
+        apu.write(0xFF13, 0x00);
+        apu.write(0xFF14, 0x85); // frequency 0x500, trigger
+        assert_eq!(apu.read(0xFF26) & 0x01, 0x01);
+
+        // Step 2 sweeps to 0x780, whose own target (0xB40) overflows
+        run_steps(&mut apu, 3);
+        assert_eq!(apu.read(0xFF26) & 0x01, 0x00);
+    }
+
+    #[test]
+    fn test_power_off_clears_registers_but_not_wave_ram() {
+        let mut apu = Apu::new();
+        apu.write(0xFF30, 0x12);
+        trigger_ch2(&mut apu, false);
+
+        apu.write(0xFF26, 0x00);
+        assert_eq!(apu.read(0xFF26), 0x70);
+        assert_eq!(apu.read(0xFF24), 0x00);
+        assert_eq!(apu.read(0xFF17), 0x00);
+        assert_eq!(apu.read(0xFF30), 0x12);
+        apu.write(0xFF24, 0x77);
+        assert_eq!(apu.read(0xFF24), 0x00, "writes ignored while off");
+
+        apu.write(0xFF26, 0x80);
+        apu.write(0xFF24, 0x77);
+        assert_eq!(apu.read(0xFF24), 0x77);
+        assert_eq!(apu.read(0xFF11), 0x3F, "length bits read back as 1");
+    }
+
+    #[test]
+    fn test_wave_channel_plays_wave_ram() {
+        let mut apu = Apu::new();
+        for i in 0..16 {
+            apu.write(0xFF30 + i, 0xF0);
+        }
+        apu.write(0xFF1A, 0x80);
+        apu.write(0xFF1C, 0x20); // 100%
+        apu.write(0xFF1D, 0x00);
+        apu.write(0xFF1E, 0x87);
+        assert_eq!(apu.ch3.output(), 0x0F);
+        apu.tick(apu.ch3.period());
+        assert_eq!(apu.ch3.position, 1);
+        assert_eq!(apu.ch3.output(), 0x00);
+
+        apu.write(0xFF1C, 0x40); // 50%
+        apu.tick(apu.ch3.period());
+        assert_eq!(apu.ch3.output(), 0x07);
+    }
+
+    #[test]
+    fn test_noise_short_mode_repeats_every_127_clocks() {
+        let mut apu = Apu::new();
+        apu.write(0xFF21, 0xF0);
+        apu.write(0xFF22, 0x08); // 7-bit LFSR, divisor 8, shift 0
+        apu.write(0xFF23, 0x80);
+        let bits: Vec<u16> = (0..254)
+            .map(|_| {
+                apu.tick(8);
+                apu.ch4.lfsr & 0x7F
+            })
+            .collect();
+        assert_eq!(bits[..127], bits[127..]);
+        assert_ne!(bits[0], bits[1]);
+    }
+}
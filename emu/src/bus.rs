@@ -2,10 +2,13 @@
 //!
 //! The Game Boy's memory map is shared between the CPU, timer, joypad, and
 //! general-purpose RAM/ROM. [`MemoryBus`] intercepts accesses to hardware
-//! register addresses and delegates to the owning component.
+//! register addresses and delegates to the owning component. It can also
+//! carry the PPU, so the CPU can advance it mid-instruction.
 
+use crate::interrupts::InterruptController;
 use crate::joypad::Joypad;
 use crate::memory::Memory;
+use crate::ppu::Ppu;
 use crate::timer::Timer;
 
 /// MemoryBus routes memory accesses to the appropriate component.
@@ -14,6 +17,11 @@ pub struct MemoryBus<'a> {
     memory: &'a mut Memory,
     timer: &'a mut Timer,
     joypad: &'a mut Joypad,
+    ppu: Option<&'a mut Ppu>,
+    /// T-cycles the CPU already advanced the timer by mid-instruction.
+    timer_ticked: u32,
+    /// Dots the CPU already advanced the PPU by mid-instruction.
+    ppu_ticked: u32,
 }
 
 impl<'a> MemoryBus<'a> {
@@ -22,12 +30,58 @@ impl<'a> MemoryBus<'a> {
             memory,
             timer,
             joypad,
+            ppu: None,
+            timer_ticked: 0,
+            ppu_ticked: 0,
         }
     }
 
+    /// Let `tick` advance `ppu` along with the timer.
+    pub fn with_ppu(mut self, ppu: &'a mut Ppu) -> Self {
+        self.ppu = Some(ppu);
+        self
+    }
+
+    /// Advance the timer (and the PPU, if attached) mid-instruction, so bus
+    /// accesses that follow see their state at the right sub-cycle. The
+    /// caller must only tick them by the remainder (see
+    /// `timer_cycles_ticked` and `ppu_dots_ticked`) after the step.
+    #[inline]
+    pub fn tick(&mut self, cycles: u32, interrupts: &InterruptController) {
+        self.timer.tick(cycles, self.memory, interrupts);
+        self.timer_ticked += cycles;
+        if let Some(ppu) = self.ppu.as_deref_mut() {
+            // The PPU does not speed up in double speed
+            let dots = if self.memory.is_double_speed() {
+                cycles / 2
+            } else {
+                cycles
+            };
+            ppu.tick(dots, self.memory, interrupts);
+            self.ppu_ticked += dots;
+        }
+    }
+
+    /// T-cycles already applied to the timer via `tick`.
+    #[inline]
+    pub fn timer_cycles_ticked(&self) -> u32 {
+        self.timer_ticked
+    }
+
+    /// Dots already applied to the PPU via `tick`.
+    #[inline]
+    pub fn ppu_dots_ticked(&self) -> u32 {
+        self.ppu_ticked
+    }
+
     #[inline]
     pub fn read(&self, addr: u16) -> u8 {
         match addr {
+            // An OAM DMA owns the external bus and OAM: only I/O, HRAM and
+            // IE stay readable
+            0x0000..=0xFEFF if self.memory.oam_dma_active() => 0xFF,
+            // VRAM/OAM while the PPU has them locked
+            0x8000..=0xFE9F if self.memory.cpu_access_blocked(addr) => 0xFF,
             // Joypad register
             0xFF00 => self.joypad.read(),
             // Timer registers
@@ -0,0 +1,107 @@
+//! CGB colour conversion.
+//!
+//! Palette RAM holds little-endian RGB555 (`0bbbbbgg gggrrrrr`). Raw
+//! conversion expands each 5-bit channel to 8 bits; CGB correction
+//! approximates how the (dimmer, less saturated) CGB LCD shows the colour.
+
+/// How RGB555 palette colours are mapped to display RGB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorCorrection {
+    /// Plain 5→8 bit expansion: 0x7FFF is pure white.
+    #[default]
+    None,
+    /// Channel mixing after byuu/higan's CGB LCD model: each output channel
+    /// is a weighted sum of the inputs (weights out of 32), clamped to 960
+    /// and divided by 4, so full white becomes 0xF0.
+    Cgb,
+}
+
+/// Convert an RGB555 pair (`lo`, `hi` as stored in palette RAM) to RGBA.
+#[inline]
+pub fn rgb555_to_rgba8888(lo: u8, hi: u8, correction: ColorCorrection) -> [u8; 4] {
+    let r5 = lo & 0x1F;
+    let g5 = ((lo >> 5) | (hi << 3)) & 0x1F;
+    let b5 = (hi >> 2) & 0x1F;
+    match correction {
+        ColorCorrection::None => [expand5(r5), expand5(g5), expand5(b5), 255],
+        ColorCorrection::Cgb => {
+            let (r, g, b) = (r5 as u16, g5 as u16, b5 as u16);
+            let mix = |v: u16| (v.min(960) >> 2) as u8;
+            [
+                mix(r * 26 + g * 4 + b * 2),
+                mix(g * 24 + b * 8),
+                mix(r * 6 + g * 4 + b * 22),
+                255,
+            ]
+        }
+    }
+}
+
+/// Expand a 5-bit channel to 8 bits, replicating the top bits so 31 → 255.
+#[inline]
+fn expand5(v: u8) -> u8 {
+    v << 3 | v >> 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rgb555_black() {
+        let rgba = rgb555_to_rgba8888(0x00, 0x00, ColorCorrection::None);
+        assert_eq!(rgba, [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_rgb555_white() {
+        // RGB555 white: R=31 G=31 B=31
+        // lo = 0xFF (R[4:0]=11111, G[2:0]=111 in bits 7:5)
+        // hi = 0x7F (G[4:3]=11 in bits 1:0, B[4:0]=11111 in bits 6:2)
+        let rgba = rgb555_to_rgba8888(0xFF, 0x7F, ColorCorrection::None);
+        assert_eq!(rgba, [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_rgb555_pure_red() {
+        // R=31, G=0, B=0 → lo = 0x1F, hi = 0x00
+        let rgba = rgb555_to_rgba8888(0x1F, 0x00, ColorCorrection::None);
+        assert_eq!(rgba[0], 0xFF, "red channel");
+        assert_eq!(rgba[1], 0x00, "green channel");
+        assert_eq!(rgba[2], 0x00, "blue channel");
+        assert_eq!(rgba[3], 0xFF, "alpha");
+    }
+
+    #[test]
+    fn test_rgb555_pure_blue() {
+        // R=0, G=0, B=31 → lo = 0x00, hi = 0x7C (B[4:0]=11111 in bits 6:2)
+        let rgba = rgb555_to_rgba8888(0x00, 0x7C, ColorCorrection::None);
+        assert_eq!(rgba[0], 0x00, "red");
+        assert_eq!(rgba[1], 0x00, "green");
+        assert_eq!(rgba[2], 0xFF, "blue");
+        assert_eq!(rgba[3], 0xFF, "alpha");
+    }
+
+    #[test]
+    fn test_cgb_correction() {
+        // R=16 G=8 B=4 → raw 0x1110
+        assert_eq!(
+            rgb555_to_rgba8888(0x10, 0x11, ColorCorrection::None),
+            [132, 66, 33, 255]
+        );
+        // R = (416 + 32 + 8) / 4, G = (192 + 32) / 4, B = (96 + 32 + 88) / 4
+        assert_eq!(
+            rgb555_to_rgba8888(0x10, 0x11, ColorCorrection::Cgb),
+            [114, 56, 54, 255]
+        );
+        // White is clamped, black stays black
+        assert_eq!(
+            rgb555_to_rgba8888(0xFF, 0x7F, ColorCorrection::Cgb),
+            [240, 240, 240, 255]
+        );
+        assert_eq!(
+            rgb555_to_rgba8888(0x00, 0x00, ColorCorrection::Cgb),
+            [0, 0, 0, 255]
+        );
+    }
+}
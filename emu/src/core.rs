@@ -3,16 +3,28 @@
 //! [`GameBoyCore`] owns all emulator components and provides the main
 //! `step_frame` loop, ROM loading, button input, and camera integration.
 
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
 use crate::bus::MemoryBus;
-use crate::cpu::Cpu;
+use crate::color::ColorCorrection;
+use crate::cpu::{Cpu, GbModel};
+use crate::graphics::decode_2bpp_tiles_to_rgba;
 use crate::interrupts::{Interrupt, InterruptController};
-use crate::joypad::Joypad;
-use crate::memory::Memory;
-use crate::ppu::Ppu;
+use crate::joypad::{Button, Joypad};
+use crate::log::{self, LogCategory, LogLevel};
+use crate::log_info;
+use crate::memory::camera::{PhotoError, rgba_to_sensor_gray};
+use crate::memory::{Memory, io};
+use crate::ppu::{ModeEvent, Ppu, ScanlineCallback};
+use crate::printer::{Printer, SharedPrinter};
+use crate::serial::SerialLink;
 use crate::timer::Timer;
 
-const CYCLES_PER_FRAME: u32 = 70_224;
-const CYCLES_PER_FRAME_DOUBLE: u32 = 140_448; // CPU runs 2× but PPU timing unchanged
+/// PPU dots per frame; the PPU runs at this rate in either CPU speed.
+const DOTS_PER_FRAME: u32 = 70_224;
 const FRAME_BUFFER_SIZE: usize = 160 * 144 * 4;
 const CAMERA_BUFFER_SIZE: usize = 128 * 112 * 4;
 
@@ -45,6 +57,137 @@ impl<const N: usize> DoubleBuffer<N> {
     }
 }
 
+/// 64-bit FNV-1a. Unlike `DefaultHasher`, its output is fixed across Rust
+/// versions and platforms, so checksums can be compared between peers.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Fnv1a(0xCBF2_9CE4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= b as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01B3);
+        }
+    }
+}
+
+/// One step of a scripted input sequence (see `GameBoyCore::queue_input`).
+struct InputSegment {
+    /// Bit i set = `Button::from_u8(i)` held (A, B, Select, Start, Right,
+    /// Left, Up, Down).
+    mask: u8,
+    frames: u32,
+}
+
+/// Autofire for one button (see `GameBoyCore::set_turbo`).
+#[derive(Clone, Copy)]
+struct Turbo {
+    /// Frames the button spends pressed, then released, per cycle.
+    half_period: f32,
+    /// Frames since the user pressed the button.
+    held_frames: u32,
+}
+
+/// Address range scanned by `GameBoyCore::memory_search`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: memory_search
+pub(crate) enum MemRegion {
+    /// 0xC000-0xDFFF (the currently mapped WRAM banks).
+    Wram,
+    /// 0xFF80-0xFFFE.
+    Hram,
+    /// WRAM then HRAM.
+    All,
+}
+
+impl MemRegion {
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: memory_search
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "wram" => Some(MemRegion::Wram),
+            "hram" => Some(MemRegion::Hram),
+            "all" => Some(MemRegion::All),
+            _ => None,
+        }
+    }
+
+    fn ranges(self) -> &'static [std::ops::RangeInclusive<u16>] {
+        match self {
+            MemRegion::Wram => &[0xC000..=0xDFFF],
+            MemRegion::Hram => &[0xFF80..=0xFFFE],
+            MemRegion::All => &[0xC000..=0xDFFF, 0xFF80..=0xFFFE],
+        }
+    }
+}
+
+/// Result of `GameBoyCore::run_frame_budgeted`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: run_frame_budgeted
+pub(crate) enum FrameProgress {
+    /// The instruction budget ran out mid-frame; call again to resume.
+    Partial,
+    /// The frame finished and was rendered into the frame buffer.
+    Complete,
+}
+
+/// Why `GameBoyCore::step_until_break` returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: step_until_break
+pub(crate) enum StepResult {
+    /// The instruction budget ran out.
+    Budget,
+    /// The last instruction wrote a watched `value` to `addr`.
+    ValueWatchpoint { addr: u16, value: u8 },
+}
+
+/// Called with the photo slot a ROM-driven camera capture wrote (the
+/// active capture buffer, slot 0).
+pub(crate) type CaptureCallback = Box<dyn FnMut(u8)>;
+
+/// Quote `s` as a JSON string literal.
+#[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: export_state_json
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 || c as u32 >= 0x7F => {
+                out.push_str(&format!("\\u{:04x}", c as u32));
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// A frame started but not yet finished (see `run_frame_budgeted`).
+/// `cycles` counts CPU T-cycles, `dots` PPU time (half the cycles in
+/// double speed).
+struct FrameInProgress {
+    cycles: u32,
+    dots: u32,
+}
+
+/// ROM being assembled from chunks by `begin_rom_load` / `push_rom_chunk`.
+struct PendingRom {
+    data: Vec<u8>,
+    /// Ranges written so far (start → end), kept disjoint.
+    chunks: BTreeMap<usize, usize>,
+    received: usize,
+}
+
 pub(crate) struct GameBoyCore {
     pub(crate) cpu: Cpu,
     pub(crate) memory: Memory,
@@ -57,6 +200,26 @@ pub(crate) struct GameBoyCore {
     pub(crate) frame_count: u32,
     pub(crate) total_cycles: u64,
     pub(crate) instruction_count: u64,
+    pending_rom: Option<PendingRom>,
+    paused: bool,
+    input_queue: VecDeque<InputSegment>,
+    /// The queue set the buttons last frame; release them once it drains.
+    scripted_input_held: bool,
+    /// Buttons the user holds via `set_button` (bit i = `Button::from_u8(i)`).
+    held_buttons: u8,
+    turbo: [Option<Turbo>; 8],
+    frame_in_progress: Option<FrameInProgress>,
+    capture_callback: Option<CaptureCallback>,
+    /// `Memory::camera_capture_count` when the callback was last run.
+    captures_notified: u32,
+    /// PC the CPU starts at after `load_rom` (0x0100 unless overridden).
+    entry_pc: u16,
+    /// Model whose post-boot registers a non-CGB `load_rom` leaves: DMG or
+    /// Pocket/Light (see `set_pocket_model`).
+    dmg_model: GbModel,
+    /// The printer `enable_printer` plugged into the link port, shared with
+    /// `Memory` so the printout can be read back.
+    printer: Option<Rc<RefCell<Printer>>>,
 }
 
 impl GameBoyCore {
@@ -73,6 +236,18 @@ impl GameBoyCore {
             frame_count: 0,
             total_cycles: 0,
             instruction_count: 0,
+            pending_rom: None,
+            paused: false,
+            input_queue: VecDeque::new(),
+            scripted_input_held: false,
+            held_buttons: 0,
+            turbo: [None; 8],
+            frame_in_progress: None,
+            capture_callback: None,
+            captures_notified: 0,
+            entry_pc: 0x0100,
+            dmg_model: GbModel::Dmg,
+            printer: None,
         }
     }
 
@@ -80,51 +255,286 @@ impl GameBoyCore {
         // Memory reset first (validates ROM, resets all hardware registers)
         self.memory.load_rom(rom_data, cgb_mode)?;
         // Reset remaining components to their power-on state
-        self.cpu.reset(cgb_mode);
+        self.cpu.reset(if cgb_mode {
+            GbModel::Cgb
+        } else {
+            self.dmg_model
+        });
+        // An installed boot ROM runs first and hands off at 0x0100 itself
+        let pc = if self.memory.is_boot_rom_mapped() {
+            0x0000
+        } else {
+            self.entry_pc
+        };
+        self.cpu.set_register("pc", pc);
         self.ppu.reset(cgb_mode);
         self.timer = crate::timer::Timer::new();
         self.interrupts = crate::interrupts::InterruptController::new();
         self.joypad = crate::joypad::Joypad::new();
+        self.input_queue.clear();
+        self.scripted_input_held = false;
+        self.held_buttons = 0;
+        self.frame_in_progress = None;
+        self.captures_notified = 0;
         self.frame_count = 0;
         self.total_cycles = 0;
         self.instruction_count = 0;
+        self.memory.set_rtc_paused(self.paused);
+        Ok(())
+    }
+
+    /// Install a DMG (256-byte) or CGB (2304-byte) boot ROM. Every
+    /// subsequent `load_rom` starts at 0x0000 inside it, showing the logo
+    /// scroll, until its write to 0xFF50 unmaps it and the cartridge
+    /// continues at 0x0100. Nothing changes until that `load_rom`, so this
+    /// is safe to call while a game runs. The boot ROM takes precedence
+    /// over `set_entry_pc`, which is ignored while one is installed.
+    #[cfg_attr(not(any(feature = "ios", feature = "wasm")), allow(dead_code))]
+    pub(crate) fn load_boot_rom(&mut self, data: &[u8]) -> Result<(), &'static str> {
+        self.memory.load_boot_rom(data)
+    }
+
+    /// Start execution at `pc` instead of 0x0100 on every subsequent
+    /// `load_rom` (unless a boot ROM is installed, which always starts at
+    /// 0x0000 and hands off at 0x0100), e.g. to skip a game's intro after poking the state it
+    /// expects into memory. Testing/debugging convenience only.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: set_entry_pc
+    pub(crate) fn set_entry_pc(&mut self, pc: u16) {
+        self.entry_pc = pc;
+    }
+
+    /// Start later non-CGB `load_rom`s with a Game Boy Pocket/Light's
+    /// registers (A=0xFF) instead of a DMG's, for games that check.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: set_pocket_model
+    pub(crate) fn set_pocket_model(&mut self, pocket: bool) {
+        self.dmg_model = if pocket { GbModel::Mgb } else { GbModel::Dmg };
+    }
+
+    /// Toggle the slower, more accurate timing paths (see `Cpu::set_accurate`)
+    /// and PPU-mode VRAM/OAM access blocking.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: set_accurate
+    pub(crate) fn set_accurate(&mut self, accurate: bool) {
+        self.cpu.set_accurate(accurate);
+        self.memory.set_access_blocking(accurate);
+    }
+
+    /// Debugger write that ignores PPU-mode VRAM/OAM blocking (see `Memory::poke`).
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: write_byte
+    pub(crate) fn poke(&mut self, addr: u16, value: u8) {
+        self.memory.poke(addr, value);
+    }
+
+    /// Tell the core the frontend has paused (or resumed) emulation.
+    /// Wall-clock driven hardware — the MBC3 RTC — freezes while paused and
+    /// resumes without jumping forward by the paused duration.
+    #[cfg_attr(not(any(feature = "ios", feature = "wasm")), allow(dead_code))]
+    pub(crate) fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+        self.memory.set_rtc_paused(paused);
+    }
+
+    /// Make the MBC3 RTC advance one second every `fps` frames instead of
+    /// following the wall clock, so runs are reproducible; `None` restores
+    /// wall-clock time.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: set_rtc_frames_per_second
+    pub(crate) fn set_rtc_frames_per_second(&mut self, fps: Option<u32>) {
+        self.memory.set_rtc_frames_per_second(fps);
+    }
+
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: is_paused
+    pub(crate) fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Start a chunked ROM load of `total_len` bytes, discarding any
+    /// load already in progress.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: begin_rom_load
+    pub(crate) fn begin_rom_load(&mut self, total_len: usize) {
+        self.pending_rom = Some(PendingRom {
+            data: vec![0; total_len],
+            chunks: BTreeMap::new(),
+            received: 0,
+        });
+    }
+
+    /// Copy one chunk into the pending ROM at `offset`. Chunks may arrive in
+    /// any order; one overlapping bytes already pushed is rejected, so
+    /// `finish_rom_load` can't mistake a repeated chunk for a missing one.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: push_rom_chunk
+    pub(crate) fn push_rom_chunk(
+        &mut self,
+        offset: usize,
+        bytes: &[u8],
+    ) -> Result<(), &'static str> {
+        let pending = self.pending_rom.as_mut().ok_or("No ROM load in progress")?;
+        let end = offset
+            .checked_add(bytes.len())
+            .filter(|&end| end <= pending.data.len())
+            .ok_or("ROM chunk out of range")?;
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        // Only the last chunk starting before `end` can reach past `offset`
+        if pending
+            .chunks
+            .range(..end)
+            .next_back()
+            .is_some_and(|(_, &e)| e > offset)
+        {
+            return Err("ROM chunk overlaps an earlier one");
+        }
+        pending.chunks.insert(offset, end);
+        pending.data[offset..end].copy_from_slice(bytes);
+        pending.received += bytes.len();
         Ok(())
     }
 
-    /// Run one frame of emulation (~16.74ms of Game Boy time).
-    /// Returns the number of instructions executed this frame.
+    /// Finish a chunked load: validates that exactly `total_len` bytes were
+    /// pushed, then loads the assembled ROM as `load_rom` would.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: finish_rom_load
+    pub(crate) fn finish_rom_load(&mut self, cgb_mode: bool) -> Result<(), &'static str> {
+        let pending = self.pending_rom.take().ok_or("No ROM load in progress")?;
+        if pending.received != pending.data.len() {
+            return Err("ROM length mismatch");
+        }
+        self.load_rom(&pending.data, cgb_mode)
+    }
+
+    /// Run one frame of emulation (~16.74ms of Game Boy time) and return
+    /// the number of instructions this call executed. If `run_frame_budgeted`
+    /// left a frame partial, this only finishes that frame, so the count
+    /// covers just its remainder rather than a whole frame.
     pub(crate) fn step_frame(&mut self) -> u32 {
-        let mut cycles_elapsed: u32 = 0;
-        let mut instructions_this_frame: u32 = 0;
+        let start = self.instruction_count;
+        self.run_frame_budgeted(u32::MAX);
+        (self.instruction_count - start) as u32
+    }
 
-        let cycles_per_frame = if self.memory.is_double_speed() {
-            CYCLES_PER_FRAME_DOUBLE
-        } else {
-            CYCLES_PER_FRAME
-        };
-        while cycles_elapsed < cycles_per_frame {
-            let cycles = {
-                let mut bus = MemoryBus::new(&mut self.memory, &mut self.timer, &mut self.joypad);
-                self.cpu.step(&mut bus, &mut self.interrupts)
-            };
+    /// Run at most `max_instructions` instructions of the current frame, so
+    /// a single-threaded frontend can bound the work done per tick. A frame
+    /// interrupted by the budget resumes on the next call; the completed
+    /// frame is identical to one produced by `step_frame`.
+    ///
+    /// A frame ends at V-blank entry, so every frame holds exactly one
+    /// V-blank whatever the CPU speed (or speed switches) within it. With
+    /// the LCD off there are no V-blanks and a frame is `DOTS_PER_FRAME`
+    /// dots instead.
+    pub(crate) fn run_frame_budgeted(&mut self, max_instructions: u32) -> FrameProgress {
+        if self.frame_in_progress.is_none() {
+            self.apply_queued_input();
+            self.apply_turbo();
+            self.ppu.frame_ready(); // drop a V-blank already seen by step_single
+            self.frame_in_progress = Some(FrameInProgress { cycles: 0, dots: 0 });
+        }
+        let mut frame = self.frame_in_progress.take().unwrap();
 
-            self.timer.tick(cycles, &mut self.memory, &self.interrupts);
-            self.ppu.tick(cycles, &mut self.memory, &self.interrupts);
-            if self.ppu.took_hblank_step() {
-                self.memory.tick_hdma_hblank();
+        let mut instructions: u32 = 0;
+        loop {
+            if instructions == max_instructions {
+                self.frame_in_progress = Some(frame);
+                return FrameProgress::Partial;
             }
 
-            cycles_elapsed += cycles;
-            instructions_this_frame += 1;
+            let (cycles, dots) = self.step_components();
+            frame.cycles += cycles;
+            frame.dots += dots;
+            instructions += 1;
             self.instruction_count += 1;
+
+            let lcd_on = self.memory.read_io_direct(io::LCDC) & 0x80 != 0;
+            if self.ppu.frame_ready() || (!lcd_on && frame.dots >= DOTS_PER_FRAME) {
+                break;
+            }
         }
 
-        self.total_cycles += cycles_elapsed as u64;
+        self.total_cycles += frame.cycles as u64;
         self.frame_count += 1;
 
         self.memory.tick_rtc();
         self.render_frame();
-        instructions_this_frame
+        self.notify_captures();
+        FrameProgress::Complete
+    }
+
+    /// Run `frames` frames back to back. Returns the instructions executed.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: run_frames
+    pub(crate) fn run_frames(&mut self, frames: u32) -> u32 {
+        (0..frames).map(|_| self.step_frame()).sum()
+    }
+
+    /// Queue a scripted input segment: hold `buttons` (bit 0-3 = A, B,
+    /// Select, Start) and `dpad` (bit 0-3 = Right, Left, Up, Down) for the
+    /// next `frames` frames after any earlier segments. Every button is
+    /// released once the queue runs dry. For kiosk/demo scripting.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: queue_input
+    pub(crate) fn queue_input(&mut self, buttons: u8, dpad: u8, frames: u32) {
+        if frames > 0 {
+            let mask = (buttons & 0x0F) | (dpad & 0x0F) << 4;
+            self.input_queue.push_back(InputSegment { mask, frames });
+        }
+    }
+
+    /// Set the joypad from the head of the input queue for this frame.
+    fn apply_queued_input(&mut self) {
+        let mask = match self.input_queue.front_mut() {
+            Some(segment) => {
+                segment.frames -= 1;
+                let mask = segment.mask;
+                if segment.frames == 0 {
+                    self.input_queue.pop_front();
+                }
+                self.scripted_input_held = true;
+                mask
+            }
+            None if self.scripted_input_held => {
+                self.scripted_input_held = false;
+                0
+            }
+            None => return,
+        };
+
+        for i in 0..8 {
+            let button = Button::from_u8(i).unwrap();
+            let pressed = mask & (1 << i) != 0;
+            if self.joypad.set_button(button, pressed) {
+                self.interrupts.request(Interrupt::Joypad, &mut self.memory);
+            }
+        }
+    }
+
+    /// Autofire `button` at `rate_hz` presses per second (of 60 Hz frames)
+    /// while the user holds it: pressed for half of each period, released
+    /// for the other half, starting pressed. A rate of 0 turns it off.
+    #[cfg_attr(not(any(feature = "ios", feature = "wasm")), allow(dead_code))]
+    pub(crate) fn set_turbo(&mut self, button: Button, rate_hz: f32) {
+        self.turbo[button as usize] = (rate_hz > 0.0).then(|| Turbo {
+            half_period: 30.0 / rate_hz,
+            held_frames: 0,
+        });
+        if self.held_buttons & 1 << button as u8 != 0 && self.joypad.set_button(button, true) {
+            self.interrupts.request(Interrupt::Joypad, &mut self.memory);
+        }
+    }
+
+    /// Toggle held turbo buttons for this frame.
+    fn apply_turbo(&mut self) {
+        for i in 0..8u8 {
+            let Some(turbo) = &mut self.turbo[i as usize] else {
+                continue;
+            };
+            if self.held_buttons & 1 << i == 0 {
+                turbo.held_frames = 0;
+                continue;
+            }
+            let half_periods = (turbo.held_frames as f32 / turbo.half_period) as u32;
+            let pressed = half_periods.is_multiple_of(2);
+            turbo.held_frames += 1;
+
+            let button = Button::from_u8(i).unwrap();
+            if self.joypad.set_button(button, pressed) {
+                self.interrupts.request(Interrupt::Joypad, &mut self.memory);
+            }
+        }
     }
 
     /// Execute a single CPU instruction, ticking timer and PPU.
@@ -132,16 +542,7 @@ impl GameBoyCore {
     /// Returns the number of T-cycles consumed.
     #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: step_instruction
     pub(crate) fn step_single(&mut self) -> u32 {
-        let cycles = {
-            let mut bus = MemoryBus::new(&mut self.memory, &mut self.timer, &mut self.joypad);
-            self.cpu.step(&mut bus, &mut self.interrupts)
-        };
-
-        self.timer.tick(cycles, &mut self.memory, &self.interrupts);
-        self.ppu.tick(cycles, &mut self.memory, &self.interrupts);
-        if self.ppu.took_hblank_step() {
-            self.memory.tick_hdma_hblank();
-        }
+        let (cycles, _) = self.step_components();
 
         self.total_cycles += cycles as u64;
         self.instruction_count += 1;
@@ -150,20 +551,464 @@ impl GameBoyCore {
             self.frame_count += 1;
             self.render_frame();
         }
+        self.notify_captures();
 
         cycles
     }
 
+    /// Stop `step_until_break` once a write stores exactly `value` at `addr`
+    /// (e.g. a game variable reaching a sentinel). Other values written
+    /// there are ignored.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: add_value_watchpoint
+    pub(crate) fn add_value_watchpoint(&mut self, addr: u16, value: u8) {
+        self.memory.add_value_watchpoint(addr, value);
+    }
+
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: clear_value_watchpoints
+    pub(crate) fn clear_value_watchpoints(&mut self) {
+        self.memory.clear_value_watchpoints();
+    }
+
+    /// Single-step up to `max_instructions` instructions, stopping right
+    /// after one that trips a watchpoint.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: step_until_break
+    pub(crate) fn step_until_break(&mut self, max_instructions: u32) -> StepResult {
+        self.memory.take_watch_hit();
+        for _ in 0..max_instructions {
+            self.step_single();
+            if let Some((addr, value)) = self.memory.take_watch_hit() {
+                return StepResult::ValueWatchpoint { addr, value };
+            }
+        }
+        StepResult::Budget
+    }
+
+    /// Run one CPU instruction and advance the timer, PPU, OAM DMA and HDMA by it.
+    /// Returns the CPU T-cycles and the PPU dots they took: the timer runs
+    /// off the CPU clock, but the PPU does not speed up in double speed.
+    fn step_components(&mut self) -> (u32, u32) {
+        let div_before = self.timer.internal_counter();
+        let (cycles, timer_ticked, ppu_ticked) = {
+            let mut bus = MemoryBus::new(&mut self.memory, &mut self.timer, &mut self.joypad)
+                .with_ppu(&mut self.ppu);
+            let cycles = self.cpu.step(&mut bus, &mut self.interrupts);
+            (cycles, bus.timer_cycles_ticked(), bus.ppu_dots_ticked())
+        };
+        self.memory.tick_oam_dma(cycles);
+
+        self.timer
+            .tick(cycles - timer_ticked, &mut self.memory, &self.interrupts);
+        if self.memory.tick_serial(cycles) {
+            self.interrupts.request(Interrupt::Serial, &mut self.memory);
+        }
+        let dots = if self.memory.is_double_speed() {
+            cycles / 2
+        } else {
+            cycles
+        };
+        self.ppu
+            .tick(dots - ppu_ticked, &mut self.memory, &self.interrupts);
+
+        // The APU frame sequencer steps on DIV bit 4 falling (bit 5 in
+        // double speed, keeping it at 512 Hz), including DIV resets
+        let bit = if self.memory.is_double_speed() {
+            13
+        } else {
+            12
+        };
+        let div_after = self.timer.internal_counter();
+        let sequencer = (div_before >> bit) & 1 == 1 && (div_after >> bit) & 1 == 0;
+        self.memory.tick_apu(dots, sequencer);
+        if self.ppu.took_hblank_step() {
+            self.memory.tick_hdma_hblank();
+        }
+        (cycles, dots)
+    }
+
     fn render_frame(&mut self) {
         // PPU writes RGBA directly — just copy the completed scanlines into the front buffer.
-        self.frame_buffer.back_mut().copy_from_slice(self.ppu.get_buffer());
+        self.frame_buffer
+            .back_mut()
+            .copy_from_slice(self.ppu.get_buffer());
         self.frame_buffer.swap();
     }
 
+    /// Hash of all deterministic machine state: CPU registers, RAM regions,
+    /// I/O, PPU/timer counters and cartridge banking. Two cores fed the same
+    /// ROM and inputs report the same value every frame (for lockstep netplay).
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: state_checksum
+    pub(crate) fn state_checksum(&self) -> u64 {
+        let mut h = Fnv1a::new();
+        self.cpu.hash_state(&mut h);
+        self.memory.hash_state(&mut h);
+        self.ppu.hash_state(&mut h);
+        self.timer.hash_state(&mut h);
+        self.joypad.hash_state(&mut h);
+        self.frame_count.hash(&mut h);
+        self.total_cycles.hash(&mut h);
+        h.finish()
+    }
+
+    /// Cheat search: every address in `region` currently holding `value`.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: memory_search
+    pub(crate) fn memory_search(&self, value: u8, region: MemRegion) -> Vec<u16> {
+        region
+            .ranges()
+            .iter()
+            .flat_map(|range| range.clone())
+            .filter(|&addr| self.memory.peek(addr) == value)
+            .collect()
+    }
+
+    /// Narrow an earlier search to the addresses that now hold `value`.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: memory_search_refine
+    pub(crate) fn memory_search_refine(&self, prev: &[u16], value: u8) -> Vec<u16> {
+        prev.iter()
+            .copied()
+            .filter(|&addr| self.memory.peek(addr) == value)
+            .collect()
+    }
+
+    /// Let debugger reads (`Memory::peek`) see cartridge RAM regardless of
+    /// the game's RAM-enable register. The register itself is untouched.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: set_ram_enable_override
+    pub(crate) fn set_ram_enable_override(&mut self, value: Option<bool>) {
+        self.memory.set_ram_enable_override(value);
+    }
+
+    /// Allocate `Some(bytes)` of cartridge RAM at the next `load_rom` instead
+    /// of the header's size, or `None` to follow the header (camera excluded).
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: override_ram_size
+    pub(crate) fn override_ram_size(&mut self, bytes: Option<usize>) {
+        self.memory.set_ram_size_override(bytes);
+    }
+
+    /// Whether the loaded cartridge has a battery (see `Cartridge::is_battery_backed`).
+    pub(crate) fn has_battery(&self) -> bool {
+        self.memory.has_battery()
+    }
+
+    /// Serial bytes appended since `cursor`, plus the cursor to pass next
+    /// time. A cursor past the end means the log was cleared, so everything
+    /// currently buffered is returned.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: take_serial_since
+    pub(crate) fn take_serial_since(&self, cursor: usize) -> (Vec<u8>, usize) {
+        let output = self.memory.serial_output();
+        let start = if cursor > output.len() { 0 } else { cursor };
+        (output[start..].to_vec(), output.len())
+    }
+
+    /// SHA-1 of the loaded ROM as lowercase hex, a stable identifier for
+    /// database lookups and save keys.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: rom_sha1
+    pub(crate) fn rom_sha1_hex(&self) -> String {
+        crate::sha1::to_hex(&self.memory.rom_sha1())
+    }
+
+    /// Cartridge RAM to write to a `.sav`. Empty (and logged) for carts
+    /// without a battery, whose RAM would not survive power-off anyway.
+    #[cfg_attr(not(feature = "ios"), allow(dead_code))] // ios: gb_get_save_data
+    pub(crate) fn save_data(&self) -> &[u8] {
+        if !self.has_battery() {
+            log_info!(
+                LogCategory::Memory,
+                "Cartridge has no battery; nothing to save"
+            );
+            return &[];
+        }
+        self.memory.get_cartridge_ram()
+    }
+
+    /// Save data in the common `.sav` layout, including the MBC3 RTC footer
+    /// so saves move between emulators. Empty for carts without a battery.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: export_ram_with_rtc
+    pub(crate) fn export_ram_with_rtc(&self) -> Vec<u8> {
+        if !self.has_battery() {
+            log_info!(
+                LogCategory::Memory,
+                "Cartridge has no battery; nothing to save"
+            );
+            return Vec::new();
+        }
+        self.memory.export_cartridge_ram_with_rtc()
+    }
+
+    /// Load a `.sav`, restoring the MBC3 RTC (registers and timestamp) when present.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: load_ram_with_rtc
+    pub(crate) fn load_ram_with_rtc(&mut self, data: &[u8]) {
+        self.memory.import_cartridge_ram_with_rtc(data);
+    }
+
+    /// Plug a peer into the link cable port (`None` unplugs it).
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: record_serial_link
+    pub(crate) fn set_serial_link(&mut self, link: Option<Box<dyn SerialLink>>) {
+        self.printer = None;
+        self.memory.set_serial_link(link);
+    }
+
+    /// Plug a Game Boy Printer into the link cable port (`false` unplugs
+    /// whatever is attached).
+    #[cfg_attr(not(any(feature = "ios", feature = "wasm")), allow(dead_code))]
+    pub(crate) fn enable_printer(&mut self, enable: bool) {
+        self.printer = enable.then(|| Rc::new(RefCell::new(Printer::new())));
+        let link = self.printer.clone().map(SharedPrinter::new);
+        self.memory
+            .set_serial_link(link.map(|p| Box::new(p) as Box<dyn SerialLink>));
+    }
+
+    /// The printer's current printout as 160-pixel-wide RGBA rows (empty
+    /// with no printer attached or nothing printed yet).
+    #[cfg_attr(not(any(feature = "ios", feature = "wasm")), allow(dead_code))]
+    pub(crate) fn printer_image(&self) -> Vec<u8> {
+        self.printer
+            .as_ref()
+            .map(|p| p.borrow().image_rgba())
+            .unwrap_or_default()
+    }
+
+    /// Height in pixels of `printer_image`.
+    #[cfg_attr(not(any(feature = "ios", feature = "wasm")), allow(dead_code))]
+    pub(crate) fn printer_image_height(&self) -> usize {
+        self.printer
+            .as_ref()
+            .map_or(0, |p| p.borrow().image_height())
+    }
+
+    /// Run `callback` once for every camera capture the ROM completes,
+    /// checked at the end of each frame (and each `step_single`), so
+    /// frontends need not poll `is_camera_capture_dirty`.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: set_capture_callback
+    pub(crate) fn set_capture_callback(&mut self, callback: Option<CaptureCallback>) {
+        self.capture_callback = callback;
+        self.captures_notified = self.memory.camera_capture_count();
+    }
+
+    fn notify_captures(&mut self) {
+        let count = self.memory.camera_capture_count();
+        if count == self.captures_notified {
+            return;
+        }
+        let new = count.wrapping_sub(self.captures_notified);
+        self.captures_notified = count;
+        if let Some(callback) = self.capture_callback.as_mut() {
+            for _ in 0..new {
+                callback(0);
+            }
+        }
+    }
+
+    /// Run `callback` after every rendered scanline with LY and the scroll,
+    /// window and palette registers in effect (raster-effect debugging).
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: set_scanline_callback
+    pub(crate) fn set_scanline_callback(&mut self, callback: Option<ScanlineCallback>) {
+        self.ppu.set_scanline_callback(callback);
+    }
+
+    /// Record every PPU mode change with its dot and line, for diagnosing
+    /// STAT interrupt timing. Enabling clears the previous trace.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: set_ppu_mode_trace
+    pub(crate) fn set_ppu_mode_trace(&mut self, enabled: bool) {
+        self.ppu.set_mode_trace(enabled);
+    }
+
+    /// The recorded PPU mode changes, oldest first.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: ppu_mode_trace
+    pub(crate) fn ppu_mode_trace(&self) -> Vec<ModeEvent> {
+        self.ppu.mode_trace()
+    }
+
+    /// Shade BG/OBJ via BGP/OBP in grayscale while a CGB-mode ROM has not
+    /// written colour palette RAM (see `Ppu::set_cgb_dmg_fallback`).
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: set_cgb_dmg_fallback
+    pub(crate) fn set_cgb_dmg_fallback(&mut self, enabled: bool) {
+        self.ppu.set_cgb_dmg_fallback(enabled);
+    }
+
+    /// Choose how CGB palette colours are converted for display.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: set_color_correction
+    pub(crate) fn set_color_correction(&mut self, correction: ColorCorrection) {
+        self.ppu.set_color_correction(correction);
+    }
+
+    /// Silence or re-enable a log category at runtime. Log filters are
+    /// process-wide, so this affects every core.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: set_log_enabled
+    pub(crate) fn set_log_category_enabled(&self, category: LogCategory, enabled: bool) {
+        log::set_category_enabled(category, enabled);
+    }
+
+    /// Set the most verbose log level emitted (process-wide).
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: set_log_level
+    pub(crate) fn set_log_level(&self, level: LogLevel) {
+        log::set_level(level);
+    }
+
+    /// Blend each pixel toward its previous level to mimic DMG LCD ghosting
+    /// (see `Ppu::set_lcd_ghosting`); 0 disables it.
+    #[cfg_attr(not(any(feature = "ios", feature = "wasm")), allow(dead_code))]
+    pub(crate) fn set_lcd_ghosting(&mut self, factor: f32) {
+        self.ppu.set_lcd_ghosting(factor);
+    }
+
+    /// Install an output colour LUT from 1024 bytes (256 RGBA entries), or
+    /// clear it with `None`. Shared by the WASM and FFI frontends.
+    pub(crate) fn set_output_lut(&mut self, bytes: Option<&[u8]>) -> Result<(), &'static str> {
+        let lut = match bytes {
+            Some(b) if b.len() == 256 * 4 => Some(std::array::from_fn(|v| {
+                b[v * 4..v * 4 + 4].try_into().unwrap()
+            })),
+            Some(_) => return Err("LUT must be 1024 bytes"),
+            None => None,
+        };
+        self.ppu.set_output_lut(lut);
+        Ok(())
+    }
+
+    /// Overwrite one OAM entry (see `Memory::set_sprite`), e.g. for overlays.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: set_sprite
+    pub(crate) fn set_sprite(&mut self, index: u8, y: u8, x: u8, tile: u8, attr: u8) {
+        self.memory.set_sprite(index, y, x, tile, attr);
+    }
+
+    /// Multi-line snapshot of CPU, PPU, I/O and cartridge state plus the tail
+    /// of the serial log, for pasting into bug reports.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: debug_dump
+    pub(crate) fn debug_dump(&self) -> String {
+        const SERIAL_TAIL: usize = 256;
+
+        let serial = self.memory.get_serial_output_string();
+        let skip = serial.chars().count().saturating_sub(SERIAL_TAIL);
+        let serial_tail: String = serial.chars().skip(skip).collect();
+
+        format!(
+            "frame={} cycles={} instrs={} cgb={} double_speed={} paused={}\n\
+             CPU: {}\n\
+             PPU: {}\n\
+             IO: {}\n\
+             Timer: counter={:04X} TIMA={:02X} TMA={:02X} TAC={:02X}\n\
+             Cart: {} rom_banks={} ram_size={}\n\
+             Serial (last {} chars): {:?}\n",
+            self.frame_count,
+            self.total_cycles,
+            self.instruction_count,
+            self.memory.is_cgb_mode(),
+            self.memory.is_double_speed(),
+            self.paused,
+            self.cpu.get_debug_state(),
+            self.ppu.get_debug_state(),
+            self.memory.get_io_state(),
+            self.timer.internal_counter(),
+            self.timer.read(0xFF05),
+            self.timer.read(0xFF06),
+            self.timer.read(0xFF07),
+            self.memory.get_debug_state(),
+            self.memory.get_rom_bank_count(),
+            self.memory.get_cartridge_ram().len(),
+            SERIAL_TAIL,
+            serial_tail,
+        )
+    }
+
+    /// The same state as `debug_dump` as a JSON document, for tools and
+    /// bug reports. Inspection only: it cannot be loaded back.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: export_state_json
+    pub(crate) fn export_state_json(&self) -> String {
+        let cpu = self.cpu.get_debug_state();
+        let ppu = self.ppu.get_debug_state();
+        let io = self.memory.get_io_state();
+        let cart = self.memory.get_debug_state();
+        let title: String = (0x0134..0x0144)
+            .map(|addr| self.memory.read(addr))
+            .take_while(|&b| b != 0)
+            .map(|b| b as char)
+            .collect();
+
+        format!(
+            r#"{{
+  "frame": {},
+  "cycles": {},
+  "instructions": {},
+  "cgb": {},
+  "double_speed": {},
+  "paused": {},
+  "cpu": {{"pc": {}, "sp": {}, "a": {}, "f": {}, "bc": {}, "de": {}, "hl": {}, "ime": {}, "halted": {}}},
+  "io": {{"lcdc": {}, "stat": {}, "ly": {}, "ie": {}, "if": {}, "scy": {}, "scx": {}, "bgp": {}}},
+  "ppu": {{"mode": {}, "mode_name": "{}", "line": {}, "cycles": {}, "window_line": {}}},
+  "timer": {{"counter": {}, "tima": {}, "tma": {}, "tac": {}}},
+  "cart": {{"title": {}, "mbc": "{:?}", "rom_banks": {}, "ram_size": {}, "rom_bank": {}, "ram_bank": {}, "ram_enabled": {}, "battery": {}}}
+}}
+"#,
+            self.frame_count,
+            self.total_cycles,
+            self.instruction_count,
+            self.memory.is_cgb_mode(),
+            self.memory.is_double_speed(),
+            self.paused,
+            cpu.pc,
+            cpu.sp,
+            cpu.a,
+            cpu.f,
+            cpu.bc,
+            cpu.de,
+            cpu.hl,
+            cpu.ime,
+            cpu.halted,
+            io.lcdc,
+            io.stat,
+            io.ly,
+            io.ie,
+            io.if_reg,
+            io.scy,
+            io.scx,
+            io.bgp,
+            ppu.mode,
+            ppu.mode_name,
+            ppu.line,
+            ppu.cycles,
+            ppu.window_line_counter,
+            self.timer.internal_counter(),
+            self.timer.read(0xFF05),
+            self.timer.read(0xFF06),
+            self.timer.read(0xFF07),
+            json_string(&title),
+            cart.mbc_type,
+            self.memory.get_rom_bank_count(),
+            self.memory.get_cartridge_ram().len(),
+            cart.rom_bank,
+            cart.ram_bank,
+            cart.ram_enabled,
+            self.has_battery(),
+        )
+    }
+
     pub(crate) fn set_button(&mut self, button: u8, pressed: bool) {
         if let Some(btn) = crate::joypad::Button::from_u8(button) {
-            self.joypad.set_button(btn, pressed);
             if pressed {
+                self.held_buttons |= 1 << button;
+            } else {
+                self.held_buttons &= !(1 << button);
+            }
+            if self.joypad.set_button(btn, pressed) {
+                self.interrupts.request(Interrupt::Joypad, &mut self.memory);
+            }
+        }
+    }
+
+    /// Drive the D-pad from an analog stick. `x`/`y` are in -1.0..=1.0 with
+    /// +x = right and +y = down (the browser Gamepad convention). Each axis
+    /// presses its direction once it leaves `deadzone`, so diagonals press
+    /// two directions.
+    #[cfg_attr(not(any(feature = "ios", feature = "wasm")), allow(dead_code))]
+    pub(crate) fn set_analog_dpad(&mut self, x: f32, y: f32, deadzone: f32) {
+        let deadzone = deadzone.abs();
+        let directions = [
+            (Button::Right, x > deadzone),
+            (Button::Left, x < -deadzone),
+            (Button::Up, y < -deadzone),
+            (Button::Down, y > deadzone),
+        ];
+        for (button, pressed) in directions {
+            if self.joypad.set_button(button, pressed) {
                 self.interrupts.request(Interrupt::Joypad, &mut self.memory);
             }
         }
@@ -173,6 +1018,21 @@ impl GameBoyCore {
         self.memory.set_camera_image(data);
     }
 
+    /// Feed the sensor an RGBA frame of any size (e.g. straight from a
+    /// canvas): converted to luma and scaled to 128×112 here.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: set_camera_image_rgba
+    pub(crate) fn set_camera_image_rgba(
+        &mut self,
+        rgba: &[u8],
+        width: usize,
+        height: usize,
+    ) -> Result<(), &'static str> {
+        let gray = rgba_to_sensor_gray(rgba, width, height)
+            .ok_or("RGBA buffer too small for width×height")?;
+        self.memory.set_camera_image(&gray);
+        Ok(())
+    }
+
     pub(crate) fn is_camera_cartridge(&self) -> bool {
         self.memory.get_mbc_type() == crate::memory::MbcType::PocketCamera
     }
@@ -187,31 +1047,8 @@ impl GameBoyCore {
         }
         self.memory.clear_camera_capture_dirty();
 
-        let sram = self.memory.camera_capture_sram();
-        let palette: [u8; 4] = [0xFF, 0xAA, 0x55, 0x00];
-        let buf = self.camera_live_buffer.back_mut();
-
-        for tile_y in 0..14 {
-            for tile_x in 0..16 {
-                let tile_offset = (tile_y * 16 + tile_x) * 16;
-                for row in 0..8 {
-                    let low = sram[tile_offset + row * 2];
-                    let high = sram[tile_offset + row * 2 + 1];
-                    for col in 0..8 {
-                        let bit = 7 - col;
-                        let color_idx = ((high >> bit) & 1) << 1 | ((low >> bit) & 1);
-                        let gray = palette[color_idx as usize];
-                        let px = tile_x * 8 + col;
-                        let py = tile_y * 8 + row;
-                        let i = (py * 128 + px) * 4;
-                        buf[i] = gray;
-                        buf[i + 1] = gray;
-                        buf[i + 2] = gray;
-                        buf[i + 3] = 255;
-                    }
-                }
-            }
-        }
+        let rgba = decode_2bpp_tiles_to_rgba(self.memory.camera_capture_sram(), 16, 14);
+        self.camera_live_buffer.back_mut().copy_from_slice(&rgba);
         self.camera_live_buffer.swap();
         true
     }
@@ -225,13 +1062,940 @@ impl GameBoyCore {
         self.memory.encode_camera_photo(slot, rgba)
     }
 
+    #[cfg_attr(not(any(feature = "ios", feature = "wasm")), allow(dead_code))]
+    pub(crate) fn try_decode_camera_photo(&self, slot: u8) -> Result<Vec<u8>, PhotoError> {
+        self.memory.try_decode_camera_photo(slot)
+    }
+
+    #[cfg_attr(not(any(feature = "ios", feature = "wasm")), allow(dead_code))]
+    pub(crate) fn try_encode_camera_photo(
+        &mut self,
+        slot: u8,
+        rgba: &[u8],
+    ) -> Result<(), PhotoError> {
+        self.memory.try_encode_camera_photo(slot, rgba)
+    }
+
     #[cfg_attr(not(feature = "ios"), allow(dead_code))] // ios: gb_clear_camera_photo_slot
     pub(crate) fn clear_camera_photo_slot(&mut self, slot: u8) {
         self.memory.clear_camera_photo_slot(slot)
     }
 
+    /// Erase all 30 saved photos, keeping the active capture.
+    #[cfg_attr(not(any(feature = "ios", feature = "wasm")), allow(dead_code))]
+    pub(crate) fn clear_camera_roll(&mut self) {
+        self.memory.clear_camera_roll()
+    }
+
     #[cfg_attr(not(feature = "ios"), allow(dead_code))] // ios: gb_camera_photo_count
     pub(crate) fn camera_photo_count(&self) -> u8 {
         self.memory.camera_photo_count()
     }
+
+    /// Occupied photo slots: bit i set = slot i+1 holds a photo.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: camera_occupancy
+    pub(crate) fn camera_occupancy(&self) -> u32 {
+        self.memory.camera_occupancy()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_test_rom() -> Vec<u8> {
+        let mut rom: Vec<u8> = (0..0x8000).map(|i| (i * 7 + 3) as u8).collect();
+        rom[0x0147] = 0x00; // ROM only
+        rom
+    }
+
+    /// ROM that selects the d-pad and copies P1 into WRAM forever.
+    fn make_joypad_rom() -> Vec<u8> {
+        let mut rom = vec![0u8; 0x8000];
+        let code = [
+            0x3E, 0x20, // LD A, 0x20 (select d-pad)
+            0xE0, 0x00, // LDH (0x00), A
+            0xF0, 0x00, // loop: LDH A, (0x00)
+            0xEA, 0x00, 0xC0, // LD (0xC000), A
+            0x18, 0xF9, // JR loop
+        ];
+        rom[0x100..0x100 + code.len()].copy_from_slice(&code);
+        rom
+    }
+
+    /// Golden-ROM harness: the camera test ROM from the `rom` crate drives
+    /// the sensor through real CPU writes to the camera registers. A fixed
+    /// frame count (the first capture lands on frame 4) keeps it deterministic.
+    fn run_film_rom(frames: u32) -> GameBoyCore {
+        let rom = gb_film::build_rom(&gb_film::CameraConfig::default());
+        let mut core = GameBoyCore::new();
+        core.load_rom(&rom, false).unwrap();
+        let gradient: Vec<u8> = (0..128 * 112).map(|i| ((i % 128) * 2) as u8).collect();
+        core.set_camera_image(&gradient);
+        assert!(core.memory.camera_capture_sram().iter().all(|&b| b == 0));
+        for _ in 0..frames {
+            core.step_frame();
+        }
+        core
+    }
+
+    #[test]
+    fn test_film_rom_captures_gradient() {
+        let core = run_film_rom(10);
+        assert!(
+            core.memory.is_camera_capture_dirty(),
+            "ROM never completed a capture"
+        );
+        let tiles = core.memory.camera_capture_sram();
+        assert!(tiles.iter().any(|&b| b != 0), "capture SRAM still blank");
+
+        // Gradient runs dark (left) to light (right): first tile column
+        // is darker than the last one.
+        let rgba = decode_2bpp_tiles_to_rgba(tiles, 16, 14);
+        assert!(
+            rgba[0] < rgba[127 * 4],
+            "left {} right {}",
+            rgba[0],
+            rgba[127 * 4]
+        );
+
+        let again = run_film_rom(10);
+        assert_eq!(
+            again.memory.camera_capture_sram(),
+            tiles,
+            "capture is deterministic"
+        );
+    }
+
+    #[test]
+    fn test_queued_input_holds_for_exact_frames() {
+        let mut core = GameBoyCore::new();
+        core.load_rom(&make_joypad_rom(), false).unwrap();
+        core.queue_input(0x01, 0x00, 5); // A
+        core.queue_input(0x00, 0x01, 2); // then Right
+
+        let mut held = Vec::new();
+        for _ in 0..9 {
+            core.step_frame();
+            held.push((
+                core.joypad.is_pressed(Button::A),
+                core.joypad.is_pressed(Button::Right),
+            ));
+        }
+        let a = (true, false);
+        let right = (false, true);
+        let none = (false, false);
+        assert_eq!(held, vec![a, a, a, a, a, right, right, none, none]);
+    }
+
+    #[test]
+    fn test_state_checksum_lockstep() {
+        let rom = make_joypad_rom();
+        let mut a = GameBoyCore::new();
+        let mut b = GameBoyCore::new();
+        a.load_rom(&rom, false).unwrap();
+        b.load_rom(&rom, false).unwrap();
+        assert_eq!(a.state_checksum(), b.state_checksum());
+
+        for frame in 0..10 {
+            let pressed = frame % 3 == 0;
+            a.set_button(4, pressed);
+            b.set_button(4, pressed);
+            a.step_frame();
+            b.step_frame();
+            assert_eq!(a.state_checksum(), b.state_checksum(), "frame {}", frame);
+        }
+
+        // Diverging input diverges the checksum
+        a.set_button(7, true);
+        a.step_frame();
+        b.step_frame();
+        assert_ne!(a.state_checksum(), b.state_checksum());
+        assert_ne!(a.memory.read(0xC000), b.memory.read(0xC000));
+    }
+
+    #[test]
+    fn test_debug_dump_contains_key_state() {
+        let mut rom = make_joypad_rom();
+        rom[0x0147] = 0x13; // MBC3+RAM+BATTERY
+        let mut core = GameBoyCore::new();
+        core.load_rom(&rom, false).unwrap();
+        core.step_frame();
+
+        let dump = core.debug_dump();
+        let pc = format!("PC={:04X}", core.cpu.get_debug_state().pc);
+        assert!(dump.contains(&pc), "{}", dump);
+        assert!(dump.contains("MBC=Mbc3"), "{}", dump);
+        assert!(dump.contains("mode="), "{}", dump);
+        assert!(dump.contains("LCDC="), "{}", dump);
+    }
+
+    #[test]
+    fn test_frame_driven_rtc() {
+        let mut rom = make_joypad_rom();
+        rom[0x0147] = 0x10; // MBC3+TIMER+RAM+BATTERY
+        let mut core = GameBoyCore::new();
+        core.set_rtc_frames_per_second(Some(60));
+        core.load_rom(&rom, false).unwrap();
+        core.memory.write(0x0000, 0x0A);
+
+        let seconds = |core: &mut GameBoyCore| {
+            core.memory.write(0x6000, 0x00);
+            core.memory.write(0x6000, 0x01);
+            core.memory.write(0x4000, 0x08);
+            core.memory.read(0xA000)
+        };
+        assert_eq!(seconds(&mut core), 0);
+        core.run_frames(120);
+        assert_eq!(seconds(&mut core), 2);
+        core.run_frames(59);
+        assert_eq!(seconds(&mut core), 2, "partial seconds carry over");
+        core.run_frames(1);
+        assert_eq!(seconds(&mut core), 3);
+    }
+
+    #[test]
+    fn test_ram_with_rtc_round_trip() {
+        let mut rom = make_test_rom();
+        rom[0x0147] = 0x10; // MBC3+TIMER+RAM+BATTERY
+        rom[0x0149] = 0x03; // 32KB
+        let mut core = GameBoyCore::new();
+        core.load_rom(&rom, false).unwrap();
+        core.memory.write(0x0000, 0x0A);
+        core.memory.write(0xA000, 0x5A);
+        core.memory.write(0x4000, 0x08); // RTC seconds
+        core.memory.write(0xA000, 42);
+        core.memory.write(0x4000, 0x0B); // RTC day low
+        core.memory.write(0xA000, 200);
+
+        let save = core.export_ram_with_rtc();
+        assert_eq!(save.len(), 32 * 1024 + 48);
+
+        let mut other = GameBoyCore::new();
+        other.load_rom(&rom, false).unwrap();
+        other.load_ram_with_rtc(&save);
+        assert_eq!(
+            other.export_ram_with_rtc(),
+            save,
+            "RAM, RTC and timestamp preserved"
+        );
+        assert_eq!(other.memory.get_cartridge_ram()[0], 0x5A);
+
+        // Latch and read the restored clock through the bus
+        other.memory.write(0x0000, 0x0A);
+        other.memory.write(0x6000, 0x00);
+        other.memory.write(0x6000, 0x01);
+        other.memory.write(0x4000, 0x08);
+        assert_eq!(other.memory.read(0xA000), 42);
+        other.memory.write(0x4000, 0x0B);
+        assert_eq!(other.memory.read(0xA000), 200);
+    }
+
+    /// ROM that sends 0..4 over the link (internal clock) and stores each
+    /// received byte at 0xC000.., then spins.
+    fn make_serial_rom() -> Vec<u8> {
+        let mut rom = vec![0u8; 0x8000];
+        let code = [
+            0x21, 0x00, 0xC0, // LD HL, 0xC000
+            0x06, 0x00, // LD B, 0
+            0x78, // loop: LD A, B
+            0xE0, 0x01, // LDH (SB), A
+            0x3E, 0x81, // LD A, 0x81
+            0xE0, 0x02, // LDH (SC), A
+            0xF0, 0x02, // wait: LDH A, (SC)
+            0xE6, 0x80, // AND 0x80
+            0x20, 0xFA, // JR NZ, wait
+            0xF0, 0x01, // LDH A, (SB)
+            0x22, // LD (HL+), A
+            0x04, // INC B
+            0x78, // LD A, B
+            0xFE, 0x04, // CP 4
+            0x20, 0xEA, // JR NZ, loop
+            0x18, 0xFE, // JR -2
+        ];
+        rom[0x100..0x100 + code.len()].copy_from_slice(&code);
+        rom
+    }
+
+    #[test]
+    fn test_serial_record_then_replay() {
+        use crate::serial::{RecordingSerialLink, ReplaySerialLink};
+
+        let rom = make_serial_rom();
+        let mut recorded = GameBoyCore::new();
+        recorded.load_rom(&rom, false).unwrap();
+        let peer = |b: u8| b.wrapping_mul(3) ^ 0x5A;
+        recorded.set_serial_link(Some(Box::new(RecordingSerialLink::new(Box::new(peer)))));
+        recorded.step_frame();
+
+        let transcript = recorded.memory.serial_link().unwrap().transcript().to_vec();
+        assert_eq!(transcript.len(), 4);
+        for (i, &(sent, received)) in transcript.iter().enumerate() {
+            assert_eq!(sent, i as u8);
+            assert_eq!(received, peer(i as u8));
+            assert_eq!(recorded.memory.read(0xC000 + i as u16), received);
+        }
+
+        let mut replayed = GameBoyCore::new();
+        replayed.load_rom(&rom, false).unwrap();
+        replayed.set_serial_link(Some(Box::new(ReplaySerialLink::from_transcript(
+            &transcript,
+        ))));
+        replayed.step_frame();
+        assert_eq!(
+            replayed.memory.serial_link().unwrap().transcript(),
+            &transcript[..]
+        );
+        for addr in 0xC000..0xC004 {
+            assert_eq!(replayed.memory.read(addr), recorded.memory.read(addr));
+        }
+
+        // No cable: every received byte is 0xFF
+        let mut unplugged = GameBoyCore::new();
+        unplugged.load_rom(&rom, false).unwrap();
+        unplugged.step_frame();
+        assert_eq!(unplugged.memory.read(0xC000), 0xFF);
+    }
+
+    #[test]
+    fn test_serial_transfer_takes_eight_bit_times() {
+        let mut core = GameBoyCore::new();
+        core.load_rom(&vec![0u8; 0x8000], false).unwrap(); // NOPs, 4 cycles each
+        core.memory.write_io_direct(io::IF, 0x00);
+        core.memory.write(0xFF01, 0x42);
+        core.memory.write(0xFF02, 0x81); // start, internal clock
+
+        for _ in 0..(8 * 512 / 4 - 1) {
+            core.step_single();
+        }
+        assert_eq!(core.memory.read(0xFF02) & 0x80, 0x80, "still shifting");
+        assert_eq!(core.memory.read_io_direct(io::IF) & 0x08, 0);
+        assert_eq!(core.memory.read(0xFF01), 0x42);
+
+        core.step_single();
+        assert_eq!(core.memory.read(0xFF02) & 0x80, 0);
+        assert_eq!(
+            core.memory.read_io_direct(io::IF) & 0x08,
+            0x08,
+            "Serial interrupt"
+        );
+        assert_eq!(core.memory.read(0xFF01), 0xFF, "no cable shifts in 1s");
+
+        // External clock with nothing attached never completes
+        core.memory.write(0xFF02, 0x80);
+        for _ in 0..2 * 8 * 512 / 4 {
+            core.step_single();
+        }
+        assert_eq!(core.memory.read(0xFF02) & 0x80, 0x80);
+    }
+
+    #[test]
+    fn test_chunked_rom_load_matches_single_shot() {
+        let rom = make_test_rom();
+
+        let mut single = GameBoyCore::new();
+        single.load_rom(&rom, false).unwrap();
+
+        let mut chunked = GameBoyCore::new();
+        chunked.begin_rom_load(rom.len());
+        // Out of order, uneven chunk sizes
+        chunked.push_rom_chunk(0x5000, &rom[0x5000..]).unwrap();
+        chunked.push_rom_chunk(0, &rom[..0x1234]).unwrap();
+        chunked
+            .push_rom_chunk(0x1234, &rom[0x1234..0x5000])
+            .unwrap();
+        chunked.finish_rom_load(false).unwrap();
+
+        for addr in 0..0x8000u16 {
+            assert_eq!(
+                chunked.memory.read(addr),
+                single.memory.read(addr),
+                "addr {:04X}",
+                addr
+            );
+        }
+        assert_eq!(chunked.memory.get_mbc_type(), single.memory.get_mbc_type());
+    }
+
+    #[test]
+    fn test_chunked_rom_load_rejects_incomplete() {
+        let rom = make_test_rom();
+        let mut core = GameBoyCore::new();
+        core.begin_rom_load(rom.len());
+        core.push_rom_chunk(0, &rom[..0x4000]).unwrap();
+        assert!(core.finish_rom_load(false).is_err());
+        // The pending load is consumed either way
+        assert!(core.finish_rom_load(false).is_err());
+    }
+
+    #[test]
+    fn test_chunked_rom_load_rejects_overlap() {
+        let rom = make_test_rom();
+        let half = rom.len() / 2;
+        let mut core = GameBoyCore::new();
+        core.begin_rom_load(rom.len());
+        core.push_rom_chunk(0, &rom[..half]).unwrap();
+        assert!(
+            core.push_rom_chunk(0, &rom[..half]).is_err(),
+            "duplicated chunk"
+        );
+        assert!(
+            core.push_rom_chunk(half - 1, &rom[half - 1..]).is_err(),
+            "one byte overlap"
+        );
+        assert!(
+            core.finish_rom_load(false).is_err(),
+            "second half still missing"
+        );
+
+        // Out-of-order, adjacent chunks are fine
+        core.begin_rom_load(rom.len());
+        core.push_rom_chunk(half, &rom[half..]).unwrap();
+        core.push_rom_chunk(0, &rom[..half]).unwrap();
+        core.finish_rom_load(false).unwrap();
+    }
+
+    #[test]
+    fn test_chunked_rom_load_rejects_out_of_range() {
+        let mut core = GameBoyCore::new();
+        assert!(
+            core.push_rom_chunk(0, &[0; 16]).is_err(),
+            "no load in progress"
+        );
+        core.begin_rom_load(0x100);
+        assert!(core.push_rom_chunk(0xF8, &[0; 16]).is_err());
+        assert!(core.push_rom_chunk(usize::MAX, &[0; 16]).is_err());
+    }
+
+    #[test]
+    fn test_disabled_log_category_is_suppressed() {
+        let core = GameBoyCore::new();
+        log::take_captured();
+
+        core.set_log_category_enabled(LogCategory::Camera, false);
+        log_info!(LogCategory::Camera, "capture");
+        log_info!(LogCategory::Cpu, "step");
+        core.set_log_category_enabled(LogCategory::Camera, true);
+        log_info!(LogCategory::Camera, "capture again");
+
+        assert_eq!(
+            log::take_captured(),
+            vec![
+                "[CPU] step".to_string(),
+                "[Camera] capture again".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_analog_dpad_deadzone_and_diagonals() {
+        let mut core = GameBoyCore::new();
+        let pressed = |core: &GameBoyCore| {
+            [Button::Right, Button::Left, Button::Up, Button::Down]
+                .map(|b| core.joypad.is_pressed(b))
+        };
+
+        core.set_analog_dpad(1.0, 0.0, 0.25);
+        assert_eq!(pressed(&core), [true, false, false, false]);
+
+        core.set_analog_dpad(-0.7, -0.7, 0.25);
+        assert_eq!(pressed(&core), [false, true, true, false]);
+
+        core.set_analog_dpad(0.2, -0.1, 0.25);
+        assert_eq!(pressed(&core), [false; 4]);
+    }
+
+    #[test]
+    fn test_run_frame_budgeted_resumes_to_same_frame() {
+        let rom = make_test_rom();
+        let mut stepped = GameBoyCore::new();
+        stepped.load_rom(&rom, false).unwrap();
+        stepped.step_frame();
+
+        let mut budgeted = GameBoyCore::new();
+        budgeted.load_rom(&rom, false).unwrap();
+        assert_eq!(budgeted.run_frame_budgeted(100), FrameProgress::Partial);
+        assert_eq!(budgeted.frame_count, 0);
+        let mut calls = 1;
+        while budgeted.run_frame_budgeted(100) == FrameProgress::Partial {
+            calls += 1;
+            assert!(calls < 10_000, "frame never completed");
+        }
+
+        assert_eq!(budgeted.frame_count, 1);
+        assert_eq!(budgeted.instruction_count, stepped.instruction_count);
+        assert_eq!(budgeted.state_checksum(), stepped.state_checksum());
+        assert_eq!(budgeted.frame_buffer.front(), stepped.frame_buffer.front());
+    }
+
+    #[test]
+    fn test_memory_search_and_refine() {
+        let mut core = GameBoyCore::new();
+        core.load_rom(&make_test_rom(), false).unwrap();
+        core.memory.write(0xC123, 0x63);
+        core.memory.write(0xD456, 0x63);
+        core.memory.write(0xFF90, 0x63);
+
+        let wram = core.memory_search(0x63, MemRegion::Wram);
+        assert!(wram.contains(&0xC123) && wram.contains(&0xD456));
+        assert!(!wram.contains(&0xFF90));
+        assert!(core.memory_search(0x63, MemRegion::All).contains(&0xFF90));
+
+        // The "lives" counter drops by one
+        core.memory.write(0xC123, 0x62);
+        assert_eq!(core.memory_search_refine(&wram, 0x62), vec![0xC123]);
+    }
+
+    #[test]
+    fn test_run_frames_counts_vblanks_across_speed_switch() {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x100..0x102].copy_from_slice(&[0x18, 0xFE]); // JR -2
+        let mut core = GameBoyCore::new();
+        core.load_rom(&rom, true).unwrap();
+        core.run_frames(1); // align to V-blank
+
+        let run = |core: &mut GameBoyCore, frames: u32| {
+            core.set_ppu_mode_trace(true);
+            let (start_frames, start_cycles) = (core.frame_count, core.total_cycles);
+            core.run_frames(frames);
+            let vblanks = core.ppu_mode_trace().iter().filter(|e| e.mode == 1).count();
+            assert_eq!(core.frame_count - start_frames, frames);
+            assert_eq!(vblanks, frames as usize);
+            core.total_cycles - start_cycles
+        };
+
+        let normal = run(&mut core, 3);
+        core.memory.toggle_double_speed();
+        let double = run(&mut core, 3);
+        core.memory.toggle_double_speed();
+        let back = run(&mut core, 3);
+
+        let frame = DOTS_PER_FRAME as u64;
+        assert!(normal.abs_diff(3 * frame) <= 12, "normal speed: {}", normal);
+        assert!(double.abs_diff(6 * frame) <= 24, "double speed: {}", double);
+        assert!(back.abs_diff(3 * frame) <= 12, "back to normal: {}", back);
+    }
+
+    #[test]
+    fn test_div_runs_twice_as_fast_in_double_speed() {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x100..0x102].copy_from_slice(&[0x18, 0xFE]); // JR -2
+        let mut core = GameBoyCore::new();
+        core.load_rom(&rom, true).unwrap();
+
+        // DIV increments over 64 scanlines (29184 dots)
+        let div_ticks = |core: &mut GameBoyCore| {
+            let (mut ticks, mut lines) = (0u32, 0);
+            let mut div = core.timer.read(0xFF04);
+            let mut ly = core.memory.read_io_direct(io::LY);
+            while lines < 64 {
+                core.step_single();
+                let now = core.timer.read(0xFF04);
+                ticks += now.wrapping_sub(div) as u32;
+                div = now;
+                if core.memory.read_io_direct(io::LY) != ly {
+                    ly = core.memory.read_io_direct(io::LY);
+                    lines += 1;
+                }
+            }
+            ticks
+        };
+
+        let normal = div_ticks(&mut core);
+        core.memory.toggle_double_speed();
+        let double = div_ticks(&mut core);
+        assert!(
+            normal.abs_diff(64 * 456 / 256) <= 1,
+            "normal speed: {}",
+            normal
+        );
+        assert!(
+            double.abs_diff(2 * 64 * 456 / 256) <= 1,
+            "double speed: {}",
+            double
+        );
+    }
+
+    #[test]
+    fn test_export_state_json() {
+        let mut rom = make_joypad_rom();
+        rom[0x0134..0x0139].copy_from_slice(b"J\"OY\\");
+        rom[0x0147] = 0x01; // MBC1
+        let mut core = GameBoyCore::new();
+        core.load_rom(&rom, false).unwrap();
+        for _ in 0..3 {
+            core.step_single();
+        }
+        let pc = core.cpu.get_debug_state().pc;
+        let json = core.export_state_json();
+
+        // Structure: one object, balanced brackets outside strings
+        let trimmed = json.trim();
+        assert!(trimmed.starts_with('{') && trimmed.ends_with('}'));
+        let (mut depth, mut in_string, mut escaped) = (0i32, false, false);
+        for c in trimmed.chars() {
+            match (in_string, escaped, c) {
+                (true, true, _) => escaped = false,
+                (true, false, '\\') => escaped = true,
+                (true, false, '"') => in_string = false,
+                (false, _, '"') => in_string = true,
+                (false, _, '{') => depth += 1,
+                (false, _, '}') => depth -= 1,
+                _ => {}
+            }
+            assert!(depth >= 0);
+        }
+        assert_eq!((depth, in_string), (0, false));
+        assert!(
+            !json.contains(",\n}") && !json.contains(", }"),
+            "no trailing commas"
+        );
+
+        assert!(json.contains(&format!("\"pc\": {},", pc)), "{}", json);
+        assert!(json.contains("\"mbc\": \"Mbc1\""), "{}", json);
+        assert!(json.contains("\"instructions\": 3,"), "{}", json);
+        assert!(json.contains(r#""title": "J\"OY\\""#), "{}", json);
+    }
+
+    #[test]
+    fn test_take_serial_since_returns_only_new_bytes() {
+        let mut core = GameBoyCore::new();
+        core.load_rom(&make_test_rom(), false).unwrap();
+        let send = |core: &mut GameBoyCore, bytes: &[u8]| {
+            for &b in bytes {
+                core.memory.write(0xFF01, b);
+                core.memory.write(0xFF02, 0x81);
+            }
+        };
+
+        send(&mut core, b"OK ");
+        let (first, cursor) = core.take_serial_since(0);
+        assert_eq!(first, b"OK ");
+        assert_eq!(cursor, 3);
+
+        send(&mut core, b"DONE");
+        let (second, cursor) = core.take_serial_since(cursor);
+        assert_eq!(second, b"DONE");
+        assert_eq!(core.take_serial_since(cursor), (Vec::new(), 7));
+
+        // After a clear, a stale cursor restarts from the beginning
+        core.memory.clear_serial_output();
+        send(&mut core, b"!");
+        assert_eq!(core.take_serial_since(cursor), (b"!".to_vec(), 1));
+    }
+
+    #[test]
+    fn test_capture_callback_once_per_capture() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let rom = gb_film::build_rom(&gb_film::CameraConfig::default());
+        let mut core = GameBoyCore::new();
+        core.load_rom(&rom, false).unwrap();
+        core.set_camera_image(&[0x80; 128 * 112]);
+
+        let slots = Rc::new(RefCell::new(Vec::new()));
+        let seen = slots.clone();
+        core.set_capture_callback(Some(Box::new(move |slot| seen.borrow_mut().push(slot))));
+
+        core.run_frames(3);
+        assert!(slots.borrow().is_empty(), "no capture before frame 4");
+        core.run_frames(20);
+
+        let captures = core.memory.camera_capture_count();
+        assert!(captures > 0);
+        assert_eq!(slots.borrow().len(), captures as usize);
+        assert!(slots.borrow().iter().all(|&slot| slot == 0));
+    }
+
+    #[test]
+    fn test_boot_rom_hands_off_to_cartridge() {
+        let mut boot = vec![0u8; 0x100];
+        boot[0x00..0x03].copy_from_slice(&[0xC3, 0xFC, 0x00]); // JP 0x00FC
+        boot[0xFC..0x100].copy_from_slice(&[0x3E, 0x01, 0xE0, 0x50]); // LD A,1; LDH (0x50),A
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x0000] = 0xAB;
+
+        let mut core = GameBoyCore::new();
+        core.set_entry_pc(0x0200); // the boot ROM takes precedence
+        core.load_rom(&rom, false).unwrap();
+        core.load_boot_rom(&boot).unwrap();
+        assert_eq!(
+            core.memory.read(0x0000),
+            0xAB,
+            "a running game keeps its vectors"
+        );
+        core.load_rom(&rom, false).unwrap();
+        assert_eq!(core.cpu.register("pc"), Some(0x0000));
+        assert_eq!(
+            core.memory.read(0x0000),
+            0xC3,
+            "boot ROM shadows the cartridge"
+        );
+
+        for _ in 0..3 {
+            core.step_single();
+        }
+        assert_eq!(core.cpu.register("pc"), Some(0x0100));
+        assert!(!core.memory.is_boot_rom_mapped());
+        assert_eq!(
+            core.memory.read(0x0000),
+            0xAB,
+            "cartridge visible after 0xFF50"
+        );
+
+        // Reloading maps it again
+        core.load_rom(&rom, false).unwrap();
+        assert_eq!(core.cpu.register("pc"), Some(0x0000));
+        assert_eq!(core.memory.read(0x0000), 0xC3);
+    }
+
+    #[test]
+    fn test_printer_prints_screen_sent_over_serial() {
+        use crate::printer::packet;
+
+        // Band k (two tile rows) is solid colour k % 4
+        let band = |k: usize| -> Vec<u8> {
+            let planes = [(k & 1) as u8 * 0xFF, (k >> 1 & 1) as u8 * 0xFF];
+            planes.iter().copied().cycle().take(640).collect()
+        };
+
+        let mut sequence = packet(0x01, false, &[]); // INIT
+        // Band 0 is all zero bytes; send it RLE-compressed as 4×129 + 124 copies
+        let mut rle = [0xFF, 0x00].repeat(4);
+        rle.extend([0x80 | 122, 0x00]);
+        sequence.extend(packet(0x04, true, &rle));
+        for k in 1..9 {
+            sequence.extend(packet(0x04, false, &band(k)));
+        }
+        sequence.extend(packet(0x04, false, &[])); // end of data
+        sequence.extend(packet(0x02, false, &[0x01, 0x13, 0xE4, 0x40])); // PRINT
+        sequence.extend(packet(0x0F, false, &[])); // STATUS
+
+        let mut core = GameBoyCore::new();
+        core.load_rom(&vec![0u8; 0x8000], false).unwrap();
+        core.enable_printer(true);
+        let mut received = Vec::new();
+        for &byte in &sequence {
+            core.memory.write(0xFF01, byte);
+            core.memory.write(0xFF02, 0x81);
+            core.memory.tick_serial(8 * 512);
+            received.push(core.memory.read(0xFF01));
+        }
+        let tail = &received[received.len() - 2..];
+        assert_eq!(
+            tail,
+            &[0x81, 0x02],
+            "status poll sees the print in progress"
+        );
+
+        assert_eq!(core.printer_image_height(), 144);
+        let image = core.printer_image();
+        assert_eq!(image.len(), 160 * 144 * 4);
+        for k in 0..9 {
+            let gray = [0xFF, 0xAA, 0x55, 0x00][k % 4];
+            let pixel = ((k * 16 + 5) * 160 + 77) * 4;
+            assert_eq!(image[pixel..pixel + 4], [gray, gray, gray, 255], "band {k}");
+        }
+
+        core.enable_printer(false);
+        assert!(core.printer_image().is_empty());
+    }
+
+    #[test]
+    fn test_entry_pc_override() {
+        let mut rom = vec![0xFFu8; 0x8000]; // RST 38 everywhere else
+        rom[0x0200] = 0x00; // NOP
+        let mut core = GameBoyCore::new();
+        core.set_entry_pc(0x0200);
+        core.load_rom(&rom, false).unwrap();
+        assert_eq!(core.cpu.register("pc"), Some(0x0200));
+        core.step_single();
+        assert_eq!(
+            core.cpu.register("pc"),
+            Some(0x0201),
+            "executed the NOP at 0x0200"
+        );
+
+        core.set_entry_pc(0x0100);
+        core.load_rom(&rom, false).unwrap();
+        assert_eq!(core.cpu.register("pc"), Some(0x0100));
+    }
+
+    #[test]
+    fn test_pocket_model_applies_to_dmg_loads_only() {
+        let rom = vec![0u8; 0x8000];
+        let mut core = GameBoyCore::new();
+        core.set_pocket_model(true);
+        core.load_rom(&rom, false).unwrap();
+        assert_eq!(core.cpu.register("a"), Some(0xFF));
+        core.load_rom(&rom, true).unwrap();
+        assert_eq!(
+            core.cpu.register("a"),
+            Some(0x11),
+            "CGB mode keeps the CGB registers"
+        );
+
+        core.set_pocket_model(false);
+        core.load_rom(&rom, false).unwrap();
+        assert_eq!(core.cpu.register("a"), Some(0x01));
+    }
+
+    #[test]
+    fn test_joypad_interrupt_needs_selected_line() {
+        let mut core = GameBoyCore::new();
+        core.load_rom(&vec![0u8; 0x8000], false).unwrap();
+        core.memory.write_io_direct(io::IF, 0x00);
+
+        core.joypad.write(0x20); // d-pad only
+        core.set_button(Button::A as u8, true);
+        assert_eq!(core.memory.read_io_direct(io::IF) & 0x10, 0);
+        core.set_button(Button::A as u8, false);
+
+        core.joypad.write(0x10); // action buttons
+        core.set_button(Button::A as u8, true);
+        assert_eq!(core.memory.read_io_direct(io::IF) & 0x10, 0x10);
+    }
+
+    #[test]
+    fn test_turbo_toggles_held_button() {
+        let mut core = GameBoyCore::new();
+        core.load_rom(&make_joypad_rom(), false).unwrap();
+        core.set_turbo(Button::A, 15.0); // 4-frame period: 2 on, 2 off
+        core.set_button(Button::A as u8, true);
+
+        let mut states = Vec::new();
+        for _ in 0..8 {
+            core.run_frames(1);
+            states.push(core.joypad.is_pressed(Button::A));
+        }
+        assert_eq!(states, [true, true, false, false, true, true, false, false]);
+
+        // Releasing stops it; pressing again restarts the cycle pressed
+        core.set_button(Button::A as u8, false);
+        core.run_frames(3);
+        assert!(!core.joypad.is_pressed(Button::A));
+        core.set_button(Button::A as u8, true);
+        core.run_frames(1);
+        assert!(core.joypad.is_pressed(Button::A));
+
+        // Rate 0 disables turbo: the button stays held
+        core.set_turbo(Button::A, 0.0);
+        core.run_frames(4);
+        assert!(core.joypad.is_pressed(Button::A));
+    }
+
+    #[test]
+    fn test_frame_with_channel_2_produces_audio() {
+        let mut core = GameBoyCore::new();
+        core.load_rom(&vec![0u8; 0x8000], false).unwrap();
+        core.memory.set_audio_sample_rate(44_100);
+        let mut out = vec![0.0f32; 4096];
+        core.memory.drain_audio(&mut out);
+
+        // Channel 2: 50% duty, full volume, ~1 kHz, routed to both sides
+        for (addr, value) in [
+            (0xFF16, 0x80),
+            (0xFF17, 0xF0),
+            (0xFF18, 0x83),
+            (0xFF19, 0x87),
+        ] {
+            core.memory.write(addr, value);
+        }
+        core.step_frame(); // the first frame after load is short
+        core.memory.drain_audio(&mut out);
+        core.step_frame();
+
+        let frames = core.memory.drain_audio(&mut out);
+        assert!(
+            (730..=740).contains(&frames),
+            "one frame at 44.1 kHz, got {}",
+            frames
+        );
+        let peak = out[..frames * 2].iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!(peak > 0.1, "peak {}", peak);
+    }
+
+    #[test]
+    fn test_oam_dma_blocks_cpu_reads_outside_hram() {
+        let mut core = GameBoyCore::new();
+        core.load_rom(&vec![0u8; 0x8000], false).unwrap();
+        for i in 0..0xA0u16 {
+            core.memory.write(0xC000 + i, 0x42 + i as u8);
+        }
+        // The usual HRAM routine, reading WRAM right after starting the DMA
+        // and again once it has finished
+        let routine = [
+            0x3E, 0xC0, // LD A, 0xC0
+            0xE0, 0x46, // LDH (DMA), A
+            0xFA, 0x00, 0xC0, // LD A, (0xC000)
+            0xE0, 0xA0, // LDH (0xA0), A
+            0x3E, 0x28, // LD A, 40
+            0x3D, // loop: DEC A
+            0x20, 0xFD, // JR NZ, loop
+            0xFA, 0x00, 0xC0, // LD A, (0xC000)
+            0xE0, 0xA1, // LDH (0xA1), A
+            0x18, 0xFE, // JR -2
+        ];
+        for (i, &b) in routine.iter().enumerate() {
+            core.memory.write(0xFF80 + i as u16, b);
+        }
+        core.cpu.set_register("pc", 0xFF80);
+
+        for _ in 0..4 {
+            core.step_single();
+        }
+        assert!(core.memory.oam_dma_active());
+        assert_eq!(
+            core.memory.read(0xFFA0),
+            0xFF,
+            "WRAM reads 0xFF mid-transfer"
+        );
+
+        while core.cpu.register("pc") != Some(0xFF93) {
+            core.step_single();
+        }
+        assert!(!core.memory.oam_dma_active());
+        assert_eq!(core.memory.read(0xFFA1), 0x42, "WRAM readable again");
+        assert!((0..0xA0).all(|i| core.memory.get_oam()[i] == 0x42 + i as u8));
+    }
+
+    #[test]
+    fn test_value_watchpoint() {
+        let mut rom = vec![0u8; 0x8000];
+        let code = [
+            0x3C, // loop: INC A
+            0xEA, 0x00, 0xC0, // LD (0xC000), A
+            0x18, 0xFA, // JR loop
+        ];
+        rom[0x150..0x150 + code.len()].copy_from_slice(&code);
+        rom[0x100..0x103].copy_from_slice(&[0xC3, 0x50, 0x01]); // JP 0x0150
+        let mut core = GameBoyCore::new();
+        core.load_rom(&rom, false).unwrap();
+        core.cpu.set_register("a", 0);
+
+        core.add_value_watchpoint(0xC000, 5);
+        core.add_value_watchpoint(0xC001, 2);
+        // JP, then two loop iterations store 1 and 2 at 0xC000
+        assert_eq!(core.step_until_break(7), StepResult::Budget);
+        assert_eq!(core.memory.read(0xC000), 2);
+
+        let hit = core.step_until_break(100);
+        assert_eq!(
+            hit,
+            StepResult::ValueWatchpoint {
+                addr: 0xC000,
+                value: 5
+            }
+        );
+        assert_eq!(core.memory.read(0xC000), 5);
+        assert_eq!(
+            core.cpu.register("pc"),
+            Some(0x0154),
+            "stops after the store"
+        );
+
+        core.clear_value_watchpoints();
+        assert_eq!(core.step_until_break(100), StepResult::Budget);
+    }
 }
@@ -8,12 +8,25 @@ mod alu;
 mod opcodes;
 
 use std::fmt;
+use std::hash::{Hash, Hasher};
 
 use crate::bus::MemoryBus;
 use crate::interrupts::{Interrupt, InterruptController};
 use crate::log::LogCategory;
-use crate::log_info;
 use crate::memory::io;
+use crate::{log_info, log_warn};
+
+/// An illegal opcode (0xD3, 0xDB, 0xDD, 0xE3, 0xE4, 0xEB-0xED, 0xF4,
+/// 0xFC, 0xFD) the CPU hit. Hardware locks up until reset; so does the
+/// emulated CPU, rather than aborting the process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: cpu_fault_*
+pub struct CpuFault {
+    /// Address of the illegal opcode.
+    pub pc: u16,
+    pub opcode: u8,
+}
 
 /// Debug state for CPU inspection.
 #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: cpu_* accessors
@@ -27,6 +40,8 @@ pub struct CpuDebugState {
     pub hl: u16,
     pub ime: bool,
     pub halted: bool,
+    /// Set once an illegal opcode has locked the CPU.
+    pub fault: Option<CpuFault>,
 }
 
 impl fmt::Display for CpuDebugState {
@@ -35,10 +50,53 @@ impl fmt::Display for CpuDebugState {
             f,
             "PC={:04X} SP={:04X} A={:02X} F={:02X} BC={:04X} DE={:04X} HL={:04X} IME={} halt={}",
             self.pc, self.sp, self.a, self.f, self.bc, self.de, self.hl, self.ime, self.halted
-        )
+        )?;
+        if let Some(fault) = self.fault {
+            write!(
+                f,
+                " LOCKED (opcode {:02X} at {:04X})",
+                fault.opcode, fault.pc
+            )?;
+        }
+        Ok(())
     }
 }
 
+/// Hardware model, for the register values its boot ROM leaves behind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GbModel {
+    /// Original Game Boy.
+    Dmg,
+    /// Game Boy Pocket / Light: as DMG, but A=0xFF.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: set_pocket_model
+    Mgb,
+    /// Game Boy Color (running a CGB or DMG cartridge in CGB mode).
+    Cgb,
+}
+
+/// Complete architectural CPU state, for save states and rewind (see
+/// `Cpu::snapshot`). Serializable with the `serde` feature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: CpuSnapshot
+pub struct CpuState {
+    pub a: u8,
+    pub f: u8,
+    pub b: u8,
+    pub c: u8,
+    pub d: u8,
+    pub e: u8,
+    pub h: u8,
+    pub l: u8,
+    pub sp: u16,
+    pub pc: u16,
+    pub halted: bool,
+    pub ime: bool,
+    pub ime_pending: bool,
+    pub halt_bug: bool,
+    pub fault: Option<CpuFault>,
+}
+
 pub struct Cpu {
     // 8-bit registers
     a: u8,
@@ -56,8 +114,13 @@ pub struct Cpu {
 
     // State
     halted: bool,
-    ime: bool,         // Interrupt Master Enable
-    ime_pending: bool, // EI enables IME after next instruction
+    ime: bool,               // Interrupt Master Enable
+    ime_pending: bool,       // EI enables IME after the next instruction executes
+    fault: Option<CpuFault>, // Locked by an illegal opcode until reset
+    halt_bug: bool, // HALT with IME=0 and an interrupt pending: next PC increment is skipped
+
+    // Accuracy: tick the timer between the M-cycles of interrupt dispatch
+    accurate: bool,
 
     // Debug
     instruction_count: u64,
@@ -86,15 +149,17 @@ impl Cpu {
             halted: false,
             ime: true,
             ime_pending: false,
+            fault: None,
+            halt_bug: false,
+            accurate: false,
             instruction_count: 0,
         }
     }
 
     pub fn step(&mut self, bus: &mut MemoryBus, interrupts: &mut InterruptController) -> u32 {
-        // Handle pending IME enable
-        if self.ime_pending {
-            self.ime = true;
-            self.ime_pending = false;
+        // Locked by an illegal opcode: nothing runs, not even interrupts
+        if self.fault.is_some() {
+            return 4;
         }
 
         // Handle interrupts
@@ -118,6 +183,11 @@ impl Cpu {
         // Trace first 20 instructions
         let pc_before = self.pc;
         let opcode = self.fetch(bus);
+        if self.halt_bug {
+            // HALT bug: PC fails to advance, so this byte is read again
+            self.halt_bug = false;
+            self.pc = pc_before;
+        }
 
         if self.instruction_count < 20 {
             log_info!(
@@ -135,7 +205,33 @@ impl Cpu {
         }
 
         self.instruction_count += 1;
-        self.execute(opcode, bus)
+
+        // EI takes effect only after the *following* instruction has executed,
+        // so `EI; HALT` enters HALT with IME set and `EI; DI` never enables IME.
+        let enable_ime = self.ime_pending;
+        let cycles = self.execute(opcode, bus);
+        if enable_ime && self.ime_pending {
+            self.ime = true;
+            self.ime_pending = false;
+        }
+        cycles
+    }
+
+    /// Lock up on an illegal opcode (just fetched from `pc - 1`).
+    fn lock_up(&mut self, opcode: u8) -> u32 {
+        let pc = self.pc.wrapping_sub(1);
+        log_warn!(
+            LogCategory::Cpu,
+            "Illegal opcode 0x{opcode:02X} at PC: 0x{pc:04X}, CPU locked"
+        );
+        self.fault = Some(CpuFault { pc, opcode });
+        4
+    }
+
+    /// The illegal opcode that locked the CPU, if any.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: cpu_fault_pc, cpu_fault_opcode
+    pub fn fault(&self) -> Option<CpuFault> {
+        self.fault
     }
 
     #[inline]
@@ -185,7 +281,21 @@ impl Cpu {
         interrupts.clear(interrupt, bus.memory_mut());
 
         // Push PC and jump to handler
-        self.push_word(bus, self.pc);
+        if self.accurate {
+            // M1-M2: internal wait states
+            bus.tick(8, interrupts);
+            // M3-M4: push PC high, then low, each at the end of its M-cycle
+            self.sp = self.sp.wrapping_sub(1);
+            bus.tick(4, interrupts);
+            bus.write(self.sp, (self.pc >> 8) as u8);
+            self.sp = self.sp.wrapping_sub(1);
+            bus.tick(4, interrupts);
+            bus.write(self.sp, self.pc as u8);
+            // M5: load the vector into PC
+            bus.tick(4, interrupts);
+        } else {
+            self.push_word(bus, self.pc);
+        }
         self.pc = vector;
 
         Some(20) // Interrupt handling takes 20 cycles
@@ -292,30 +402,148 @@ impl Cpu {
         (high << 8) | low
     }
 
-    /// Reset CPU to power-on state for the given mode.
+    /// Reset CPU to the post-boot-ROM state of `model`.
     /// Called by GameBoyCore::load_rom() on every ROM load.
-    pub fn reset(&mut self, cgb_mode: bool) {
-        *self = Self::new();
-        if cgb_mode {
-            // Post-GBC boot ROM register state (per Pan Docs / Gambatte reference):
-            // A=0x11 is the GBC hardware indicator games check at 0x0100.
-            // Other registers differ from the DMG boot ROM values.
-            self.a = 0x11;
-            self.f = 0x80; // Z=1, N=0, H=0, C=0
-            self.c = 0x00;
-            self.d = 0xFF;
-            self.e = 0x56;
-            self.h = 0x00;
-            self.l = 0x0D;
+    pub fn reset(&mut self, model: GbModel) {
+        let accurate = self.accurate;
+        *self = Self::new(); // DMG values
+        self.accurate = accurate;
+        match model {
+            GbModel::Dmg => {}
+            // A is the hardware ID games check at 0x0100: 0x01 DMG,
+            // 0xFF Pocket/Light, 0x11 GBC
+            GbModel::Mgb => self.a = 0xFF,
+            GbModel::Cgb => {
+                // Post-GBC boot ROM register state (per Pan Docs / Gambatte reference)
+                self.a = 0x11;
+                self.f = 0x80; // Z=1, N=0, H=0, C=0
+                self.c = 0x00;
+                self.d = 0xFF;
+                self.e = 0x56;
+                self.h = 0x00;
+                self.l = 0x0D;
+            }
         }
     }
 
+    /// Enable sub-instruction timing where modelled (currently interrupt
+    /// dispatch). Off by default; the fast path batches all cycles.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: set_accurate
+    pub fn set_accurate(&mut self, accurate: bool) {
+        self.accurate = accurate;
+    }
+
     /// Set GBC initial register state (A=0x11).
     #[allow(dead_code)]
     pub fn set_cgb_initial_state(&mut self) {
         self.a = 0x11;
     }
 
+    /// Read a register by name (`a`..`l`, `af`, `bc`, `de`, `hl`, `sp`, `pc`)
+    /// for debugger front ends. `None` for an unknown name.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: DebugSession
+    pub fn register(&self, name: &str) -> Option<u16> {
+        Some(match name {
+            "a" => self.a as u16,
+            "f" => self.f as u16,
+            "b" => self.b as u16,
+            "c" => self.c as u16,
+            "d" => self.d as u16,
+            "e" => self.e as u16,
+            "h" => self.h as u16,
+            "l" => self.l as u16,
+            "af" => self.af(),
+            "bc" => self.bc(),
+            "de" => self.de(),
+            "hl" => self.hl(),
+            "sp" => self.sp,
+            "pc" => self.pc,
+            _ => return None,
+        })
+    }
+
+    /// Write a register by name (see `register`); 8-bit registers take the
+    /// low byte and F keeps only its upper nibble. False for an unknown name.
+    pub fn set_register(&mut self, name: &str, value: u16) -> bool {
+        let byte = value as u8;
+        match name {
+            "a" => self.a = byte,
+            "f" => self.f = byte & 0xF0,
+            "b" => self.b = byte,
+            "c" => self.c = byte,
+            "d" => self.d = byte,
+            "e" => self.e = byte,
+            "h" => self.h = byte,
+            "l" => self.l = byte,
+            "af" => self.set_af(value),
+            "bc" => self.set_bc(value),
+            "de" => self.set_de(value),
+            "hl" => self.set_hl(value),
+            "sp" => self.sp = value,
+            "pc" => self.pc = value,
+            _ => return false,
+        }
+        true
+    }
+
+    /// Capture the register file and execution state (see `restore`).
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: cpu_snapshot
+    pub fn snapshot(&self) -> CpuState {
+        CpuState {
+            a: self.a,
+            f: self.f,
+            b: self.b,
+            c: self.c,
+            d: self.d,
+            e: self.e,
+            h: self.h,
+            l: self.l,
+            sp: self.sp,
+            pc: self.pc,
+            halted: self.halted,
+            ime: self.ime,
+            ime_pending: self.ime_pending,
+            halt_bug: self.halt_bug,
+            fault: self.fault,
+        }
+    }
+
+    /// Return to a `snapshot`. The accuracy setting and instruction count
+    /// are not part of the state and are left alone.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: cpu_restore
+    pub fn restore(&mut self, state: &CpuState) {
+        self.a = state.a;
+        self.f = state.f & 0xF0;
+        self.b = state.b;
+        self.c = state.c;
+        self.d = state.d;
+        self.e = state.e;
+        self.h = state.h;
+        self.l = state.l;
+        self.sp = state.sp;
+        self.pc = state.pc;
+        self.halted = state.halted;
+        self.ime = state.ime;
+        self.ime_pending = state.ime_pending;
+        self.halt_bug = state.halt_bug;
+        self.fault = state.fault;
+    }
+
+    /// Feed all architectural state into `h` (see `GameBoyCore::state_checksum`).
+    pub fn hash_state<H: Hasher>(&self, h: &mut H) {
+        [
+            self.a, self.f, self.b, self.c, self.d, self.e, self.h, self.l,
+        ]
+        .hash(h);
+        self.sp.hash(h);
+        self.pc.hash(h);
+        self.halted.hash(h);
+        self.ime.hash(h);
+        self.ime_pending.hash(h);
+        self.fault.hash(h);
+        self.halt_bug.hash(h);
+    }
+
     /// Get current CPU state for debugging.
     #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: cpu_* accessors
     pub fn get_debug_state(&self) -> CpuDebugState {
@@ -329,6 +557,7 @@ impl Cpu {
             hl: self.hl(),
             ime: self.ime,
             halted: self.halted,
+            fault: self.fault,
         }
     }
 }
@@ -345,6 +574,7 @@ mod tests {
     use crate::interrupts::InterruptController;
     use crate::joypad::Joypad;
     use crate::memory::Memory;
+    use crate::ppu::Ppu;
     use crate::timer::Timer;
 
     struct TestContext {
@@ -352,6 +582,7 @@ mod tests {
         memory: Memory,
         timer: Timer,
         joypad: Joypad,
+        ppu: Ppu,
         ic: InterruptController,
     }
 
@@ -360,10 +591,23 @@ mod tests {
             let mut bus = MemoryBus::new(&mut self.memory, &mut self.timer, &mut self.joypad);
             self.cpu.step(&mut bus, &mut self.ic)
         }
+
+        /// Step and then tick the timer and PPU by the cycles the CPU didn't
+        /// already apply mid-instruction, as `GameBoyCore` does.
+        fn step_with_timer(&mut self) -> u32 {
+            let (cycles, ticked, dots) = {
+                let mut bus = MemoryBus::new(&mut self.memory, &mut self.timer, &mut self.joypad)
+                    .with_ppu(&mut self.ppu);
+                let cycles = self.cpu.step(&mut bus, &mut self.ic);
+                (cycles, bus.timer_cycles_ticked(), bus.ppu_dots_ticked())
+            };
+            self.timer.tick(cycles - ticked, &mut self.memory, &self.ic);
+            self.ppu.tick(cycles - dots, &mut self.memory, &self.ic);
+            cycles
+        }
     }
 
     fn setup_with_rom(rom_data: &[u8]) -> TestContext {
-        let mut mem = Memory::new();
         // Create a ROM with header and our test data starting at 0x100
         let mut rom = vec![0u8; 0x8000];
         // Copy test data to ROM entry point
@@ -372,12 +616,20 @@ mod tests {
                 rom[0x100 + i] = byte;
             }
         }
-        mem.load_rom(&rom, true).unwrap(); // CGB mode: KEY1 register correctly reflects speed_armed
+        setup_with_rom_image(&rom)
+    }
+
+    /// Like `setup_with_rom`, but takes a full ROM image (e.g. with code at
+    /// the interrupt vectors).
+    fn setup_with_rom_image(rom: &[u8]) -> TestContext {
+        let mut mem = Memory::new();
+        mem.load_rom(rom, true).unwrap(); // CGB mode: KEY1 register correctly reflects speed_armed
         TestContext {
             cpu: Cpu::new(),
             memory: mem,
             timer: Timer::new(),
             joypad: Joypad::new(),
+            ppu: Ppu::new(),
             ic: InterruptController::new(),
         }
     }
@@ -473,6 +725,82 @@ mod tests {
         assert!(ctx.cpu.flag(FLAG_N));
     }
 
+    #[test]
+    fn test_cp_flags_exhaustive() {
+        let mut cpu = Cpu::new();
+        for a in 0..=255u8 {
+            for value in 0..=255u8 {
+                // Reference: CP is SUB with the result discarded
+                let diff = a as i16 - value as i16;
+                let half = (a & 0x0F) as i16 - (value & 0x0F) as i16;
+                let expected = ((diff & 0xFF == 0) as u8) << 7
+                    | 1 << 6
+                    | ((half < 0) as u8) << 5
+                    | ((diff < 0) as u8) << 4;
+
+                cpu.a = a;
+                cpu.f = 0;
+                cpu.cp(value);
+                assert_eq!(cpu.f, expected, "CP a={:02X} value={:02X}", a, value);
+                assert_eq!(cpu.a, a);
+            }
+        }
+    }
+
+    #[test]
+    fn test_illegal_opcodes_lock_cpu() {
+        for opcode in [
+            0xD3, 0xDB, 0xDD, 0xE3, 0xE4, 0xEB, 0xEC, 0xED, 0xF4, 0xFC, 0xFD,
+        ] {
+            let mut ctx = setup_with_rom(&[opcode]);
+            ctx.cpu.ime = false;
+            ctx.step();
+            let expected = Some(CpuFault { pc: 0x0100, opcode });
+            assert_eq!(ctx.cpu.fault(), expected, "opcode {:02X}", opcode);
+            assert_eq!(ctx.cpu.get_debug_state().fault, expected);
+
+            // Locked: PC stays put and even a pending interrupt is ignored
+            ctx.cpu.ime = true;
+            ctx.memory.write(0xFFFF, 0x01);
+            ctx.memory.write(0xFF0F, 0x01);
+            for _ in 0..4 {
+                assert_eq!(ctx.step(), 4);
+            }
+            assert_eq!(ctx.cpu.pc, 0x0101, "opcode {:02X}", opcode);
+            assert!(ctx.cpu.get_debug_state().to_string().contains("LOCKED"));
+
+            ctx.cpu.reset(GbModel::Dmg);
+            assert_eq!(ctx.cpu.fault(), None, "reset unlocks");
+        }
+    }
+
+    #[test]
+    fn test_logic_flags_exhaustive() {
+        const A: u8 = 0x5A;
+        let mut cpu = Cpu::new();
+        for name in ["AND", "OR", "XOR"] {
+            for value in 0..=255u8 {
+                // Z from the result, N = C = 0, H set only by AND
+                let (result, half) = match name {
+                    "AND" => (A & value, true),
+                    "OR" => (A | value, false),
+                    _ => (A ^ value, false),
+                };
+                let expected = ((result == 0) as u8) << 7 | (half as u8) << 5;
+
+                cpu.a = A;
+                cpu.f = 0xF0; // every flag set beforehand
+                match name {
+                    "AND" => cpu.and(value),
+                    "OR" => cpu.or(value),
+                    _ => cpu.xor(value),
+                }
+                assert_eq!(cpu.a, result, "{} value={:02X}", name, value);
+                assert_eq!(cpu.f, expected, "{} value={:02X}", name, value);
+            }
+        }
+    }
+
     #[test]
     fn test_and() {
         let mut ctx = setup_with_rom(&[0xA0]); // AND B
@@ -552,6 +880,39 @@ mod tests {
         assert_eq!(ctx.cpu.sp, 0xFFFE);
     }
 
+    #[test]
+    fn test_pop_af_masks_low_nibble() {
+        let mut ctx = setup_with_rom(&[
+            0xF5, // PUSH AF
+            0xF1, // POP AF
+            0xF5, // PUSH AF
+            0xC1, // POP BC
+            0x01, 0xFF, 0x34, // LD BC, 0x34FF
+            0xC5, // PUSH BC
+            0xF1, // POP AF
+        ]);
+        ctx.cpu.sp = 0xFFFE;
+        ctx.cpu.a = 0x12;
+        ctx.cpu.f = 0xBF; // stray low bits, unreachable on hardware
+
+        // PUSH AF stores F as-is; POP AF drops the low nibble
+        ctx.step();
+        assert_eq!(ctx.memory.read(0xFFFC), 0xBF);
+        ctx.step();
+        assert_eq!(ctx.cpu.af(), 0x12B0);
+
+        ctx.step();
+        ctx.step();
+        assert_eq!(ctx.cpu.bc(), 0x12B0);
+
+        // Arbitrary stack bytes popped into AF
+        ctx.step();
+        ctx.step();
+        ctx.step();
+        assert_eq!(ctx.cpu.af(), 0x34F0);
+        assert_eq!(ctx.cpu.sp, 0xFFFE);
+    }
+
     #[test]
     fn test_rlca() {
         let mut ctx = setup_with_rom(&[0x07]); // RLCA
@@ -623,6 +984,272 @@ mod tests {
         assert!(ctx.cpu.ime); // Now enabled after one instruction
     }
 
+    #[test]
+    fn test_reti_enables_ime_immediately() {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x0040] = 0xD9; // VBlank handler: RETI
+        rom[0x0048] = 0xD9; // STAT handler: RETI
+        let mut ctx = setup_with_rom_image(&rom);
+        ctx.memory.write(0xFFFF, 0x03); // IE: VBlank + STAT
+        ctx.memory.write_io_direct(io::IF, 0x01); // VBlank pending
+
+        // Service VBlank
+        assert_eq!(ctx.step(), 20);
+        assert_eq!(ctx.cpu.pc, 0x0040);
+        assert!(!ctx.cpu.ime);
+
+        // STAT becomes pending while inside the VBlank handler
+        ctx.memory.write_io_direct(io::IF, 0x02);
+
+        // RETI re-enables IME with no one-instruction delay
+        ctx.step();
+        assert_eq!(ctx.cpu.pc, 0x0100);
+        assert!(ctx.cpu.ime);
+        assert!(!ctx.cpu.ime_pending);
+
+        // The very next step dispatches STAT without executing at 0x0100
+        assert_eq!(ctx.step(), 20);
+        assert_eq!(ctx.cpu.pc, 0x0048);
+        assert_eq!(ctx.memory.read_io_direct(io::IF) & 0x02, 0);
+    }
+
+    #[test]
+    fn test_ei_halt_services_pending_interrupt() {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x0100] = 0xF3; // DI
+        rom[0x0101] = 0xFB; // EI
+        rom[0x0102] = 0x76; // HALT
+        rom[0x0040] = 0xD9; // VBlank handler: RETI
+        let mut ctx = setup_with_rom_image(&rom);
+        ctx.memory.write(0xFFFF, 0x01);
+        ctx.memory.write_io_direct(io::IF, 0x01); // VBlank already pending
+        ctx.cpu.ime = false;
+        ctx.cpu.sp = 0xFFFE;
+
+        ctx.step(); // DI
+        ctx.step(); // EI
+        assert!(!ctx.cpu.ime);
+
+        ctx.step(); // HALT executes; IME becomes set afterwards
+        assert!(ctx.cpu.halted);
+        assert!(ctx.cpu.ime);
+
+        // Interrupt is serviced and returns past the HALT
+        assert_eq!(ctx.step(), 20);
+        assert_eq!(ctx.cpu.pc, 0x0040);
+        assert!(!ctx.cpu.halted);
+        let ret_addr = ctx.memory.read(0xFFFC) as u16 | (ctx.memory.read(0xFFFD) as u16) << 8;
+        assert_eq!(ret_addr, 0x0103);
+    }
+
+    #[test]
+    fn test_ei_halt_wakes_on_later_interrupt() {
+        let mut ctx = setup_with_rom(&[0xF3, 0xFB, 0x76]); // DI; EI; HALT
+        ctx.memory.write(0xFFFF, 0x04);
+        ctx.memory.write_io_direct(io::IF, 0x00);
+
+        ctx.step();
+        ctx.step();
+        ctx.step();
+        assert!(ctx.cpu.halted && ctx.cpu.ime);
+        assert_eq!(ctx.step(), 4, "still halted");
+
+        ctx.memory.write_io_direct(io::IF, 0x04); // Timer
+        ctx.step();
+        assert_eq!(ctx.cpu.pc, 0x0050);
+    }
+
+    #[test]
+    fn test_halt_wakes_without_dispatch_when_ime_off() {
+        let mut ctx = setup_with_rom(&[0xF3, 0x76, 0x3C]); // DI; HALT; INC A
+        ctx.memory.write(0xFFFF, 0x04);
+        ctx.memory.write_io_direct(io::IF, 0x00);
+        ctx.cpu.sp = 0xFFFE;
+
+        ctx.step();
+        ctx.step();
+        assert!(ctx.cpu.halted && !ctx.cpu.ime);
+        assert_eq!(ctx.step(), 4, "still halted");
+
+        // Timer becomes pending: wake and run INC A instead of jumping to 0x0050
+        ctx.memory.write_io_direct(io::IF, 0x04);
+        let a = ctx.cpu.a;
+        ctx.step();
+        assert!(!ctx.cpu.halted);
+        assert_eq!(ctx.cpu.pc, 0x0103);
+        assert_eq!(ctx.cpu.a, a.wrapping_add(1));
+        assert_eq!(ctx.cpu.sp, 0xFFFE, "nothing pushed");
+        assert_eq!(
+            ctx.memory.read_io_direct(io::IF) & 0x04,
+            0x04,
+            "IF left pending"
+        );
+    }
+
+    #[test]
+    fn test_halt_bug_repeats_next_byte() {
+        let mut ctx = setup_with_rom(&[0xF3, 0x76, 0x3C, 0x00]); // DI; HALT; INC A; NOP
+        ctx.memory.write(0xFFFF, 0x04);
+        ctx.memory.write_io_direct(io::IF, 0x04); // timer pending but IME off
+        ctx.cpu.ime = false;
+        let a = ctx.cpu.a;
+
+        ctx.step();
+        ctx.step();
+        assert!(!ctx.cpu.halted, "HALT falls straight through");
+        assert_eq!(ctx.cpu.pc, 0x0102);
+
+        ctx.step();
+        assert_eq!(ctx.cpu.pc, 0x0102, "PC not incremented");
+        ctx.step();
+        assert_eq!(ctx.cpu.pc, 0x0103);
+        assert_eq!(ctx.cpu.a, a.wrapping_add(2), "INC A ran twice");
+        ctx.step();
+        assert_eq!(ctx.cpu.pc, 0x0104, "only once");
+    }
+
+    /// Dispatch a VBlank interrupt with SP=0xFF06, so the low PC byte is
+    /// pushed onto DIV (0xFF04) and resets it. Returns the timer after
+    /// a further 240 cycles.
+    fn dispatch_onto_div(accurate: bool) -> Timer {
+        let mut ctx = setup_with_rom(&[0x00]);
+        ctx.cpu.set_accurate(accurate);
+        ctx.cpu.sp = 0xFF06;
+        ctx.memory.write(0xFFFF, 0x01);
+        ctx.memory.write(0xFF0F, 0x01);
+
+        assert_eq!(ctx.step_with_timer(), 20);
+        assert_eq!(ctx.cpu.pc, 0x0040);
+        assert_eq!(ctx.cpu.sp, 0xFF04);
+        ctx.timer.tick(240, &mut ctx.memory, &ctx.ic);
+        ctx.timer
+    }
+
+    #[test]
+    fn test_interrupt_dispatch_push_cycle_offset() {
+        // Accurate: low byte pushed at the end of M4, so only M5 (4 cycles)
+        // elapses after the DIV reset: 4 + 240 = 244 < 256.
+        assert_eq!(dispatch_onto_div(true).read(0xFF04), 0x00);
+        // Batched: push happens before all 20 cycles: 20 + 240 = 260.
+        assert_eq!(dispatch_onto_div(false).read(0xFF04), 0x01);
+
+        // Accurate: the PPU advances with each M-cycle, so the high byte
+        // lands in VRAM at dot 76 (OAM scan) but the low byte at dot 80,
+        // when mode 3 has locked VRAM.
+        assert_eq!(dispatch_onto_vram(true), [0x00, 0x12]);
+        // Batched: both bytes land while the PPU is still at dot 64.
+        assert_eq!(dispatch_onto_vram(false), [0x34, 0x12]);
+    }
+
+    /// Dispatch a VBlank interrupt at PC=0x1234 with SP=0x8002, starting 16
+    /// dots before line 0 enters mode 3. Returns VRAM 0x8000-0x8001.
+    fn dispatch_onto_vram(accurate: bool) -> [u8; 2] {
+        let mut ctx = setup_with_rom(&[0x00]);
+        ctx.cpu.set_accurate(accurate);
+        ctx.memory.set_access_blocking(true);
+        ctx.memory.write(0xFF40, 0x91);
+        ctx.ppu.tick(64, &mut ctx.memory, &ctx.ic);
+        ctx.cpu.pc = 0x1234;
+        ctx.cpu.sp = 0x8002;
+        ctx.memory.write(0xFFFF, 0x01);
+        ctx.memory.write(0xFF0F, 0x01);
+
+        assert_eq!(ctx.step_with_timer(), 20);
+        assert_eq!(ctx.cpu.pc, 0x0040);
+        let ppu = ctx.ppu.get_debug_state();
+        assert_eq!((ppu.line, ppu.mode, ppu.cycles), (0, 3, 4));
+        [ctx.memory.read(0x8000), ctx.memory.read(0x8001)]
+    }
+
+    #[test]
+    fn test_ei_di_keeps_ime_disabled() {
+        let mut ctx = setup_with_rom(&[0xFB, 0xF3, 0x00]); // EI; DI; NOP
+        ctx.cpu.ime = false;
+        ctx.step();
+        ctx.step();
+        ctx.step();
+        assert!(!ctx.cpu.ime);
+    }
+
+    /// LD A, 0x12; LD BC, 0x3456; EI; INC A; PUSH BC; DEC C; JR -3
+    const SNAPSHOT_PROGRAM: [u8; 11] = [
+        0x3E, 0x12, 0x01, 0x56, 0x34, 0xFB, 0x3C, 0xC5, 0x0D, 0x18, 0xFD,
+    ];
+
+    #[test]
+    fn test_snapshot_restore_round_trip() {
+        let mut ctx = setup_with_rom(&SNAPSHOT_PROGRAM);
+        ctx.cpu.ime = false;
+        for _ in 0..3 {
+            ctx.step();
+        }
+        let state = ctx.cpu.snapshot();
+        assert_eq!((state.a, state.pc, state.sp), (0x12, 0x0106, 0xFFFE));
+        assert!(state.ime_pending && !state.ime, "EI still pending");
+
+        for _ in 0..6 {
+            ctx.step();
+        }
+        assert_ne!(ctx.cpu.snapshot(), state);
+        ctx.cpu.restore(&state);
+        assert_eq!(ctx.cpu.snapshot(), state);
+
+        // Execution resumes identically, including the delayed EI
+        let mut fresh = setup_with_rom(&SNAPSHOT_PROGRAM);
+        fresh.cpu.restore(&state);
+        for _ in 0..3 {
+            ctx.step();
+            fresh.step();
+        }
+        assert_eq!(ctx.cpu.snapshot(), fresh.cpu.snapshot());
+        assert!(fresh.cpu.ime);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_cpu_state_serde_round_trip() {
+        let mut ctx = setup_with_rom(&SNAPSHOT_PROGRAM);
+        for _ in 0..5 {
+            ctx.step();
+        }
+        let state = ctx.cpu.snapshot();
+        let json = serde_json::to_string(&state).unwrap();
+        let back: CpuState = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, state);
+    }
+
+    #[test]
+    fn test_ei_di_blocks_pending_vblank() {
+        let mut ctx = setup_with_rom(&[0xFB, 0xF3, 0x00]); // EI; DI; NOP
+        ctx.cpu.ime = false;
+        ctx.memory.write(0xFFFF, 0x01);
+        ctx.memory.write_io_direct(io::IF, 0x01);
+
+        for pc in [0x0101, 0x0102, 0x0103] {
+            ctx.step();
+            assert_eq!(ctx.cpu.pc, pc, "no dispatch between EI and DI");
+        }
+        assert_eq!(
+            ctx.memory.read_io_direct(io::IF) & 0x01,
+            0x01,
+            "VBlank still pending"
+        );
+    }
+
+    #[test]
+    fn test_ei_delays_dispatch_by_one_instruction() {
+        let mut ctx = setup_with_rom(&[0xFB, 0x00, 0x00]); // EI; NOP; NOP
+        ctx.cpu.ime = false;
+        ctx.memory.write(0xFFFF, 0x01);
+        ctx.memory.write_io_direct(io::IF, 0x01);
+
+        ctx.step(); // EI
+        ctx.step(); // NOP runs before IME takes effect
+        assert_eq!(ctx.cpu.pc, 0x0102);
+        ctx.step();
+        assert_eq!(ctx.cpu.pc, 0x0040, "VBlank dispatched after the NOP");
+    }
+
     #[test]
     fn test_ld_hl_n() {
         let mut ctx = setup_with_rom(&[
@@ -638,12 +1265,112 @@ mod tests {
         assert_eq!(bus.read(0xC000), 0x42);
     }
 
+    #[test]
+    fn test_ld_hli_store_wraps_from_ffff() {
+        let mut ctx = setup_with_rom(&[
+            0x22, // LD (HL+), A
+            0x22, // LD (HL+), A
+        ]);
+        ctx.cpu.set_hl(0xFFFF);
+        ctx.cpu.a = 0x1F;
+        ctx.cpu.ime = false; // enabling IE must not dispatch a pending IF
+
+        assert_eq!(ctx.step(), 8);
+        assert_eq!(ctx.memory.get_ie(), 0x1F, "0xFFFF is IE");
+        assert_eq!(ctx.cpu.hl(), 0x0000);
+
+        // HL=0x0000 is ROM: the write is an MBC register poke, ROM is unchanged
+        ctx.step();
+        assert_eq!(ctx.memory.read(0x0000), 0x00);
+        assert_eq!(ctx.cpu.hl(), 0x0001);
+    }
+
+    #[test]
+    fn test_ld_hld_load_wraps_from_0000() {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x0000] = 0x5A;
+        rom[0x0100] = 0x3A; // LD A, (HL-)
+        rom[0x0101] = 0x3A; // LD A, (HL-)
+        let mut ctx = setup_with_rom_image(&rom);
+        ctx.cpu.set_hl(0x0000);
+        ctx.cpu.ime = false;
+        ctx.memory.write(0xFFFF, 0x05);
+
+        assert_eq!(ctx.step(), 8);
+        assert_eq!(ctx.cpu.a, 0x5A);
+        assert_eq!(ctx.cpu.hl(), 0xFFFF);
+
+        ctx.step();
+        assert_eq!(ctx.cpu.a, 0x05, "second load reads IE");
+        assert_eq!(ctx.cpu.hl(), 0xFFFE);
+    }
+
+    #[test]
+    fn test_inc_dec_hl_indirect() {
+        let mut ctx = setup_with_rom(&[
+            0x34, // INC (HL)
+            0x35, // DEC (HL)
+            0x35, // DEC (HL)
+        ]);
+        ctx.cpu.set_hl(0xC010);
+        ctx.memory.write(0xC010, 0xFF);
+        ctx.cpu.set_flag(FLAG_C, true);
+
+        assert_eq!(ctx.step(), 12);
+        assert_eq!(ctx.memory.read(0xC010), 0x00);
+        assert!(ctx.cpu.flag(FLAG_Z));
+        assert!(ctx.cpu.flag(FLAG_H));
+        assert!(ctx.cpu.flag(FLAG_C), "INC leaves carry untouched");
+
+        assert_eq!(ctx.step(), 12);
+        assert_eq!(ctx.memory.read(0xC010), 0xFF);
+        assert!(ctx.cpu.flag(FLAG_N));
+        assert!(ctx.cpu.flag(FLAG_H));
+
+        assert_eq!(ctx.step(), 12);
+        assert_eq!(ctx.memory.read(0xC010), 0xFE);
+        assert!(!ctx.cpu.flag(FLAG_H));
+    }
+
+    #[test]
+    fn test_inc_hl_indirect_uses_bus_routing() {
+        // TIMA lives in the Timer, not Memory — the read and the write must
+        // both go through the bus for the RMW to land.
+        let mut ctx = setup_with_rom(&[0x34]); // INC (HL)
+        ctx.cpu.set_hl(0xFF05);
+        ctx.timer.write(0xFF05, 0x41);
+
+        assert_eq!(ctx.step(), 12);
+        assert_eq!(ctx.timer.read(0xFF05), 0x42);
+    }
+
+    #[test]
+    fn test_inc_hl_on_locked_vram_reads_ff_and_drops_write() {
+        // In accurate mode VRAM is locked during mode 3: the RMW reads 0xFF
+        // (so INC wraps to 0 and sets Z) and the write never lands.
+        let mut ctx = setup_with_rom(&[0x34, 0x34]); // INC (HL) twice
+        ctx.memory.write(0xFF40, 0x91);
+        ctx.memory.write(0x8000, 0x12);
+        ctx.memory.set_access_blocking(true);
+        ctx.memory.write_io_direct(crate::memory::io::STAT, 0x03); // mode 3
+        ctx.cpu.set_hl(0x8000);
+
+        assert_eq!(ctx.step(), 12);
+        assert!(ctx.cpu.flag(FLAG_Z), "incremented the 0xFF it read");
+        assert_eq!(ctx.memory.read(0x8000), 0x12, "write dropped");
+
+        // Unlocked in HBlank, the same instruction goes through
+        ctx.memory.write_io_direct(crate::memory::io::STAT, 0x00);
+        ctx.step();
+        assert_eq!(ctx.memory.read(0x8000), 0x13);
+    }
+
     // ── GBC initial register state ────────────────────────────────────────────
 
     #[test]
     fn test_reset_dmg_registers() {
         let mut cpu = Cpu::new();
-        cpu.reset(false);
+        cpu.reset(GbModel::Dmg);
         assert_eq!(cpu.a, 0x01, "A (DMG)");
         assert_eq!(cpu.f, 0xB0, "F (DMG)");
         assert_eq!(cpu.b, 0x00, "B (DMG)");
@@ -654,11 +1381,29 @@ mod tests {
         assert_eq!(cpu.l, 0x4D, "L (DMG)");
     }
 
+    #[test]
+    fn test_reset_mgb_registers() {
+        let mut cpu = Cpu::new();
+        cpu.reset(GbModel::Cgb);
+        cpu.reset(GbModel::Mgb);
+        assert_eq!(cpu.a, 0xFF, "A (MGB) — tells Pocket/Light apart from DMG");
+        assert_eq!(cpu.f, 0xB0, "F (MGB)");
+        assert_eq!(
+            (cpu.bc(), cpu.de(), cpu.hl()),
+            (0x0013, 0x00D8, 0x014D),
+            "BC/DE/HL (MGB)"
+        );
+        assert_eq!((cpu.sp, cpu.pc), (0xFFFE, 0x0100));
+    }
+
     #[test]
     fn test_reset_cgb_registers() {
         let mut cpu = Cpu::new();
-        cpu.reset(true);
-        assert_eq!(cpu.a, 0x11, "A (GBC) — hardware ID checked by games at 0x0100");
+        cpu.reset(GbModel::Cgb);
+        assert_eq!(
+            cpu.a, 0x11,
+            "A (GBC) — hardware ID checked by games at 0x0100"
+        );
         assert_eq!(cpu.f, 0x80, "F (GBC)");
         assert_eq!(cpu.b, 0x00, "B (GBC)");
         assert_eq!(cpu.c, 0x00, "C (GBC)");
@@ -692,8 +1437,14 @@ mod tests {
 
         ctx.step(); // STOP → reads via bus.read(0xFF4D) → sees speed_armed=1
 
-        assert!(!ctx.cpu.halted, "CPU must not halt when speed switch is triggered");
-        assert!(ctx.memory.is_double_speed(), "double speed should be active");
+        assert!(
+            !ctx.cpu.halted,
+            "CPU must not halt when speed switch is triggered"
+        );
+        assert!(
+            ctx.memory.is_double_speed(),
+            "double speed should be active"
+        );
     }
 
     #[test]
@@ -2,6 +2,7 @@
 
 use super::{Cpu, FLAG_C, FLAG_H, FLAG_N, FLAG_Z};
 use crate::bus::MemoryBus;
+use crate::memory::io;
 
 impl Cpu {
     pub(super) fn execute(&mut self, opcode: u8, bus: &mut MemoryBus) -> u32 {
@@ -1069,7 +1070,14 @@ impl Cpu {
 
             // Misc
             0x76 => {
-                self.halted = true;
+                // With IME off and an interrupt already pending, HALT exits
+                // at once and the HALT bug repeats the next byte
+                let pending = bus.get_ie() & bus.read_io_direct(io::IF) & 0x1F != 0;
+                if pending && !self.ime && !self.ime_pending {
+                    self.halt_bug = true;
+                } else {
+                    self.halted = true;
+                }
                 4
             } // HALT
             0x10 => {
@@ -1086,6 +1094,7 @@ impl Cpu {
             } // STOP / speed switch
             0xF3 => {
                 self.ime = false;
+                self.ime_pending = false;
                 4
             } // DI
             0xFB => {
@@ -1192,14 +1201,9 @@ impl Cpu {
                 self.execute_cb(cb_opcode, bus)
             }
 
-            _ => {
-                // Unimplemented opcode
-                panic!(
-                    "Unimplemented opcode: 0x{:02X} at PC: 0x{:04X}",
-                    opcode,
-                    self.pc.wrapping_sub(1)
-                );
-            }
+            // Illegal opcodes (0xD3, 0xDB, 0xDD, 0xE3, 0xE4, 0xEB-0xED,
+            // 0xF4, 0xFC, 0xFD) hang the CPU
+            _ => self.lock_up(opcode),
         }
     }
 
@@ -0,0 +1,296 @@
+//! Text command interface for external debuggers.
+//!
+//! [`DebugServer`] parses one-line commands in the spirit of the GDB remote
+//! protocol and answers with a one-line (or short multi-line) string, so a
+//! frontend can pipe it over a WebSocket, TCP socket or stdin. It drives any
+//! [`DebugTarget`]; `GameBoyCore` is the only implementation.
+//!
+//! | Command              | Effect                                         |
+//! |----------------------|------------------------------------------------|
+//! | `regs`               | Dump CPU registers                             |
+//! | `reg <name> <hex>`   | Set a register (`a`..`l`, `af`..`hl`, `sp`, `pc`) |
+//! | `mem <addr> [len]`   | Hex dump `len` bytes (default 16, max 256)     |
+//! | `write <addr> <b>..` | Write bytes starting at `addr`                 |
+//! | `break <addr>`       | Set a PC breakpoint                            |
+//! | `delete <addr>`      | Clear a PC breakpoint                          |
+//! | `step [n]`           | Execute `n` instructions (default 1)           |
+//! | `continue`           | Run until a breakpoint (or the step budget)    |
+//! | `info`               | PPU and timer state                            |
+//!
+//! Addresses and values are hex, with an optional `0x` or `$` prefix.
+
+use std::collections::BTreeSet;
+
+use crate::core::GameBoyCore;
+
+/// What the debug server needs from the emulator.
+#[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: DebugSession
+pub trait DebugTarget {
+    fn register(&self, name: &str) -> Option<u16>;
+    fn set_register(&mut self, name: &str, value: u16) -> bool;
+    /// Side-effect-free memory read.
+    fn peek(&self, addr: u16) -> u8;
+    /// Memory write through the normal bus routing.
+    fn poke(&mut self, addr: u16, value: u8);
+    /// Execute one instruction.
+    fn step(&mut self);
+    /// Human-readable PPU/timer summary.
+    fn info(&self) -> String;
+}
+
+impl DebugTarget for GameBoyCore {
+    fn register(&self, name: &str) -> Option<u16> {
+        self.cpu.register(name)
+    }
+
+    fn set_register(&mut self, name: &str, value: u16) -> bool {
+        self.cpu.set_register(name, value)
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        self.memory.peek(addr)
+    }
+
+    fn poke(&mut self, addr: u16, value: u8) {
+        self.memory.write(addr, value);
+    }
+
+    fn step(&mut self) {
+        self.step_single();
+    }
+
+    fn info(&self) -> String {
+        format!(
+            "PPU: {}\nTimer: counter={:04X} TIMA={:02X} TMA={:02X} TAC={:02X}",
+            self.ppu.get_debug_state(),
+            self.timer.internal_counter(),
+            self.timer.read(0xFF05),
+            self.timer.read(0xFF06),
+            self.timer.read(0xFF07),
+        )
+    }
+}
+
+/// Instructions `continue` runs before giving up (about 60 frames).
+const CONTINUE_BUDGET: u32 = 60 * 17_556;
+
+/// Longest `mem` dump in one response.
+const MAX_DUMP: usize = 256;
+
+/// Wraps a [`DebugTarget`] and answers text commands.
+#[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: DebugSession
+pub struct DebugServer<T: DebugTarget> {
+    target: T,
+    breakpoints: BTreeSet<u16>,
+}
+
+#[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: DebugSession
+impl<T: DebugTarget> DebugServer<T> {
+    pub fn new(target: T) -> Self {
+        DebugServer {
+            target,
+            breakpoints: BTreeSet::new(),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn target(&self) -> &T {
+        &self.target
+    }
+
+    /// Stop debugging and hand the target back.
+    pub fn into_target(self) -> T {
+        self.target
+    }
+
+    /// Run one command line and return the response. Errors start with `E `.
+    pub fn handle_command(&mut self, line: &str) -> String {
+        let mut words = line.split_whitespace();
+        let Some(cmd) = words.next() else {
+            return "E empty command".to_string();
+        };
+        let args: Vec<&str> = words.collect();
+        match self.dispatch(cmd, &args) {
+            Ok(reply) => reply,
+            Err(e) => format!("E {}", e),
+        }
+    }
+
+    fn dispatch(&mut self, cmd: &str, args: &[&str]) -> Result<String, String> {
+        match (cmd, args) {
+            ("regs", []) => Ok(self.regs()),
+            ("reg", [name, value]) => {
+                let name = name.to_ascii_lowercase();
+                if self.target.set_register(&name, parse_hex(value)?) {
+                    Ok("OK".to_string())
+                } else {
+                    Err(format!("unknown register '{}'", name))
+                }
+            }
+            ("mem", [addr, rest @ ..]) if rest.len() <= 1 => {
+                let addr = parse_hex(addr)?;
+                let len = match rest {
+                    [len] => (parse_hex(len)? as usize).clamp(1, MAX_DUMP),
+                    _ => 16,
+                };
+                let bytes: Vec<String> = (0..len)
+                    .map(|i| format!("{:02X}", self.target.peek(addr.wrapping_add(i as u16))))
+                    .collect();
+                Ok(format!("{:04X}: {}", addr, bytes.join(" ")))
+            }
+            ("write", [addr, bytes @ ..]) if !bytes.is_empty() => {
+                let addr = parse_hex(addr)?;
+                let values = bytes
+                    .iter()
+                    .map(|b| match parse_hex(b)? {
+                        v @ 0..=0xFF => Ok(v as u8),
+                        _ => Err(format!("'{}' is not a byte", b)),
+                    })
+                    .collect::<Result<Vec<u8>, String>>()?;
+                for (i, v) in values.into_iter().enumerate() {
+                    self.target.poke(addr.wrapping_add(i as u16), v);
+                }
+                Ok("OK".to_string())
+            }
+            ("break", [addr]) => {
+                let addr = parse_hex(addr)?;
+                self.breakpoints.insert(addr);
+                Ok(format!("Breakpoint set at {:04X}", addr))
+            }
+            ("delete", [addr]) => {
+                let addr = parse_hex(addr)?;
+                if self.breakpoints.remove(&addr) {
+                    Ok(format!("Breakpoint cleared at {:04X}", addr))
+                } else {
+                    Err(format!("no breakpoint at {:04X}", addr))
+                }
+            }
+            ("step", rest) if rest.len() <= 1 => {
+                let count = match rest {
+                    [n] => n.parse::<u32>().map_err(|_| format!("bad count '{}'", n))?,
+                    _ => 1,
+                };
+                for _ in 0..count {
+                    self.target.step();
+                }
+                Ok(self.regs())
+            }
+            ("continue", []) => Ok(self.resume()),
+            ("info", []) => Ok(self.target.info()),
+            _ => Err(format!("unknown command or bad arguments: '{}'", cmd)),
+        }
+    }
+
+    /// Step at least once, then until a breakpoint or the budget runs out.
+    fn resume(&mut self) -> String {
+        for _ in 0..CONTINUE_BUDGET {
+            self.target.step();
+            let pc = self.pc();
+            if self.breakpoints.contains(&pc) {
+                return format!("Breakpoint hit at {:04X}", pc);
+            }
+        }
+        format!(
+            "Stopped after {} instructions at {:04X}",
+            CONTINUE_BUDGET,
+            self.pc()
+        )
+    }
+
+    fn pc(&self) -> u16 {
+        self.target.register("pc").unwrap_or(0)
+    }
+
+    fn regs(&self) -> String {
+        let r = |name| self.target.register(name).unwrap_or(0);
+        format!(
+            "A={:02X} F={:02X} B={:02X} C={:02X} D={:02X} E={:02X} H={:02X} L={:02X} SP={:04X} PC={:04X}",
+            r("a"),
+            r("f"),
+            r("b"),
+            r("c"),
+            r("d"),
+            r("e"),
+            r("h"),
+            r("l"),
+            r("sp"),
+            r("pc"),
+        )
+    }
+}
+
+fn parse_hex(s: &str) -> Result<u16, String> {
+    let digits = s
+        .trim_start_matches("0x")
+        .trim_start_matches("0X")
+        .trim_start_matches('$');
+    u16::from_str_radix(digits, 16).map_err(|_| format!("bad hex value '{}'", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// NOP sled at 0x100 with `JP 0x0150` at 0x0104 and an `INC A` loop at 0x0150.
+    fn server() -> DebugServer<GameBoyCore> {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x104..0x107].copy_from_slice(&[0xC3, 0x50, 0x01]); // JP 0x0150
+        rom[0x150..0x153].copy_from_slice(&[0x3C, 0x18, 0xFD]); // INC A; JR -3
+        let mut core = GameBoyCore::new();
+        core.load_rom(&rom, false).unwrap();
+        DebugServer::new(core)
+    }
+
+    #[test]
+    fn test_step_reports_new_pc() {
+        let mut dbg = server();
+        let reply = dbg.handle_command("step");
+        assert!(reply.ends_with("PC=0101"), "{}", reply);
+        let reply = dbg.handle_command("step 3");
+        assert!(reply.ends_with("PC=0104"), "{}", reply);
+    }
+
+    #[test]
+    fn test_mem_dump_and_write() {
+        let mut dbg = server();
+        assert_eq!(dbg.handle_command("write c000 12 34 ab"), "OK");
+        assert_eq!(
+            dbg.handle_command("mem c000"),
+            "C000: 12 34 AB 00 00 00 00 00 00 00 00 00 00 00 00 00"
+        );
+        assert_eq!(dbg.handle_command("mem $C001 2"), "C001: 34 AB");
+    }
+
+    #[test]
+    fn test_break_then_continue() {
+        let mut dbg = server();
+        assert_eq!(dbg.handle_command("break 0150"), "Breakpoint set at 0150");
+        assert_eq!(dbg.handle_command("continue"), "Breakpoint hit at 0150");
+        assert_eq!(dbg.target().cpu.register("pc"), Some(0x0150));
+
+        // Continuing from a breakpoint runs the loop body once and stops again
+        let a = dbg.target().cpu.register("a").unwrap();
+        assert_eq!(dbg.handle_command("continue"), "Breakpoint hit at 0150");
+        assert_eq!(dbg.target().cpu.register("a"), Some((a + 1) & 0xFF));
+
+        assert_eq!(
+            dbg.handle_command("delete 0150"),
+            "Breakpoint cleared at 0150"
+        );
+        assert!(dbg.handle_command("delete 0150").starts_with("E "));
+    }
+
+    #[test]
+    fn test_registers_and_errors() {
+        let mut dbg = server();
+        assert_eq!(dbg.handle_command("reg hl c123"), "OK");
+        assert_eq!(dbg.target().cpu.register("hl"), Some(0xC123));
+        assert!(
+            dbg.handle_command("reg xy 1")
+                .starts_with("E unknown register")
+        );
+        assert!(dbg.handle_command("mem zz").starts_with("E bad hex"));
+        assert!(dbg.handle_command("frobnicate").starts_with("E "));
+        assert!(dbg.handle_command("info").contains("Timer: counter="));
+    }
+}
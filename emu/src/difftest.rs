@@ -0,0 +1,102 @@
+//! Differential testing: run one ROM on two differently configured cores in
+//! lockstep and report where they first disagree. Useful for checking that a
+//! refactor (or a fast path) is observably identical to the code it replaces.
+
+use std::fmt;
+
+use crate::core::GameBoyCore;
+
+/// Where two runs first differed.
+#[derive(Debug)]
+pub(crate) struct Divergence {
+    /// Index of the frame after which the states differed (0-based).
+    pub frame: u32,
+    /// First differing address in the 64 KiB bus view, or `None` when only
+    /// CPU registers or the frame buffer differ.
+    pub addr: Option<u16>,
+    /// `debug_dump` of each core at the divergence point.
+    pub dump_a: String,
+    pub dump_b: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.addr {
+            Some(addr) => writeln!(f, "diverged after frame {} at {:04X}", self.frame, addr)?,
+            None => writeln!(f, "diverged after frame {} (CPU/frame buffer)", self.frame)?,
+        }
+        write!(f, "--- A ---\n{}--- B ---\n{}", self.dump_a, self.dump_b)
+    }
+}
+
+/// Load `rom` (DMG mode) into two cores, let `setup_a` / `setup_b` adjust
+/// each one before every frame (they receive the frame index), and step both
+/// for `frames` frames. Returns the first divergence, or `None`.
+pub(crate) fn diff_run(
+    rom: &[u8],
+    frames: u32,
+    mut setup_a: impl FnMut(&mut GameBoyCore, u32),
+    mut setup_b: impl FnMut(&mut GameBoyCore, u32),
+) -> Option<Divergence> {
+    let mut a = GameBoyCore::new();
+    let mut b = GameBoyCore::new();
+    a.load_rom(rom, false).expect("ROM A failed to load");
+    b.load_rom(rom, false).expect("ROM B failed to load");
+
+    for frame in 0..frames {
+        setup_a(&mut a, frame);
+        setup_b(&mut b, frame);
+        a.step_frame();
+        b.step_frame();
+
+        let addr = (0..=0xFFFFu16).find(|&addr| a.memory.read(addr) != b.memory.read(addr));
+        let cpu_differs =
+            a.cpu.get_debug_state().to_string() != b.cpu.get_debug_state().to_string();
+        let frame_differs = a.ppu.get_buffer() != b.ppu.get_buffer();
+        if addr.is_some() || cpu_differs || frame_differs {
+            return Some(Divergence {
+                frame,
+                addr,
+                dump_a: a.debug_dump(),
+                dump_b: b.debug_dump(),
+            });
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn film_rom() -> Vec<u8> {
+        gb_film::build_rom(&gb_film::CameraConfig::default())
+    }
+
+    #[test]
+    fn test_identical_cores_do_not_diverge() {
+        let divergence = diff_run(&film_rom(), 6, |_, _| {}, |_, _| {});
+        if let Some(divergence) = divergence {
+            panic!("{}", divergence);
+        }
+    }
+
+    #[test]
+    fn test_perturbed_core_reports_divergence() {
+        // HRAM 0xFFF0 is unused by the film ROM; poke it in B before frame 3.
+        let perturb = |core: &mut GameBoyCore, frame: u32| {
+            if frame == 3 {
+                core.memory.write(0xFFF0, 0x5A);
+            }
+        };
+        let divergence = diff_run(&film_rom(), 6, |_, _| {}, perturb).expect("should diverge");
+        assert_eq!(divergence.frame, 3);
+        assert_eq!(divergence.addr, Some(0xFFF0));
+        assert!(
+            divergence
+                .to_string()
+                .starts_with("diverged after frame 3 at FFF0")
+        );
+        assert!(divergence.dump_b.contains("frame=4"));
+    }
+}
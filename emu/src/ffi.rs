@@ -9,6 +9,8 @@ use std::ptr;
 use std::slice;
 
 use crate::core::GameBoyCore;
+use crate::joypad::Button;
+use crate::memory::camera::PhotoError;
 
 /// Opaque GameBoy emulator handle for FFI.
 struct GameBoyHandle {
@@ -45,7 +47,12 @@ pub extern "C" fn gb_destroy(handle: *mut c_void) {
 /// Pass cgb_mode=false for standard DMG mode (existing behaviour).
 /// Returns true on success, false on failure.
 #[unsafe(no_mangle)]
-pub extern "C" fn gb_load_rom(handle: *mut c_void, data: *const u8, len: usize, cgb_mode: bool) -> bool {
+pub extern "C" fn gb_load_rom(
+    handle: *mut c_void,
+    data: *const u8,
+    len: usize,
+    cgb_mode: bool,
+) -> bool {
     if handle.is_null() || data.is_null() || len == 0 {
         return false;
     }
@@ -57,6 +64,21 @@ pub extern "C" fn gb_load_rom(handle: *mut c_void, data: *const u8, len: usize,
     }
 }
 
+/// Install a DMG (256-byte) or CGB (2304-byte) boot ROM to run before the
+/// cartridge on every subsequent gb_load_rom. Returns false on a bad size.
+#[unsafe(no_mangle)]
+pub extern "C" fn gb_load_boot_rom(handle: *mut c_void, data: *const u8, len: usize) -> bool {
+    if handle.is_null() || data.is_null() {
+        return false;
+    }
+
+    unsafe {
+        let gb = &mut *(handle as *mut GameBoyHandle);
+        let boot_rom = slice::from_raw_parts(data, len);
+        gb.core.load_boot_rom(boot_rom).is_ok()
+    }
+}
+
 /// Run one frame of emulation (~16.74ms of Game Boy time).
 #[unsafe(no_mangle)]
 pub extern "C" fn gb_step_frame(handle: *mut c_void) {
@@ -70,6 +92,20 @@ pub extern "C" fn gb_step_frame(handle: *mut c_void) {
     }
 }
 
+/// Pause or resume emulation bookkeeping. While paused the cartridge RTC
+/// does not advance, and it resumes without jumping by the paused duration.
+#[unsafe(no_mangle)]
+pub extern "C" fn gb_set_paused(handle: *mut c_void, paused: bool) {
+    if handle.is_null() {
+        return;
+    }
+
+    unsafe {
+        let gb = &mut *(handle as *mut GameBoyHandle);
+        gb.core.set_paused(paused);
+    }
+}
+
 /// Get a pointer to the frame buffer (160x144 RGBA pixels).
 /// The buffer is owned by the emulator and valid until the next call or destruction.
 /// Returns NULL if handle is invalid.
@@ -117,6 +153,37 @@ pub extern "C" fn gb_set_button(handle: *mut c_void, button: u8, pressed: bool)
     }
 }
 
+/// Autofire a held button (0=A .. 7=Down) at `rate_hz` presses per second.
+/// A rate of 0 turns autofire off.
+#[unsafe(no_mangle)]
+pub extern "C" fn gb_set_turbo(handle: *mut c_void, button: u8, rate_hz: f32) {
+    let Some(button) = Button::from_u8(button) else {
+        return;
+    };
+    if handle.is_null() {
+        return;
+    }
+
+    unsafe {
+        let gb = &mut *(handle as *mut GameBoyHandle);
+        gb.core.set_turbo(button, rate_hz);
+    }
+}
+
+/// Map an analog stick onto the D-pad. `x`/`y` in -1.0..=1.0 (+y = down);
+/// each axis presses its direction beyond `deadzone`.
+#[unsafe(no_mangle)]
+pub extern "C" fn gb_set_analog_dpad(handle: *mut c_void, x: f32, y: f32, deadzone: f32) {
+    if handle.is_null() {
+        return;
+    }
+
+    unsafe {
+        let gb = &mut *(handle as *mut GameBoyHandle);
+        gb.core.set_analog_dpad(x, y, deadzone);
+    }
+}
+
 /// Set camera image data for Game Boy Camera emulation.
 /// Expects 128x112 pixels as 8-bit grayscale (0=black, 255=white).
 #[unsafe(no_mangle)]
@@ -240,7 +307,7 @@ pub extern "C" fn gb_get_frame_count(handle: *const c_void) -> u32 {
     }
 }
 
-/// Get cartridge RAM (save data) size.
+/// Get cartridge RAM (save data) size. 0 for carts without a battery.
 #[unsafe(no_mangle)]
 pub extern "C" fn gb_get_save_size(handle: *const c_void) -> usize {
     if handle.is_null() {
@@ -249,12 +316,28 @@ pub extern "C" fn gb_get_save_size(handle: *const c_void) -> usize {
 
     unsafe {
         let gb = &*(handle as *const GameBoyHandle);
+        if !gb.core.has_battery() {
+            return 0;
+        }
         gb.core.memory.get_cartridge_ram().len()
     }
 }
 
+/// Whether the cartridge has a battery (only then is a `.sav` worth writing).
+#[unsafe(no_mangle)]
+pub extern "C" fn gb_has_battery(handle: *const c_void) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+
+    unsafe {
+        let gb = &*(handle as *const GameBoyHandle);
+        gb.core.has_battery()
+    }
+}
+
 /// Copy cartridge RAM (save data) to the provided buffer.
-/// Returns the number of bytes copied, or 0 on error.
+/// Returns the number of bytes copied, or 0 on error or for carts without a battery.
 #[unsafe(no_mangle)]
 pub extern "C" fn gb_get_save_data(
     handle: *const c_void,
@@ -267,7 +350,7 @@ pub extern "C" fn gb_get_save_data(
 
     unsafe {
         let gb = &*(handle as *const GameBoyHandle);
-        let ram = gb.core.memory.get_cartridge_ram();
+        let ram = gb.core.save_data();
         let copy_len = ram.len().min(buffer_len);
 
         if copy_len > 0 {
@@ -294,6 +377,111 @@ pub extern "C" fn gb_load_save_data(handle: *mut c_void, data: *const u8, len: u
     }
 }
 
+/// Whether the cartridge rumble motor is on; poll once per frame for haptics.
+#[unsafe(no_mangle)]
+pub extern "C" fn gb_is_rumbling(handle: *const c_void) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+
+    unsafe {
+        let gb = &*(handle as *const GameBoyHandle);
+        gb.core.memory.is_rumbling()
+    }
+}
+
+/// Plug a Game Boy Printer into the link port (false unplugs it).
+#[unsafe(no_mangle)]
+pub extern "C" fn gb_enable_printer(handle: *mut c_void, enable: bool) {
+    if handle.is_null() {
+        return;
+    }
+
+    unsafe {
+        let gb = &mut *(handle as *mut GameBoyHandle);
+        gb.core.enable_printer(enable);
+    }
+}
+
+/// Height in pixels of the current printout (it is always 160 wide).
+#[unsafe(no_mangle)]
+pub extern "C" fn gb_printer_image_height(handle: *const c_void) -> usize {
+    if handle.is_null() {
+        return 0;
+    }
+
+    unsafe {
+        let gb = &*(handle as *const GameBoyHandle);
+        gb.core.printer_image_height()
+    }
+}
+
+/// Copy the current printout (160 × height RGBA) to the provided buffer.
+/// Returns the number of bytes copied; 0 on error or before a print.
+#[unsafe(no_mangle)]
+pub extern "C" fn gb_printer_image(
+    handle: *const c_void,
+    buffer: *mut u8,
+    buffer_len: usize,
+) -> usize {
+    if handle.is_null() || buffer.is_null() {
+        return 0;
+    }
+
+    unsafe {
+        let gb = &*(handle as *const GameBoyHandle);
+        let image = gb.core.printer_image();
+        let copy_len = image.len().min(buffer_len);
+
+        if copy_len > 0 {
+            ptr::copy_nonoverlapping(image.as_ptr(), buffer, copy_len);
+        }
+
+        copy_len
+    }
+}
+
+/// Copy the MBC3 clock (48-byte BGB/VBA RTC block) to the provided buffer.
+/// Returns the number of bytes copied; 0 on error or without an RTC.
+#[unsafe(no_mangle)]
+pub extern "C" fn gb_get_rtc_data(
+    handle: *const c_void,
+    buffer: *mut u8,
+    buffer_len: usize,
+) -> usize {
+    if handle.is_null() || buffer.is_null() {
+        return 0;
+    }
+
+    unsafe {
+        let gb = &*(handle as *const GameBoyHandle);
+        let rtc = gb.core.memory.get_rtc_data();
+        let copy_len = rtc.len().min(buffer_len);
+
+        if copy_len > 0 {
+            ptr::copy_nonoverlapping(rtc.as_ptr(), buffer, copy_len);
+        }
+
+        copy_len
+    }
+}
+
+/// Restore the MBC3 clock from a `gb_get_rtc_data` block. The clock catches
+/// up on the time since it was saved. Returns false without an RTC.
+#[unsafe(no_mangle)]
+pub extern "C" fn gb_load_rtc_data(handle: *mut c_void, data: *const u8, len: usize) -> bool {
+    if handle.is_null() || data.is_null() {
+        return false;
+    }
+
+    unsafe {
+        let gb = &mut *(handle as *mut GameBoyHandle);
+        gb.core
+            .memory
+            .load_rtc_data(slice::from_raw_parts(data, len))
+    }
+}
+
 /// Get the current camera contrast level (0-15, or -1 if unknown).
 #[unsafe(no_mangle)]
 pub extern "C" fn gb_camera_contrast(handle: *const c_void) -> i32 {
@@ -321,7 +509,9 @@ pub extern "C" fn gb_set_camera_exposure(handle: *mut c_void, exposure: i32) {
         if exposure < 0 {
             gb.core.memory.set_camera_exposure_override(None);
         } else {
-            gb.core.memory.set_camera_exposure_override(Some(exposure as u16));
+            gb.core
+                .memory
+                .set_camera_exposure_override(Some(exposure as u16));
         }
     }
 }
@@ -352,6 +542,55 @@ pub extern "C" fn gb_encode_camera_photo(
     }
 }
 
+/// Decode a photo slot like `gb_decode_camera_photo`, reporting failures.
+/// Returns the number of bytes written, or a negated `GB_PHOTO_ERR_*` code.
+#[unsafe(no_mangle)]
+pub extern "C" fn gb_try_decode_camera_photo(
+    handle: *const c_void,
+    slot: u8,
+    buffer: *mut u8,
+    buffer_len: usize,
+) -> i32 {
+    if handle.is_null() || buffer.is_null() {
+        return -PhotoError::NoCamera.code();
+    }
+
+    unsafe {
+        let gb = &*(handle as *const GameBoyHandle);
+        match gb.core.try_decode_camera_photo(slot) {
+            Ok(rgba) => {
+                let copy_len = rgba.len().min(buffer_len);
+                ptr::copy_nonoverlapping(rgba.as_ptr(), buffer, copy_len);
+                copy_len as i32
+            }
+            Err(e) => -e.code(),
+        }
+    }
+}
+
+/// Encode a photo slot like `gb_encode_camera_photo`, reporting failures.
+/// Returns 0 on success, or a negated `GB_PHOTO_ERR_*` code.
+#[unsafe(no_mangle)]
+pub extern "C" fn gb_try_encode_camera_photo(
+    handle: *mut c_void,
+    slot: u8,
+    rgba: *const u8,
+    len: usize,
+) -> i32 {
+    if handle.is_null() || rgba.is_null() {
+        return -PhotoError::NoCamera.code();
+    }
+
+    unsafe {
+        let gb = &mut *(handle as *mut GameBoyHandle);
+        let data = slice::from_raw_parts(rgba, len);
+        match gb.core.try_encode_camera_photo(slot, data) {
+            Ok(()) => 0,
+            Err(e) => -e.code(),
+        }
+    }
+}
+
 /// Clear a GB Camera SRAM slot (zero out tile data).
 /// Slots 1-30 = saved photos.
 #[unsafe(no_mangle)]
@@ -366,6 +605,20 @@ pub extern "C" fn gb_clear_camera_photo_slot(handle: *mut c_void, slot: u8) {
     }
 }
 
+/// Erase all saved photos (slots 1-30) and reset the SRAM state vector.
+/// The active capture buffer is kept.
+#[unsafe(no_mangle)]
+pub extern "C" fn gb_clear_camera_roll(handle: *mut c_void) {
+    if handle.is_null() {
+        return;
+    }
+
+    unsafe {
+        let gb = &mut *(handle as *mut GameBoyHandle);
+        gb.core.clear_camera_roll();
+    }
+}
+
 /// Get the number of occupied photo slots (0-30) by scanning the SRAM state vector.
 #[unsafe(no_mangle)]
 pub extern "C" fn gb_camera_photo_count(handle: *const c_void) -> u8 {
@@ -379,6 +632,36 @@ pub extern "C" fn gb_camera_photo_count(handle: *const c_void) -> u8 {
     }
 }
 
+/// Set the display colour LUT: `lut` points to 256 RGBA entries (1024 bytes),
+/// each remapping one channel value. Pass NULL to clear it.
+/// Returns false on a bad handle or length.
+#[unsafe(no_mangle)]
+pub extern "C" fn gb_set_output_lut(handle: *mut c_void, lut: *const u8, len: usize) -> bool {
+    if handle.is_null() {
+        return false;
+    }
+
+    unsafe {
+        let gb = &mut *(handle as *mut GameBoyHandle);
+        let bytes = (!lut.is_null()).then(|| slice::from_raw_parts(lut, len));
+        gb.core.set_output_lut(bytes).is_ok()
+    }
+}
+
+/// Emulate DMG LCD ghosting: `factor` (0.0-0.95) of each pixel's previous
+/// level is kept every frame. 0 turns it off.
+#[unsafe(no_mangle)]
+pub extern "C" fn gb_set_lcd_ghosting(handle: *mut c_void, factor: f32) {
+    if handle.is_null() {
+        return;
+    }
+
+    unsafe {
+        let gb = &mut *(handle as *mut GameBoyHandle);
+        gb.core.set_lcd_ghosting(factor);
+    }
+}
+
 /// Read a byte from any memory address (for HRAM polling etc.).
 #[unsafe(no_mangle)]
 pub extern "C" fn gb_read_memory(handle: *const c_void, addr: u16) -> u8 {
@@ -0,0 +1,115 @@
+//! Game Boy tile format conversions.
+//!
+//! A 2bpp tile is 8×8 pixels in 16 bytes: each row is a low-bitplane byte
+//! followed by a high-bitplane byte, leftmost pixel in bit 7. Pixel values
+//! are 2-bit colour indices (0 = lightest with the default palette). These
+//! helpers are shared by the camera SRAM encode/decode and by tools.
+
+/// The DMG gray ramp used to turn colour indices 0-3 into RGB.
+pub(crate) const GRAY: [u8; 4] = [0xFF, 0xAA, 0x55, 0x00];
+
+/// Expand an 8×8 1bpp mask (one byte per row, bit 7 = leftmost) to a 2bpp
+/// tile, drawing set bits in colour `on` and clear bits in colour `off`.
+pub fn pack_1bpp_to_2bpp(rows: &[u8; 8], on: u8, off: u8) -> [u8; 16] {
+    let mut pixels = [0u8; 64];
+    for (y, &row) in rows.iter().enumerate() {
+        for x in 0..8 {
+            pixels[y * 8 + x] = if row & (0x80 >> x) != 0 { on } else { off };
+        }
+    }
+    pack_2bpp_tile(&pixels)
+}
+
+/// Pack 64 colour indices (row-major, only the low 2 bits used) into a tile.
+pub fn pack_2bpp_tile(pixels: &[u8; 64]) -> [u8; 16] {
+    let mut tile = [0u8; 16];
+    for (row, chunk) in pixels.chunks_exact(8).enumerate() {
+        for (col, &color) in chunk.iter().enumerate() {
+            let bit = 7 - col;
+            tile[row * 2] |= (color & 0x01) << bit;
+            tile[row * 2 + 1] |= ((color >> 1) & 0x01) << bit;
+        }
+    }
+    tile
+}
+
+/// Unpack a tile into 64 colour indices (row-major).
+pub fn unpack_2bpp_tile(tile: &[u8; 16]) -> [u8; 64] {
+    let mut pixels = [0u8; 64];
+    for row in 0..8 {
+        let (low, high) = (tile[row * 2], tile[row * 2 + 1]);
+        for col in 0..8 {
+            let bit = 7 - col;
+            pixels[row * 8 + col] = ((high >> bit) & 1) << 1 | ((low >> bit) & 1);
+        }
+    }
+    pixels
+}
+
+/// Pack a `tiles_x*8 × tiles_y*8` colour-index image into row-major tiles.
+pub fn pack_2bpp_tiles(pixels: &[u8], tiles_x: usize, tiles_y: usize) -> Vec<u8> {
+    let width = tiles_x * 8;
+    let mut tiles = Vec::with_capacity(tiles_x * tiles_y * 16);
+    for tile_y in 0..tiles_y {
+        for tile_x in 0..tiles_x {
+            let tile_pixels: [u8; 64] =
+                std::array::from_fn(|i| pixels[(tile_y * 8 + i / 8) * width + tile_x * 8 + i % 8]);
+            tiles.extend_from_slice(&pack_2bpp_tile(&tile_pixels));
+        }
+    }
+    tiles
+}
+
+/// Decode `tiles_x × tiles_y` 2bpp tiles (16 bytes each, row-major) into
+/// RGBA using the DMG gray ramp. Missing trailing bytes decode as colour 0.
+pub fn decode_2bpp_tiles_to_rgba(tiles: &[u8], tiles_x: usize, tiles_y: usize) -> Vec<u8> {
+    let width = tiles_x * 8;
+    let mut rgba = vec![0u8; width * tiles_y * 8 * 4];
+
+    for tile_y in 0..tiles_y {
+        for tile_x in 0..tiles_x {
+            let offset = (tile_y * tiles_x + tile_x) * 16;
+            let tile: [u8; 16] =
+                std::array::from_fn(|i| tiles.get(offset + i).copied().unwrap_or(0));
+            for (p, &color) in unpack_2bpp_tile(&tile).iter().enumerate() {
+                let gray = GRAY[color as usize];
+                let i = ((tile_y * 8 + p / 8) * width + tile_x * 8 + p % 8) * 4;
+                rgba[i..i + 4].copy_from_slice(&[gray, gray, gray, 255]);
+            }
+        }
+    }
+
+    rgba
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tile_pack_unpack_round_trip() {
+        let pixels: [u8; 64] = std::array::from_fn(|i| ((i / 8 + i % 8) % 4) as u8);
+        let tile = pack_2bpp_tile(&pixels);
+        // Row 0 = 0,1,2,3,0,1,2,3: low plane 0101_0101, high plane 0011_0011
+        assert_eq!(&tile[..2], &[0x55, 0x33]);
+        assert_eq!(unpack_2bpp_tile(&tile), pixels);
+    }
+
+    #[test]
+    fn test_1bpp_font_matches_rom_generator() {
+        // "g" from the gb-film font, white on black
+        let g = [0x00, 0x70, 0x88, 0x88, 0x78, 0x08, 0x88, 0x70];
+        let rom = gb_film::build_rom(&gb_film::CameraConfig::default());
+        assert_eq!(pack_1bpp_to_2bpp(&g, 0, 3), rom[0x0600..0x0610]);
+    }
+
+    #[test]
+    fn test_tile_sheet_pack_decode() {
+        let pixels: Vec<u8> = (0..16 * 8).map(|i| ((i % 16) / 4) as u8).collect();
+        let tiles = pack_2bpp_tiles(&pixels, 2, 1);
+        let rgba = decode_2bpp_tiles_to_rgba(&tiles, 2, 1);
+        for (i, &color) in pixels.iter().enumerate() {
+            assert_eq!(rgba[i * 4], GRAY[color as usize], "pixel {}", i);
+        }
+    }
+}
@@ -5,6 +5,8 @@
 //! buttons (Up/Down/Left/Right), then reads bits 0-3 to get the state.
 //! All button signals are active-low (0 = pressed).
 
+use std::hash::{Hash, Hasher};
+
 pub struct Joypad {
     // Button states (active low in hardware, but we track as true = pressed)
     a: bool,
@@ -69,7 +71,11 @@ impl Joypad {
         }
     }
 
-    pub fn set_button(&mut self, button: Button, pressed: bool) {
+    /// Press or release `button`. Returns true when the press pulls a P1
+    /// input line that is currently selected (0xFF00 bits 4/5) from high to
+    /// low — the edge that requests the joypad interrupt.
+    pub fn set_button(&mut self, button: Button, pressed: bool) -> bool {
+        let lines = self.read();
         match button {
             Button::A => self.a = pressed,
             Button::B => self.b = pressed,
@@ -80,6 +86,22 @@ impl Joypad {
             Button::Up => self.up = pressed,
             Button::Down => self.down = pressed,
         }
+        lines & !self.read() & 0x0F != 0
+    }
+
+    /// Whether `button` is currently held.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: is_button_pressed
+    pub fn is_pressed(&self, button: Button) -> bool {
+        match button {
+            Button::A => self.a,
+            Button::B => self.b,
+            Button::Select => self.select,
+            Button::Start => self.start,
+            Button::Right => self.right,
+            Button::Left => self.left,
+            Button::Up => self.up,
+            Button::Down => self.down,
+        }
     }
 
     /// Read the joypad register (0xFF00). Returns button states based on selection bits.
@@ -127,6 +149,23 @@ impl Joypad {
         result
     }
 
+    /// Feed button and selection state into `h` (see `GameBoyCore::state_checksum`).
+    pub fn hash_state<H: Hasher>(&self, h: &mut H) {
+        [
+            self.a,
+            self.b,
+            self.select,
+            self.start,
+            self.right,
+            self.left,
+            self.up,
+            self.down,
+            self.select_buttons,
+            self.select_dpad,
+        ]
+        .hash(h);
+    }
+
     /// Write to the joypad register (0xFF00) to select button/d-pad reading mode.
     pub fn write(&mut self, value: u8) {
         // Bits 4-5 select which buttons to read
@@ -165,6 +204,24 @@ mod tests {
         assert_eq!(result & 0x01, 0x00); // A is bit 0, should be low
     }
 
+    #[test]
+    fn test_press_edge_only_on_selected_lines() {
+        let mut joypad = Joypad::new();
+        joypad.write(0x10); // action buttons
+        assert!(joypad.set_button(Button::A, true));
+        assert!(!joypad.set_button(Button::A, true), "already low");
+        assert!(!joypad.set_button(Button::Up, true), "d-pad not selected");
+        assert!(
+            !joypad.set_button(Button::A, false),
+            "release is a rising edge"
+        );
+
+        // Select shares its line with Up, which is already held
+        joypad.write(0x00);
+        assert!(!joypad.set_button(Button::Select, true));
+        assert!(joypad.set_button(Button::B, true));
+    }
+
     #[test]
     fn test_dpad_pressed() {
         let mut joypad = Joypad::new();
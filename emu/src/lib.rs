@@ -9,14 +9,23 @@
 //! Both frontends delegate to `GameBoyCore`, which owns the CPU, memory,
 //! PPU, timer, interrupt controller, and joypad.
 
+mod apu;
 mod bus;
+pub mod color;
 mod core;
 mod cpu;
+mod debug;
+#[cfg(test)]
+mod difftest;
+pub mod graphics;
 mod interrupts;
 mod joypad;
 mod log;
 pub(crate) mod memory;
 mod ppu;
+mod printer;
+mod serial;
+mod sha1;
 mod timer;
 
 // FFI module for iOS/native builds
@@ -1,8 +1,11 @@
 //! Logging infrastructure for the Game Boy emulator.
 //!
 //! Provides rate-limited, categorized logging for debugging without overwhelming output.
+//! Categories and the verbosity level can also be switched at runtime (see
+//! [`set_category_enabled`] and [`set_level`]); this is independent of which
+//! targets actually print anything.
 
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::atomic::{AtomicU8, AtomicU32, Ordering};
 
 /// Log categories for filtering and rate limiting.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -16,7 +19,24 @@ pub enum LogCategory {
 }
 
 impl LogCategory {
-    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    /// Parse a category name as used by frontends ("camera", "cpu", ...).
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: set_log_enabled
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "camera" => Some(LogCategory::Camera),
+            "memory" => Some(LogCategory::Memory),
+            "cpu" => Some(LogCategory::Cpu),
+            "ppu" => Some(LogCategory::Ppu),
+            "general" | "emu" => Some(LogCategory::General),
+            _ => None,
+        }
+    }
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+
+    #[cfg_attr(not(any(target_arch = "wasm32", test)), allow(dead_code))]
     fn prefix(self) -> &'static str {
         match self {
             LogCategory::Camera => "[Camera]",
@@ -28,6 +48,56 @@ impl LogCategory {
     }
 }
 
+/// Most verbose message kind that is emitted. Ordered from quietest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: set_log_level
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    Info,
+}
+
+impl LogLevel {
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: set_log_level
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "off" => Some(LogLevel::Off),
+            "error" => Some(LogLevel::Error),
+            "warn" => Some(LogLevel::Warn),
+            "info" => Some(LogLevel::Info),
+            _ => None,
+        }
+    }
+}
+
+/// Bitmask of enabled categories (all on by default).
+static ENABLED_CATEGORIES: AtomicU8 = AtomicU8::new(0xFF);
+static LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Info as u8);
+
+/// Enable or disable a category for the whole process.
+#[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: set_log_enabled
+pub fn set_category_enabled(category: LogCategory, enabled: bool) {
+    if enabled {
+        ENABLED_CATEGORIES.fetch_or(category.bit(), Ordering::Relaxed);
+    } else {
+        ENABLED_CATEGORIES.fetch_and(!category.bit(), Ordering::Relaxed);
+    }
+}
+
+/// Set the most verbose level that is emitted.
+#[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: set_log_level
+pub fn set_level(level: LogLevel) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// Whether a `level` message in `category` passes the runtime filters.
+pub fn enabled(category: LogCategory, level: LogLevel) -> bool {
+    level != LogLevel::Off
+        && level as u8 <= LEVEL.load(Ordering::Relaxed)
+        && ENABLED_CATEGORIES.load(Ordering::Relaxed) & category.bit() != 0
+}
+
 /// Rate limiter that tracks how many times a particular log point has been hit.
 #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
 pub struct RateLimiter {
@@ -66,86 +136,98 @@ impl RateLimiter {
 }
 
 /// Logger that outputs to the browser console.
+///
+/// Every method applies the runtime category/level filters first, so a
+/// silenced category also leaves rate limiters untouched.
 pub struct Logger;
 
 impl Logger {
     /// Log an info message.
-    #[cfg(target_arch = "wasm32")]
     pub fn info(category: LogCategory, msg: &str) {
-        let formatted = format!("{} {}", category.prefix(), msg);
-        web_sys::console::log_1(&formatted.into());
+        if enabled(category, LogLevel::Info) {
+            emit(LogLevel::Info, category, msg);
+        }
     }
 
     /// Log a warning message.
-    #[cfg(target_arch = "wasm32")]
+    #[allow(dead_code)]
     pub fn warn(category: LogCategory, msg: &str) {
-        let formatted = format!("{} {}", category.prefix(), msg);
-        web_sys::console::warn_1(&formatted.into());
+        if enabled(category, LogLevel::Warn) {
+            emit(LogLevel::Warn, category, msg);
+        }
     }
 
     /// Log an error message.
-    #[cfg(target_arch = "wasm32")]
     #[allow(dead_code)]
     pub fn error(category: LogCategory, msg: &str) {
-        let formatted = format!("{} {}", category.prefix(), msg);
-        web_sys::console::error_1(&formatted.into());
+        if enabled(category, LogLevel::Error) {
+            emit(LogLevel::Error, category, msg);
+        }
     }
 
     /// Log with rate limiting.
-    #[cfg(target_arch = "wasm32")]
     pub fn info_limited(category: LogCategory, limiter: &RateLimiter, msg: &str) {
-        if limiter.should_log() {
-            Self::info(category, msg);
+        if enabled(category, LogLevel::Info) && limiter.should_log() {
+            emit(LogLevel::Info, category, msg);
         }
     }
 
     /// Log with rate limiting at intervals.
-    #[cfg(target_arch = "wasm32")]
     #[allow(dead_code)]
     pub fn info_interval(category: LogCategory, limiter: &RateLimiter, interval: u32, msg: &str) {
-        if limiter.should_log_interval(interval) {
-            Self::info(category, msg);
+        if enabled(category, LogLevel::Info) && limiter.should_log_interval(interval) {
+            emit(LogLevel::Info, category, msg);
         }
     }
+}
 
-    // No-op implementations for non-WASM builds
-    #[cfg(not(target_arch = "wasm32"))]
-    pub fn info(_category: LogCategory, _msg: &str) {}
+#[cfg(target_arch = "wasm32")]
+fn emit(level: LogLevel, category: LogCategory, msg: &str) {
+    let formatted = wasm_bindgen::JsValue::from_str(&format!("{} {}", category.prefix(), msg));
+    match level {
+        LogLevel::Error => web_sys::console::error_1(&formatted),
+        LogLevel::Warn => web_sys::console::warn_1(&formatted),
+        _ => web_sys::console::log_1(&formatted),
+    }
+}
 
-    #[cfg(not(target_arch = "wasm32"))]
-    #[allow(dead_code)]
-    pub fn warn(_category: LogCategory, _msg: &str) {}
+// No-op for non-WASM builds
+#[cfg(all(not(target_arch = "wasm32"), not(test)))]
+fn emit(_level: LogLevel, _category: LogCategory, _msg: &str) {}
 
-    #[cfg(not(target_arch = "wasm32"))]
-    #[allow(dead_code)]
-    pub fn error(_category: LogCategory, _msg: &str) {}
+#[cfg(all(not(target_arch = "wasm32"), test))]
+thread_local! {
+    static CAPTURED: std::cell::RefCell<Vec<String>> = const { std::cell::RefCell::new(Vec::new()) };
+}
 
-    #[cfg(not(target_arch = "wasm32"))]
-    pub fn info_limited(_category: LogCategory, _limiter: &RateLimiter, _msg: &str) {}
+// Native tests capture output per thread so it can be asserted on.
+#[cfg(all(not(target_arch = "wasm32"), test))]
+fn emit(_level: LogLevel, category: LogCategory, msg: &str) {
+    CAPTURED.with(|c| {
+        c.borrow_mut()
+            .push(format!("{} {}", category.prefix(), msg))
+    });
+}
 
-    #[cfg(not(target_arch = "wasm32"))]
-    #[allow(dead_code)]
-    pub fn info_interval(
-        _category: LogCategory,
-        _limiter: &RateLimiter,
-        _interval: u32,
-        _msg: &str,
-    ) {
-    }
+#[cfg(all(not(target_arch = "wasm32"), test))]
+pub(crate) fn take_captured() -> Vec<String> {
+    CAPTURED.with(|c| std::mem::take(&mut *c.borrow_mut()))
 }
 
 /// Convenience macros for logging.
 #[macro_export]
 macro_rules! log_info {
     ($cat:expr, $($arg:tt)*) => {
-        $crate::log::Logger::info($cat, &format!($($arg)*))
+        if $crate::log::enabled($cat, $crate::log::LogLevel::Info) {
+            $crate::log::Logger::info($cat, &format!($($arg)*))
+        }
     };
 }
 
 #[macro_export]
 macro_rules! log_warn {
     ($cat:expr, $limiter:expr, $($arg:tt)*) => {
-        if $limiter.should_log() {
+        if $crate::log::enabled($cat, $crate::log::LogLevel::Warn) && $limiter.should_log() {
             $crate::log::Logger::warn($cat, &format!($($arg)*))
         }
     };
@@ -157,7 +239,9 @@ macro_rules! log_warn {
 #[macro_export]
 macro_rules! log_info_limited {
     ($cat:expr, $limiter:expr, $($arg:tt)*) => {
-        $crate::log::Logger::info_limited($cat, $limiter, &format!($($arg)*))
+        if $crate::log::enabled($cat, $crate::log::LogLevel::Info) {
+            $crate::log::Logger::info_limited($cat, $limiter, &format!($($arg)*))
+        }
     };
 }
 
@@ -21,6 +21,7 @@
 //! - https://github.com/Raphael-Boichot/Inject-pictures-in-your-Game-Boy-Camera-saves
 //! - https://github.com/untoxa/gb-photo/
 
+use crate::graphics::{decode_2bpp_tiles_to_rgba, pack_2bpp_tiles};
 use crate::log::{LogCategory, RateLimiter};
 use crate::{log_info, log_info_limited};
 
@@ -30,6 +31,68 @@ pub(crate) const RAM_BANK_SIZE: usize = 0x2000; // 8KB
 const STATE_VECTOR_OFFSET: usize = 0x11B2;
 const NUM_PHOTO_SLOTS: usize = 30;
 
+/// Why a photo slot could not be decoded or encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhotoError {
+    /// Slot is outside 0-30 (1-30 for encode) or past the end of SRAM.
+    InvalidSlot,
+    /// Slot is marked erased in the state vector.
+    Empty,
+    /// RGBA input is not 128×112×4 bytes.
+    BadLength,
+    /// The loaded cartridge is not a Pocket Camera.
+    NoCamera,
+}
+
+impl PhotoError {
+    /// Stable numeric code for frontends (0 is reserved for success).
+    pub fn code(self) -> i32 {
+        match self {
+            PhotoError::InvalidSlot => 1,
+            PhotoError::Empty => 2,
+            PhotoError::BadLength => 3,
+            PhotoError::NoCamera => 4,
+        }
+    }
+}
+
+/// Convert a `width`×`height` RGBA frame to the sensor's 128×112 grayscale
+/// input: Rec.601 luma (0.299 R + 0.587 G + 0.114 B), box-averaged when
+/// downscaling and nearest-neighbour when upscaling. Returns `None` if
+/// `rgba` is smaller than `width * height * 4` or either side is zero.
+pub fn rgba_to_sensor_gray(rgba: &[u8], width: usize, height: usize) -> Option<Vec<u8>> {
+    const WIDTH: usize = 128;
+    const HEIGHT: usize = 112;
+
+    if width == 0 || height == 0 || rgba.len() < width * height * 4 {
+        return None;
+    }
+
+    let luma = |i: usize| {
+        let px = &rgba[i * 4..i * 4 + 3];
+        299 * px[0] as u32 + 587 * px[1] as u32 + 114 * px[2] as u32
+    };
+
+    let mut gray = Vec::with_capacity(WIDTH * HEIGHT);
+    for dy in 0..HEIGHT {
+        let y0 = dy * height / HEIGHT;
+        let y1 = ((dy + 1) * height / HEIGHT).max(y0 + 1);
+        for dx in 0..WIDTH {
+            let x0 = dx * width / WIDTH;
+            let x1 = ((dx + 1) * width / WIDTH).max(x0 + 1);
+            let mut sum = 0u32;
+            for y in y0..y1 {
+                for x in x0..x1 {
+                    sum += luma(y * width + x);
+                }
+            }
+            let count = ((y1 - y0) * (x1 - x0)) as u32 * 1000;
+            gray.push(((sum + count / 2) / count) as u8);
+        }
+    }
+    Some(gray)
+}
+
 /// Game Boy Camera sensor state, hardware registers, and photo storage.
 ///
 /// Owns the 128KB cartridge RAM as well as all sensor-emulation fields.
@@ -41,10 +104,22 @@ pub struct Camera {
     pub image: Box<[u8; 128 * 112]>,
     pub image_ready: bool,
     pub capture_dirty: bool,
+    /// ROM-triggered captures completed since power-on (wrapping).
+    pub captures: u32,
     /// Smoothed exposure factor — prevents autoexposure oscillation.
     pub exposure_smooth: f32,
     /// Optional override; when `Some`, bypasses ROM-controlled exposure.
     pub exposure_override: Option<u16>,
+    /// Mirror incoming images horizontally / vertically (see `set_orientation`).
+    pub flip_h: bool,
+    pub flip_v: bool,
+    /// Live preview runs the sensor model at 1/`preview_downscale` of the
+    /// resolution per axis (1, 2, 4 or 8; see `capture_to_rgba`).
+    pub preview_downscale: u8,
+    /// Post-processing brightness offset added before quantization (0 = none).
+    pub post_brightness: i16,
+    /// Post-processing contrast multiplier around mid-gray (1.0 = none).
+    pub post_contrast: f32,
     /// 128KB cartridge RAM (16 × 8KB banks for photo storage).
     pub ram: Vec<u8>,
 }
@@ -56,8 +131,14 @@ impl Camera {
             image: Box::new([0; 128 * 112]),
             image_ready: false,
             capture_dirty: false,
+            captures: 0,
+            flip_h: false,
+            flip_v: false,
+            preview_downscale: 1,
             exposure_smooth: 1.0,
             exposure_override: None,
+            post_brightness: 0,
+            post_contrast: 1.0,
             ram: vec![0; 128 * 1024],
         }
     }
@@ -67,6 +148,7 @@ impl Camera {
     pub fn set_image(&mut self, data: &[u8]) {
         let len = data.len().min(128 * 112);
         self.image.copy_from_slice(&data[..len]);
+        self.apply_orientation();
         self.image_ready = true;
 
         static SET_IMAGE_LIMITER: RateLimiter = RateLimiter::new(30);
@@ -87,6 +169,32 @@ impl Camera {
         }
     }
 
+    /// Correct the orientation of future `set_image` frames (e.g. un-mirror a
+    /// selfie camera). `rotate_180` is both flips, so combining it with a
+    /// flip cancels that axis.
+    pub fn set_orientation(&mut self, flip_h: bool, flip_v: bool, rotate_180: bool) {
+        self.flip_h = flip_h ^ rotate_180;
+        self.flip_v = flip_v ^ rotate_180;
+    }
+
+    fn apply_orientation(&mut self) {
+        const WIDTH: usize = 128;
+        if self.flip_h {
+            for row in self.image.chunks_exact_mut(WIDTH) {
+                row.reverse();
+            }
+        }
+        if self.flip_v {
+            let (mut top, mut bottom) = (0, self.image.len() / WIDTH - 1);
+            while top < bottom {
+                let (head, tail) = self.image.split_at_mut(bottom * WIDTH);
+                head[top * WIDTH..(top + 1) * WIDTH].swap_with_slice(&mut tail[..WIDTH]);
+                top += 1;
+                bottom -= 1;
+            }
+        }
+    }
+
     /// Read a camera hardware register (index 0x00-0x7F).
     #[inline]
     pub fn reg(&self, index: u8) -> u8 {
@@ -98,6 +206,13 @@ impl Camera {
         self.exposure_override = value;
     }
 
+    /// Set the brightness/contrast post adjustment applied after the sensor
+    /// pipeline, just before quantization. `(0, 1.0)` disables it.
+    pub fn set_post_adjust(&mut self, brightness: i16, contrast: f32) {
+        self.post_brightness = brightness;
+        self.post_contrast = contrast;
+    }
+
     #[inline]
     pub fn is_image_ready(&self) -> bool {
         self.image_ready
@@ -131,6 +246,15 @@ impl Camera {
     /// - A004: Edge enhancement (bits 4-6), O flag (bit 0)
     /// - A005: Voltage offset (darkness level)
     /// - A006-A035: Dithering matrix (48 bytes for 4x4x3 threshold values)
+    ///
+    /// The N flag inverts the analog sensor output before dithering, as the
+    /// M64282FP does; `invert` (A000 bit 1) flips the quantized colours
+    /// afterwards. With dithering off, setting both cancels out.
+    ///
+    /// Until the host has supplied a frame with `set_image`, a capture stores
+    /// a blank photo (every pixel colour 0, white) instead of running the
+    /// sensor model on the zeroed buffer; the exposure smoothing state is
+    /// left untouched.
     pub fn process_capture(&mut self, invert: bool) {
         const WIDTH: usize = 128;
         const HEIGHT: usize = 112;
@@ -139,6 +263,16 @@ impl Camera {
         const TILES_Y: usize = HEIGHT / TILE_SIZE;
         const SRAM_OFFSET: usize = 0x0100;
 
+        if !self.image_ready {
+            log_info!(
+                LogCategory::Camera,
+                "Capture with no image set: storing a blank photo"
+            );
+            let end = (SRAM_OFFSET + TILES_X * TILES_Y * 16).min(self.ram.len());
+            self.ram[SRAM_OFFSET..end].fill(0);
+            return;
+        }
+
         let reg_a001 = self.regs[0x01];
         let exposure_low = self.regs[0x02];
         let exposure_high = self.regs[0x03];
@@ -163,16 +297,7 @@ impl Camera {
             invert
         );
 
-        let mut dither_thresholds: [[u8; 3]; 16] = [[0; 3]; 16];
-        for (i, row) in dither_thresholds.iter_mut().enumerate() {
-            for (t, cell) in row.iter_mut().enumerate() {
-                let reg_idx = 0x06 + i * 3 + t;
-                if reg_idx < 0x36 {
-                    *cell = self.regs[reg_idx];
-                }
-            }
-        }
-
+        let dither_thresholds = self.dither_thresholds();
         let dither_active = dither_thresholds
             .iter()
             .any(|t| t[0] != 0 || t[1] != 0 || t[2] != 0);
@@ -202,62 +327,91 @@ impl Camera {
             self.image_ready
         );
 
-        let target_factor = if exposure > 0 {
-            (exposure as f32) / 4096.0
-        } else {
-            0.0
-        };
-        let exposure_factor = self.exposure_smooth * 0.5 + target_factor * 0.5;
+        let exposure_factor = self.smoothed_exposure_factor();
         self.exposure_smooth = exposure_factor;
+        log_info!(
+            LogCategory::Camera,
+            "Effect params: exposure_f={:.2}",
+            exposure_factor
+        );
 
-        let gain_factor = match gain_bits {
-            0b00 => 2.0,
-            0b01 => 1.5,
-            0b10 => 1.0,
-            0b11 => 0.75,
-            _ => 1.0,
-        };
-
-        let offset_adjustment = (voltage_offset as f32) / 255.0 * 64.0;
+        let quantized = self.sensor_colors(&self.image[..], WIDTH, HEIGHT, exposure_factor, invert);
 
+        let mut color_counts = [0u32; 4];
+        for &color in &quantized {
+            color_counts[color as usize] += 1;
+        }
         log_info!(
             LogCategory::Camera,
-            "Effect params: exposure_f={:.2}, gain_f={:.2}, offset_adj={:.1}",
-            exposure_factor,
-            gain_factor,
-            offset_adjustment
+            "Quantized: colors [0]={}, [1]={}, [2]={}, [3]={}",
+            color_counts[0],
+            color_counts[1],
+            color_counts[2],
+            color_counts[3]
         );
 
-        let mut processed: Box<[u8; WIDTH * HEIGHT]> = Box::new([0; WIDTH * HEIGHT]);
+        let tiles = pack_2bpp_tiles(&quantized, TILES_X, TILES_Y);
+        let end = (SRAM_OFFSET + tiles.len()).min(self.ram.len());
+        self.ram[SRAM_OFFSET..end].copy_from_slice(&tiles[..end - SRAM_OFFSET]);
+    }
+
+    /// Exposure factor for the next capture: the smoothed factor eased
+    /// halfway towards the one the exposure registers (or override) ask for.
+    fn smoothed_exposure_factor(&self) -> f32 {
+        let exposure = self
+            .exposure_override
+            .unwrap_or(((self.regs[0x03] as u16) << 8) | (self.regs[0x02] as u16));
+        let target_factor = exposure as f32 / 4096.0;
+        self.exposure_smooth * 0.5 + target_factor * 0.5
+    }
+
+    /// The sensor model after exposure: voltage offset, gain, the N flag,
+    /// edge enhancement, post adjustment and (dithered) quantization of a
+    /// `width`×`height` image to 2-bit colours, with `invert` flipping them.
+    fn sensor_colors(
+        &self,
+        image: &[u8],
+        width: usize,
+        height: usize,
+        exposure_factor: f32,
+        invert: bool,
+    ) -> Vec<u8> {
+        let reg_a001 = self.regs[0x01];
+        let gain_factor = match (reg_a001 >> 4) & 0x03 {
+            0b00 => 2.0,
+            0b01 => 1.5,
+            0b10 => 1.0,
+            _ => 0.75,
+        };
+        let output_negative = (reg_a001 & 0x02) != 0;
+        let edge_mode = (self.regs[0x04] >> 4) & 0x07;
+        let offset_adjustment = (self.regs[0x05] as f32) / 255.0 * 64.0;
 
-        for y in 0..HEIGHT {
-            for x in 0..WIDTH {
-                let idx = y * WIDTH + x;
-                let raw = self.image[idx] as f32;
-                let exposed = raw * exposure_factor;
+        let mut processed: Vec<u8> = image
+            .iter()
+            .map(|&raw| {
+                let exposed = raw as f32 * exposure_factor;
                 let offset_applied = exposed - offset_adjustment;
                 let centered = offset_applied - 128.0;
                 let gained = centered * gain_factor + 128.0;
-                processed[idx] = gained.clamp(0.0, 255.0) as u8;
-            }
-        }
+                let sensor = gained.clamp(0.0, 255.0) as u8;
+                if output_negative {
+                    255 - sensor
+                } else {
+                    sensor
+                }
+            })
+            .collect();
 
-        if edge_mode > 0 {
+        if edge_mode > 0 && width > 2 && height > 2 {
             let edge_strength = (edge_mode as f32) / 7.0;
             let mut edge_enhanced = processed.clone();
 
-            for y in 1..HEIGHT - 1 {
-                for x in 1..WIDTH - 1 {
-                    let idx = y * WIDTH + x;
+            for y in 1..height - 1 {
+                for x in 1..width - 1 {
+                    let idx = y * width + x;
                     let center = processed[idx] as i32;
-                    let neighbors = [
-                        processed[idx - WIDTH] as i32,
-                        processed[idx + WIDTH] as i32,
-                        processed[idx - 1] as i32,
-                        processed[idx + 1] as i32,
-                    ];
-                    let avg_neighbors: i32 = neighbors.iter().sum::<i32>() / 4;
-                    let edge = center - avg_neighbors;
+                    let edge = Self::edge_at(&processed, idx, width);
                     let enhanced = center + (edge as f32 * edge_strength * 2.0) as i32;
                     edge_enhanced[idx] = enhanced.clamp(0, 255) as u8;
                 }
@@ -265,90 +419,204 @@ impl Camera {
             processed = edge_enhanced;
         }
 
-        let mut quantized: Box<[u8; WIDTH * HEIGHT]> = Box::new([0; WIDTH * HEIGHT]);
-        let mut color_counts = [0u32; 4];
+        if self.post_brightness != 0 || self.post_contrast != 1.0 {
+            let brightness = self.post_brightness as f32;
+            let contrast = self.post_contrast;
+            for p in processed.iter_mut() {
+                let adjusted = (*p as f32 - 128.0) * contrast + 128.0 + brightness;
+                *p = adjusted.clamp(0.0, 255.0) as u8;
+            }
+        }
 
-        for y in 0..HEIGHT {
-            for x in 0..WIDTH {
-                let idx = y * WIDTH + x;
-                let pixel = processed[idx];
-                let dither_idx = (y % 4) * 4 + (x % 4);
-                let thresholds = &dither_thresholds[dither_idx];
-
-                let color = if dither_active {
-                    if pixel < thresholds[0] {
-                        0
-                    } else if pixel < thresholds[1] {
-                        1
-                    } else if pixel < thresholds[2] {
-                        2
-                    } else {
-                        3
-                    }
-                } else {
-                    let inverted = 255 - pixel;
-                    (inverted / 64).min(3)
-                };
+        let dither_thresholds = self.dither_thresholds();
+        let dither_active = dither_thresholds
+            .iter()
+            .any(|t| t[0] != 0 || t[1] != 0 || t[2] != 0);
 
-                let final_color = if output_negative || invert { 3 - color } else { color };
-                quantized[idx] = final_color;
-                color_counts[final_color as usize] += 1;
+        processed
+            .iter()
+            .enumerate()
+            .map(|(idx, &pixel)| {
+                let (x, y) = (idx % width, idx / width);
+                let thresholds = dither_active.then(|| &dither_thresholds[(y % 4) * 4 + (x % 4)]);
+                let color = Self::quantize_pixel(pixel, thresholds);
+                if invert { 3 - color } else { color }
+            })
+            .collect()
+    }
+
+    /// Run the live preview at 1/`factor` resolution per axis to save work
+    /// on slow devices (rounded down to 1, 2, 4 or 8; 1 = full resolution).
+    /// ROM-driven captures always run at full resolution.
+    pub fn set_preview_downscale(&mut self, factor: u8) {
+        self.preview_downscale = match factor {
+            0..=1 => 1,
+            2..=3 => 2,
+            4..=7 => 4,
+            _ => 8,
+        };
+    }
+
+    /// What a capture would store right now, as 128×112 RGBA, without
+    /// writing SRAM or advancing the exposure smoothing. Runs at the
+    /// `set_preview_downscale` resolution and is scaled back up.
+    pub fn capture_to_rgba(&self) -> Vec<u8> {
+        const WIDTH: usize = 128;
+        const HEIGHT: usize = 112;
+        const GRAY: [u8; 4] = [0xFF, 0xAA, 0x55, 0x00];
+
+        let factor = self.preview_downscale as usize;
+        let (colors, width) = self.preview_colors();
+        let mut rgba = vec![0u8; WIDTH * HEIGHT * 4];
+        for (i, px) in rgba.chunks_exact_mut(4).enumerate() {
+            let (x, y) = (i % WIDTH / factor, i / WIDTH / factor);
+            let g = GRAY[colors[y * width + x] as usize];
+            px.copy_from_slice(&[g, g, g, 0xFF]);
+        }
+        rgba
+    }
+
+    /// Preview colours at the downscaled resolution, and the row width.
+    fn preview_colors(&self) -> (Vec<u8>, usize) {
+        const WIDTH: usize = 128;
+        const HEIGHT: usize = 112;
+
+        let factor = self.preview_downscale as usize;
+        let (width, height) = (WIDTH / factor, HEIGHT / factor);
+        if !self.image_ready {
+            return (vec![0; width * height], width);
+        }
+
+        // Box-average each factor×factor block
+        let image: Vec<u8> = (0..width * height)
+            .map(|i| {
+                let (bx, by) = (i % width * factor, i / width * factor);
+                let sum: u32 = (by..by + factor)
+                    .flat_map(|y| (bx..bx + factor).map(move |x| (x, y)))
+                    .map(|(x, y)| self.image[y * WIDTH + x] as u32)
+                    .sum();
+                (sum / (factor * factor) as u32) as u8
+            })
+            .collect();
+
+        let invert = self.regs[0x00] & 0x02 != 0;
+        let exposure_factor = self.smoothed_exposure_factor();
+        let colors = self.sensor_colors(&image, width, height, exposure_factor, invert);
+        (colors, width)
+    }
+
+    /// Edge-kernel response at `idx`: the pixel minus the mean of its four
+    /// orthogonal neighbours (a scaled Laplacian). `idx` must not be on the border.
+    #[inline]
+    fn edge_at(buf: &[u8], idx: usize, width: usize) -> i32 {
+        let center = buf[idx] as i32;
+        let neighbors = [
+            buf[idx - width] as i32,
+            buf[idx + width] as i32,
+            buf[idx - 1] as i32,
+            buf[idx + 1] as i32,
+        ];
+        center - neighbors.iter().sum::<i32>() / 4
+    }
+
+    /// Focus metric for the raw sensor image: mean squared edge-kernel
+    /// response, normalized to 0.0 (flat) ..= 1.0 (maximal contrast).
+    pub fn sharpness_metric(&self) -> f32 {
+        const WIDTH: usize = 128;
+        const HEIGHT: usize = 112;
+
+        let mut energy: u64 = 0;
+        for y in 1..HEIGHT - 1 {
+            for x in 1..WIDTH - 1 {
+                let edge = Self::edge_at(&self.image[..], y * WIDTH + x, WIDTH) as i64;
+                energy += (edge * edge) as u64;
             }
         }
+        let samples = ((WIDTH - 2) * (HEIGHT - 2)) as f32;
+        energy as f32 / (samples * 255.0 * 255.0)
+    }
 
-        let proc_sum: u32 = processed.iter().map(|&x| x as u32).sum();
-        let proc_avg = proc_sum / (WIDTH * HEIGHT) as u32;
-        let proc_min = processed.iter().copied().min().unwrap_or(0);
-        let proc_max = processed.iter().copied().max().unwrap_or(0);
-        log_info!(
-            LogCategory::Camera,
-            "Processed: avg={}, min={}, max={}",
-            proc_avg,
-            proc_min,
-            proc_max
-        );
-        log_info!(
-            LogCategory::Camera,
-            "Quantized: colors [0]={}, [1]={}, [2]={}, [3]={}",
-            color_counts[0],
-            color_counts[1],
-            color_counts[2],
-            color_counts[3]
-        );
+    /// Read the 4×4 dither matrix (A006-A035) as 16 cells of 3 thresholds each.
+    fn dither_thresholds(&self) -> [[u8; 3]; 16] {
+        let mut thresholds: [[u8; 3]; 16] = [[0; 3]; 16];
+        for (i, row) in thresholds.iter_mut().enumerate() {
+            for (t, cell) in row.iter_mut().enumerate() {
+                let reg_idx = 0x06 + i * 3 + t;
+                if reg_idx < 0x36 {
+                    *cell = self.regs[reg_idx];
+                }
+            }
+        }
+        thresholds
+    }
 
-        for tile_y in 0..TILES_Y {
-            for tile_x in 0..TILES_X {
-                let tile_index = tile_y * TILES_X + tile_x;
-                let sram_addr = SRAM_OFFSET + tile_index * 16;
-
-                for row in 0..TILE_SIZE {
-                    let pixel_y = tile_y * TILE_SIZE + row;
-                    let mut low_byte: u8 = 0;
-                    let mut high_byte: u8 = 0;
-
-                    for col in 0..TILE_SIZE {
-                        let pixel_x = tile_x * TILE_SIZE + col;
-                        let pixel_index = pixel_y * WIDTH + pixel_x;
-                        let color = quantized[pixel_index];
-                        let bit_pos = 7 - col;
-                        low_byte |= (color & 0x01) << bit_pos;
-                        high_byte |= ((color >> 1) & 0x01) << bit_pos;
-                    }
-
-                    if sram_addr + row * 2 + 1 < self.ram.len() {
-                        self.ram[sram_addr + row * 2] = low_byte;
-                        self.ram[sram_addr + row * 2 + 1] = high_byte;
-                    }
+    /// Quantize one processed pixel to a 2-bit colour. `thresholds` is the
+    /// dither cell for this pixel, or `None` when the matrix is all zero.
+    #[inline]
+    fn quantize_pixel(pixel: u8, thresholds: Option<&[u8; 3]>) -> u8 {
+        match thresholds {
+            Some(t) => {
+                if pixel < t[0] {
+                    0
+                } else if pixel < t[1] {
+                    1
+                } else if pixel < t[2] {
+                    2
+                } else {
+                    3
                 }
             }
+            None => {
+                let inverted = 255 - pixel;
+                (inverted / 64).min(3)
+            }
         }
     }
 
+    /// Render a 64×64 RGBA swatch of a horizontal black-to-white ramp quantized
+    /// with the current dither matrix and N flag, for previewing a pattern
+    /// before capturing.
+    pub fn dither_preview(&self) -> Vec<u8> {
+        const SIZE: usize = 64;
+
+        let dither_thresholds = self.dither_thresholds();
+        let dither_active = dither_thresholds
+            .iter()
+            .any(|t| t[0] != 0 || t[1] != 0 || t[2] != 0);
+        let output_negative = (self.regs[0x01] & 0x02) != 0;
+
+        let palette: [u8; 4] = [0xFF, 0xAA, 0x55, 0x00];
+        let mut rgba = vec![0u8; SIZE * SIZE * 4];
+
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let pixel = (x * 255 / (SIZE - 1)) as u8;
+                let pixel = if output_negative { 255 - pixel } else { pixel };
+                let thresholds = dither_active.then(|| &dither_thresholds[(y % 4) * 4 + (x % 4)]);
+                let color = Self::quantize_pixel(pixel, thresholds);
+                let gray = palette[color as usize];
+                let i = (y * SIZE + x) * 4;
+                rgba[i] = gray;
+                rgba[i + 1] = gray;
+                rgba[i + 2] = gray;
+                rgba[i + 3] = 255;
+            }
+        }
+
+        rgba
+    }
+
     /// Decode a GB Camera photo slot from SRAM into RGBA pixel data.
     /// Slot 0 = active capture buffer (bank 0, offset 0x0100).
     /// Slots 1-30 = saved photos in banks 1-15 (2 per bank).
     /// Returns 128×112×4 bytes of RGBA, or empty vec if slot is unoccupied.
     pub fn decode_photo(&self, slot: u8) -> Vec<u8> {
+        self.try_decode_photo(slot).unwrap_or_default()
+    }
+
+    /// Fallible [`decode_photo`](Self::decode_photo): reports why nothing was
+    /// decoded instead of returning an empty vec.
+    pub fn try_decode_photo(&self, slot: u8) -> Result<Vec<u8>, PhotoError> {
         const WIDTH: usize = 128;
         const HEIGHT: usize = 112;
         const TILE_SIZE: usize = 8;
@@ -357,10 +625,13 @@ impl Camera {
         const TILE_BYTES: usize = 16;
         const PHOTO_BYTES: usize = TILES_X * TILES_Y * TILE_BYTES; // 3584
 
-        if (1..=30).contains(&slot) {
+        if slot as usize > NUM_PHOTO_SLOTS {
+            return Err(PhotoError::InvalidSlot);
+        }
+        if slot != 0 {
             let state_idx = STATE_VECTOR_OFFSET + (slot - 1) as usize;
             if state_idx < self.ram.len() && self.ram[state_idx] == 0xFF {
-                return Vec::new();
+                return Err(PhotoError::Empty);
             }
         }
 
@@ -374,44 +645,25 @@ impl Camera {
         };
 
         if sram_offset + PHOTO_BYTES > self.ram.len() {
-            return Vec::new();
-        }
-
-        let palette: [u8; 4] = [0xFF, 0xAA, 0x55, 0x00];
-        let mut rgba = vec![0u8; WIDTH * HEIGHT * 4];
-
-        for tile_y in 0..TILES_Y {
-            for tile_x in 0..TILES_X {
-                let tile_index = tile_y * TILES_X + tile_x;
-                let tile_offset = sram_offset + tile_index * TILE_BYTES;
-
-                for row in 0..TILE_SIZE {
-                    let low = self.ram[tile_offset + row * 2];
-                    let high = self.ram[tile_offset + row * 2 + 1];
-
-                    for col in 0..TILE_SIZE {
-                        let bit = 7 - col;
-                        let color_idx = ((high >> bit) & 1) << 1 | ((low >> bit) & 1);
-                        let gray = palette[color_idx as usize];
-                        let px = tile_x * TILE_SIZE + col;
-                        let py = tile_y * TILE_SIZE + row;
-                        let i = (py * WIDTH + px) * 4;
-                        rgba[i] = gray;
-                        rgba[i + 1] = gray;
-                        rgba[i + 2] = gray;
-                        rgba[i + 3] = 255;
-                    }
-                }
-            }
+            return Err(PhotoError::InvalidSlot);
         }
 
-        rgba
+        Ok(decode_2bpp_tiles_to_rgba(
+            &self.ram[sram_offset..sram_offset + PHOTO_BYTES],
+            TILES_X,
+            TILES_Y,
+        ))
     }
 
     /// Encode RGBA pixel data into a GB Camera SRAM slot (inverse of decode_photo).
     /// Accepts 128x112x4 RGBA bytes. Maps gray channel to 2-bit colors and packs into tiles.
     /// Also marks the slot as occupied in the state vector.
     pub fn encode_photo(&mut self, slot: u8, rgba: &[u8]) -> bool {
+        self.try_encode_photo(slot, rgba).is_ok()
+    }
+
+    /// Fallible [`encode_photo`](Self::encode_photo).
+    pub fn try_encode_photo(&mut self, slot: u8, rgba: &[u8]) -> Result<(), PhotoError> {
         const WIDTH: usize = 128;
         const HEIGHT: usize = 112;
         const TILE_SIZE: usize = 8;
@@ -420,8 +672,11 @@ impl Camera {
         const TILE_BYTES: usize = 16;
         const PHOTO_BYTES: usize = TILES_X * TILES_Y * TILE_BYTES;
 
-        if slot == 0 || slot > 30 || rgba.len() != WIDTH * HEIGHT * 4 {
-            return false;
+        if slot == 0 || slot as usize > NUM_PHOTO_SLOTS {
+            return Err(PhotoError::InvalidSlot);
+        }
+        if rgba.len() != WIDTH * HEIGHT * 4 {
+            return Err(PhotoError::BadLength);
         }
 
         let adjusted = (slot - 1) as usize;
@@ -430,42 +685,23 @@ impl Camera {
         let sram_offset = bank * RAM_BANK_SIZE + offset_in_bank;
 
         if sram_offset + PHOTO_BYTES > self.ram.len() {
-            return false;
+            return Err(PhotoError::InvalidSlot);
         }
 
-        for tile_y in 0..TILES_Y {
-            for tile_x in 0..TILES_X {
-                let tile_index = tile_y * TILES_X + tile_x;
-                let sram_addr = sram_offset + tile_index * TILE_BYTES;
-
-                for row in 0..TILE_SIZE {
-                    let pixel_y = tile_y * TILE_SIZE + row;
-                    let mut low_byte: u8 = 0;
-                    let mut high_byte: u8 = 0;
-
-                    for col in 0..TILE_SIZE {
-                        let pixel_x = tile_x * TILE_SIZE + col;
-                        let i = (pixel_y * WIDTH + pixel_x) * 4;
-                        let gray = rgba[i];
-                        let color: u8 = match gray {
-                            0xC0..=0xFF => 0,
-                            0x80..=0xBF => 1,
-                            0x40..=0x7F => 2,
-                            0x00..=0x3F => 3,
-                        };
-                        let bit_pos = 7 - col;
-                        low_byte |= (color & 0x01) << bit_pos;
-                        high_byte |= ((color >> 1) & 0x01) << bit_pos;
-                    }
-
-                    self.ram[sram_addr + row * 2] = low_byte;
-                    self.ram[sram_addr + row * 2 + 1] = high_byte;
-                }
-            }
-        }
+        let colors: Vec<u8> = rgba
+            .chunks_exact(4)
+            .map(|px| match px[0] {
+                0xC0..=0xFF => 0,
+                0x80..=0xBF => 1,
+                0x40..=0x7F => 2,
+                0x00..=0x3F => 3,
+            })
+            .collect();
+        let tiles = pack_2bpp_tiles(&colors, TILES_X, TILES_Y);
+        self.ram[sram_offset..sram_offset + PHOTO_BYTES].copy_from_slice(&tiles);
 
         self.set_state_vector_entry(slot, adjusted as u8);
-        true
+        Ok(())
     }
 
     /// Clear a GB Camera SRAM slot (zero tile data and mark empty in state vector).
@@ -488,6 +724,54 @@ impl Camera {
         self.set_state_vector_entry(slot, 0xFF);
     }
 
+    /// Erase every saved photo: zero banks 1-15 and mark all 30 slots empty.
+    /// The active capture buffer (slot 0) is left alone.
+    pub fn clear_all_slots(&mut self) {
+        let end = (16 * RAM_BANK_SIZE).min(self.ram.len());
+        if end > RAM_BANK_SIZE {
+            self.ram[RAM_BANK_SIZE..end].fill(0);
+        }
+        let end = (STATE_VECTOR_OFFSET + NUM_PHOTO_SLOTS).min(self.ram.len());
+        if end > STATE_VECTOR_OFFSET {
+            self.ram[STATE_VECTOR_OFFSET..end].fill(0xFF);
+        }
+        self.update_state_vector_checksum();
+    }
+
+    /// Move the photo in slot `from` to slot `to` (both 1-30), for
+    /// rearranging a gallery. The whole 4KB slot (tiles, thumbnail and
+    /// metadata) moves with its state-vector entry; whatever was in `to`
+    /// takes `from`'s place, so an empty target leaves `from` empty.
+    /// Returns false if either slot is invalid or `from` is empty.
+    pub fn move_photo(&mut self, from: u8, to: u8) -> bool {
+        const SLOT_BYTES: usize = 0x1000;
+        let valid = |slot: u8| (1..=NUM_PHOTO_SLOTS as u8).contains(&slot);
+        if !valid(from) || !valid(to) || self.occupancy() & 1 << (from - 1) == 0 {
+            return false;
+        }
+        if from == to {
+            return true;
+        }
+
+        let offset = |slot: u8| {
+            let adjusted = (slot - 1) as usize;
+            (adjusted / 2 + 1) * RAM_BANK_SIZE + (adjusted % 2) * SLOT_BYTES
+        };
+        let (a, b) = (offset(from).min(offset(to)), offset(from).max(offset(to)));
+        if b + SLOT_BYTES > self.ram.len() {
+            return false;
+        }
+        let (head, tail) = self.ram.split_at_mut(b);
+        head[a..a + SLOT_BYTES].swap_with_slice(&mut tail[..SLOT_BYTES]);
+
+        self.ram.swap(
+            STATE_VECTOR_OFFSET + (from - 1) as usize,
+            STATE_VECTOR_OFFSET + (to - 1) as usize,
+        );
+        self.update_state_vector_checksum();
+        true
+    }
+
     /// Derive the contrast level (0-15) from the current dither matrix registers.
     /// Returns 0-15 if matched against known gb-photo threshold tables, or -1 if unknown.
     pub fn contrast(&self) -> i32 {
@@ -553,11 +837,18 @@ impl Camera {
 
     /// Return the number of occupied photo slots (0-30) by scanning the state vector.
     pub fn photo_count(&self) -> u8 {
+        self.occupancy().count_ones() as u8
+    }
+
+    /// Occupied slots as a 30-bit mask from the state vector: bit i set =
+    /// slot i+1 holds a photo.
+    pub fn occupancy(&self) -> u32 {
         let end = (STATE_VECTOR_OFFSET + NUM_PHOTO_SLOTS).min(self.ram.len());
         self.ram[STATE_VECTOR_OFFSET..end]
             .iter()
-            .filter(|&&b| b != 0xFF)
-            .count() as u8
+            .enumerate()
+            .filter(|&(_, &b)| b != 0xFF)
+            .fold(0, |mask, (i, _)| mask | 1 << i)
     }
 
     fn set_state_vector_entry(&mut self, slot: u8, value: u8) {
@@ -588,3 +879,354 @@ impl Camera {
         self.ram[CHECKSUM_OFFSET + 1] = xor;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Camera with a horizontal 0-255 ramp, unity gain/exposure and no dither.
+    fn ramp_camera() -> Camera {
+        let mut cam = Camera::new();
+        let ramp: Vec<u8> = (0..128 * 112).map(|i| ((i % 128) * 2) as u8).collect();
+        cam.set_image(&ramp);
+        cam.regs[0x01] = 0x20; // gain bits = 0b10 → 1.0×
+        cam.set_exposure_override(Some(4096)); // exposure factor 1.0
+        cam
+    }
+
+    /// Count how many pixels of the active capture use each 2-bit colour.
+    fn capture_histogram(cam: &Camera) -> [u32; 4] {
+        let mut counts = [0u32; 4];
+        for pair in cam.capture_sram().chunks_exact(2) {
+            for bit in 0..8 {
+                let color = ((pair[1] >> bit) & 1) << 1 | ((pair[0] >> bit) & 1);
+                counts[color as usize] += 1;
+            }
+        }
+        counts
+    }
+
+    #[test]
+    fn test_negative_flag_is_analog_and_invert_is_post_quantize() {
+        let capture = |n_flag: bool, invert: bool, dither: bool| {
+            let mut cam = ramp_camera();
+            if n_flag {
+                cam.regs[0x01] |= 0x02;
+            }
+            if dither {
+                for cell in cam.regs[0x06..0x36].chunks_exact_mut(3) {
+                    cell.copy_from_slice(&[0x20, 0x80, 0xF0]);
+                }
+            }
+            cam.process_capture(invert);
+            cam.capture_sram().to_vec()
+        };
+
+        // Asymmetric thresholds: flipping the analog signal is not the same
+        // as flipping the quantized colours.
+        assert_ne!(capture(true, false, true), capture(false, true, true));
+
+        let base = capture(false, false, false);
+        assert_ne!(capture(true, false, false), base);
+        assert_ne!(capture(false, true, false), base);
+        assert_eq!(
+            capture(true, true, false),
+            base,
+            "N flag + invert cancel out"
+        );
+    }
+
+    #[test]
+    fn test_post_adjust_brightness_lightens() {
+        let mut cam = ramp_camera();
+        cam.process_capture(false);
+        let base = capture_histogram(&cam);
+
+        cam.set_post_adjust(64, 1.0);
+        cam.process_capture(false);
+        let bright = capture_histogram(&cam);
+
+        // Colour 0 is the lightest shade
+        assert!(bright[0] > base[0], "{:?} vs {:?}", bright, base);
+        assert!(bright[3] < base[3], "{:?} vs {:?}", bright, base);
+    }
+
+    #[test]
+    fn test_post_adjust_contrast_spreads_extremes() {
+        let mut cam = ramp_camera();
+        cam.process_capture(false);
+        let base = capture_histogram(&cam);
+
+        cam.set_post_adjust(0, 2.0);
+        cam.process_capture(false);
+        let contrasty = capture_histogram(&cam);
+
+        assert!(contrasty[0] + contrasty[3] > base[0] + base[3]);
+        assert!(contrasty[0] > base[0]);
+        assert!(contrasty[3] > base[3]);
+    }
+
+    #[test]
+    fn test_dither_preview_differs_by_matrix() {
+        let mut cam = Camera::new();
+        for cell in 0..16 {
+            cam.regs[0x06 + cell * 3..0x06 + cell * 3 + 3].copy_from_slice(&[0x40, 0x80, 0xC0]);
+        }
+        let flat = cam.dither_preview();
+        assert_eq!(flat.len(), 64 * 64 * 4);
+
+        for cell in 0..16 {
+            let bias = (cell as u8) * 4;
+            cam.regs[0x06 + cell * 3..0x06 + cell * 3 + 3].copy_from_slice(&[
+                0x20 + bias,
+                0x70 + bias,
+                0xB0 + bias,
+            ]);
+        }
+        let ordered = cam.dither_preview();
+        assert_ne!(flat, ordered);
+    }
+
+    #[test]
+    fn test_sharpness_checkerboard_beats_flat_gray() {
+        let mut cam = Camera::new();
+        cam.set_image(&[0x80; 128 * 112]);
+        let flat = cam.sharpness_metric();
+
+        let checker: Vec<u8> = (0..128 * 112)
+            .map(|i| if (i % 128 + i / 128) % 2 == 0 { 0 } else { 255 })
+            .collect();
+        cam.set_image(&checker);
+        let sharp = cam.sharpness_metric();
+
+        assert_eq!(flat, 0.0);
+        assert!(sharp > 0.9 && sharp <= 1.0, "sharp={}", sharp);
+    }
+
+    #[test]
+    fn test_try_decode_photo_errors() {
+        let mut cam = Camera::new();
+        assert_eq!(cam.try_decode_photo(31), Err(PhotoError::InvalidSlot));
+        assert_eq!(
+            cam.try_encode_photo(31, &[0; 128 * 112 * 4]),
+            Err(PhotoError::InvalidSlot)
+        );
+        assert_eq!(
+            cam.try_encode_photo(1, &[0; 16]),
+            Err(PhotoError::BadLength)
+        );
+
+        cam.clear_photo_slot(1);
+        assert_eq!(cam.try_decode_photo(1), Err(PhotoError::Empty));
+        assert!(cam.decode_photo(1).is_empty());
+
+        let mut rgba = vec![0xFFu8; 128 * 112 * 4];
+        rgba[..4].copy_from_slice(&[0, 0, 0, 255]);
+        assert_eq!(cam.try_encode_photo(1, &rgba), Ok(()));
+        assert_eq!(cam.try_decode_photo(1), Ok(rgba));
+    }
+
+    #[test]
+    fn test_rgba_to_sensor_gray_luma_and_scaling() {
+        // Orange: 0.299*255 + 0.587*128 + 0.114*0 = 151.38
+        let rgba: Vec<u8> = [255, 128, 0, 255].repeat(256 * 224);
+        let gray = rgba_to_sensor_gray(&rgba, 256, 224).unwrap();
+        assert_eq!(gray.len(), 128 * 112);
+        assert!(gray.iter().all(|&g| g == 151));
+
+        // 2:1 downscale averages black/white columns to mid-gray
+        let stripes: Vec<u8> = (0..256 * 224)
+            .flat_map(|i| {
+                if i % 2 == 0 {
+                    [0, 0, 0, 255]
+                } else {
+                    [255, 255, 255, 255]
+                }
+            })
+            .collect();
+        let gray = rgba_to_sensor_gray(&stripes, 256, 224).unwrap();
+        assert!(gray.iter().all(|&g| g == 128), "{}", gray[0]);
+
+        // Upscaling a single pixel fills the frame
+        assert_eq!(
+            rgba_to_sensor_gray(&[0, 255, 0, 255], 1, 1).unwrap(),
+            vec![150; 128 * 112]
+        );
+        assert_eq!(rgba_to_sensor_gray(&[0; 15], 2, 2), None);
+    }
+
+    #[test]
+    fn test_orientation_mirrors_input() {
+        // Left half dark, right half light, plus a marker in the top row
+        let mut image: Vec<u8> = (0..128 * 112)
+            .map(|i| if i % 128 < 64 { 10 } else { 200 })
+            .collect();
+        image[0] = 99;
+
+        let mut cam = Camera::new();
+        cam.set_orientation(true, false, false);
+        cam.set_image(&image);
+        for y in 0..112 {
+            for x in 0..128 {
+                let expected = image[y * 128 + (127 - x)];
+                assert_eq!(cam.image[y * 128 + x], expected, "({}, {})", x, y);
+            }
+        }
+
+        // Rotating 180° moves the marker to the bottom-right corner
+        cam.set_orientation(false, false, true);
+        cam.set_image(&image);
+        assert_eq!(cam.image[128 * 112 - 1], 99);
+        assert_eq!(cam.image[0], 200);
+
+        cam.set_orientation(true, false, true); // flip_h cancels half the rotation
+        cam.set_image(&image);
+        assert_eq!(cam.image[111 * 128], 99);
+    }
+
+    #[test]
+    fn test_occupancy_mask() {
+        let mut cam = Camera::new();
+        for slot in 1..=30 {
+            cam.clear_photo_slot(slot);
+        }
+        assert_eq!(cam.occupancy(), 0);
+
+        let rgba = vec![0x80u8; 128 * 112 * 4];
+        for slot in [1, 5, 30] {
+            assert!(cam.encode_photo(slot, &rgba));
+        }
+        assert_eq!(cam.occupancy(), 1 << 0 | 1 << 4 | 1 << 29);
+        assert_eq!(cam.photo_count(), 3);
+    }
+
+    #[test]
+    fn test_preview_downscale() {
+        let mut cam = ramp_camera();
+        assert_eq!(cam.preview_colors().0.len(), 128 * 112);
+        let full_preview = cam.capture_to_rgba();
+
+        cam.set_preview_downscale(2);
+        let (colors, width) = cam.preview_colors();
+        assert_eq!(
+            (colors.len(), width),
+            (64 * 56, 64),
+            "a quarter of the pixels"
+        );
+        let preview = cam.capture_to_rgba();
+        assert_eq!(preview.len(), 128 * 112 * 4);
+        assert_eq!(preview[..4], preview[4..8], "2×2 blocks share a colour");
+        // A smooth ramp quantizes the same way at either resolution, bar
+        // the pixels next to a threshold
+        let differing = preview
+            .chunks_exact(4)
+            .zip(full_preview.chunks_exact(4))
+            .filter(|(a, b)| a != b)
+            .count();
+        assert!(differing <= 3 * 2 * 112, "{} pixels differ", differing);
+
+        // ROM captures stay full resolution
+        let mut reference = ramp_camera();
+        reference.process_capture(false);
+        cam.process_capture(false);
+        assert_eq!(cam.capture_sram(), reference.capture_sram());
+    }
+
+    #[test]
+    fn test_clear_all_slots() {
+        let mut cam = ramp_camera();
+        cam.process_capture(false);
+        let capture = cam.capture_sram().to_vec();
+        // Fresh SRAM is zeroed, which the state vector reads as all occupied
+        cam.ram[STATE_VECTOR_OFFSET..STATE_VECTOR_OFFSET + NUM_PHOTO_SLOTS].fill(0xFF);
+        let gray = vec![0x80u8; 128 * 112 * 4];
+        for slot in [1, 2, 15, 30] {
+            assert!(cam.encode_photo(slot, &gray));
+        }
+        assert_eq!(cam.photo_count(), 4);
+
+        cam.clear_all_slots();
+        assert_eq!(cam.photo_count(), 0);
+        for slot in 1..=30 {
+            assert!(
+                cam.decode_photo(slot).is_empty(),
+                "slot {} still decodes",
+                slot
+            );
+        }
+        assert!(cam.ram[RAM_BANK_SIZE..].iter().all(|&b| b == 0));
+        // 30 × 0xFF: sum wraps to 0xE2, xor of an even count is 0
+        assert_eq!((cam.ram[0x11D5], cam.ram[0x11D6]), (0xE2, 0x00));
+        assert_eq!(cam.capture_sram(), &capture[..], "slot 0 untouched");
+    }
+
+    #[test]
+    fn test_move_photo() {
+        let mut cam = Camera::new();
+        for slot in 1..=30 {
+            cam.clear_photo_slot(slot);
+        }
+        let rgba: Vec<u8> = (0..128 * 112)
+            .flat_map(|i| {
+                let v = [0x00, 0x55, 0xAA, 0xFF][(i / 8 + i / 128) % 4];
+                [v, v, v, 0xFF]
+            })
+            .collect();
+        assert!(cam.encode_photo(3, &rgba));
+        let original = cam.decode_photo(3);
+        let checksum = |cam: &Camera| (cam.ram[0x11D5], cam.ram[0x11D6]);
+        let before = checksum(&cam);
+
+        assert!(!cam.move_photo(4, 10), "empty source");
+        assert!(!cam.move_photo(3, 31), "invalid target");
+        assert!(cam.move_photo(3, 10));
+
+        assert_eq!(cam.try_decode_photo(3), Err(PhotoError::Empty));
+        assert_eq!(cam.decode_photo(10), original);
+        assert_eq!(cam.occupancy(), 1 << 9);
+        assert_eq!(
+            checksum(&cam),
+            before,
+            "sum/xor checksum ignores entry order"
+        );
+    }
+
+    #[test]
+    fn test_capture_tiles_decode_matches_slot0() {
+        let mut cam = ramp_camera();
+        cam.process_capture(false);
+        let tiles = cam.capture_sram();
+        assert_eq!(tiles.len(), 3584);
+        assert_eq!(
+            decode_2bpp_tiles_to_rgba(tiles, 16, 14),
+            cam.decode_photo(0)
+        );
+    }
+
+    #[test]
+    fn test_capture_without_image_is_blank() {
+        let mut cam = Camera::new();
+        cam.ram[0x0100..0x0100 + 3584].fill(0xA5);
+        cam.regs[0x02] = 0x10; // exposure that would otherwise be used
+        cam.process_capture(false);
+        assert!(cam.capture_sram().iter().all(|&b| b == 0));
+        assert_eq!(cam.exposure_smooth, 1.0);
+
+        // Stable across repeated captures and the invert flag
+        cam.process_capture(true);
+        assert!(cam.capture_sram().iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn test_post_adjust_reset_is_identity() {
+        let mut cam = ramp_camera();
+        cam.process_capture(false);
+        let base = cam.capture_sram().to_vec();
+
+        cam.set_post_adjust(40, 1.5);
+        cam.process_capture(false);
+        cam.set_post_adjust(0, 1.0);
+        cam.process_capture(false);
+        assert_eq!(cam.capture_sram(), &base[..]);
+    }
+}
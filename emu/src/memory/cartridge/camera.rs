@@ -7,10 +7,10 @@
 //! SRAM is always accessible regardless of the RAM enable register,
 //! matching real hardware behaviour.
 
-use super::{Cartridge, MbcType};
+use super::{Cartridge, MbcType, rom_bank_offset};
 use crate::log::{LogCategory, RateLimiter};
-use crate::{log_info, log_info_limited};
 use crate::memory::camera::Camera;
+use crate::{log_info, log_info_limited};
 
 const ROM_BANK_SIZE: usize = 0x4000;
 const RAM_BANK_SIZE: usize = 0x2000;
@@ -18,8 +18,9 @@ const RAM_BANK_SIZE: usize = 0x2000;
 pub struct PocketCamera {
     rom: Vec<u8>,
     pub camera: Camera,
-    rom_bank: u16, // 7-bit MBC3-compatible ROM bank
-    ram_bank: u8,  // 0x00-0x0F = SRAM, 0x10+ = camera registers
+    rom_bank: u16,        // 7-bit MBC3-compatible ROM bank
+    rom_bank_base: usize, // Byte offset of `rom_bank`, updated on bank switch
+    ram_bank: u8,         // 0x00-0x0F = SRAM, 0x10+ = camera registers
 }
 
 impl PocketCamera {
@@ -28,6 +29,7 @@ impl PocketCamera {
             rom,
             camera: Camera::new(),
             rom_bank: 1,
+            rom_bank_base: ROM_BANK_SIZE,
             ram_bank: 0,
         }
     }
@@ -38,8 +40,7 @@ impl Cartridge for PocketCamera {
         match addr {
             0x0000..=0x3FFF => self.rom.get(addr as usize).copied().unwrap_or(0xFF),
             0x4000..=0x7FFF => {
-                let bank = self.rom_bank.max(1) as usize;
-                let offset = bank * ROM_BANK_SIZE + (addr as usize - 0x4000);
+                let offset = self.rom_bank_base + (addr as usize - 0x4000);
                 self.rom.get(offset).copied().unwrap_or(0xFF)
             }
             _ => 0xFF,
@@ -54,6 +55,7 @@ impl Cartridge for PocketCamera {
             0x2000..=0x3FFF => {
                 let bank = value & 0x7F;
                 self.rom_bank = if bank == 0 { 1 } else { bank as u16 };
+                self.rom_bank_base = rom_bank_offset(self.rom_bank, self.rom.len());
             }
             // RAM bank / camera register bank select (5-bit)
             0x4000..=0x5FFF => {
@@ -63,7 +65,11 @@ impl Cartridge for PocketCamera {
                     "RAM bank: {} -> {} (mode={})",
                     self.ram_bank,
                     new_bank,
-                    if new_bank >= 0x10 { "CAMERA_REGS" } else { "SRAM" }
+                    if new_bank >= 0x10 {
+                        "CAMERA_REGS"
+                    } else {
+                        "SRAM"
+                    }
                 );
                 self.ram_bank = new_bank;
             }
@@ -165,6 +171,7 @@ impl Cartridge for PocketCamera {
                     );
                     self.camera.process_capture(invert);
                     self.camera.capture_dirty = true;
+                    self.camera.captures = self.camera.captures.wrapping_add(1);
                     self.camera.regs[0] &= !0x01;
                     log_info!(
                         LogCategory::Camera,
@@ -192,6 +199,10 @@ impl Cartridge for PocketCamera {
         self.camera.ram[..len].copy_from_slice(&data[..len]);
     }
 
+    fn is_battery_backed(&self) -> bool {
+        true
+    }
+
     fn mbc_type(&self) -> MbcType {
         MbcType::PocketCamera
     }
@@ -200,6 +211,10 @@ impl Cartridge for PocketCamera {
         self.rom.len() / ROM_BANK_SIZE
     }
 
+    fn rom_data(&self) -> &[u8] {
+        &self.rom
+    }
+
     fn current_rom_bank(&self) -> u16 {
         self.rom_bank
     }
@@ -3,7 +3,7 @@
 //! Supports up to 2MB ROM (128 banks) and 32KB RAM (4 banks).
 //! Two modes: ROM banking (default) and RAM banking (mode bit set).
 
-use super::{Cartridge, MbcType};
+use super::{Cartridge, MbcType, rom_bank_offset};
 
 const ROM_BANK_SIZE: usize = 0x4000; // 16KB
 const RAM_BANK_SIZE: usize = 0x2000; // 8KB
@@ -12,6 +12,7 @@ pub struct Mbc1 {
     rom: Vec<u8>,
     ram: Vec<u8>,
     rom_bank: u16, // 5-bit bank number (upper 2 bits from 0x4000-0x5FFF in ROM mode)
+    rom_bank_base: usize, // Byte offset of `rom_bank`, updated on bank switch
     ram_bank: u8,
     ram_enabled: bool,
     mode: bool, // false = ROM banking mode, true = RAM banking mode
@@ -23,11 +24,16 @@ impl Mbc1 {
             rom,
             ram: vec![0; ram_size],
             rom_bank: 1,
+            rom_bank_base: ROM_BANK_SIZE,
             ram_bank: 0,
             ram_enabled: false,
             mode: false,
         }
     }
+
+    fn update_rom_bank_base(&mut self) {
+        self.rom_bank_base = rom_bank_offset(self.rom_bank.max(1), self.rom.len());
+    }
 }
 
 impl Cartridge for Mbc1 {
@@ -35,8 +41,7 @@ impl Cartridge for Mbc1 {
         match addr {
             0x0000..=0x3FFF => self.rom.get(addr as usize).copied().unwrap_or(0xFF),
             0x4000..=0x7FFF => {
-                let bank = self.rom_bank.max(1) as usize;
-                let offset = bank * ROM_BANK_SIZE + (addr as usize - 0x4000);
+                let offset = self.rom_bank_base + (addr as usize - 0x4000);
                 self.rom.get(offset).copied().unwrap_or(0xFF)
             }
             _ => 0xFF,
@@ -50,8 +55,8 @@ impl Cartridge for Mbc1 {
             // ROM bank number (lower 5 bits); 0 → 1
             0x2000..=0x3FFF => {
                 let bank = value & 0x1F;
-                self.rom_bank =
-                    (self.rom_bank & 0x60) | (if bank == 0 { 1 } else { bank }) as u16;
+                self.rom_bank = (self.rom_bank & 0x60) | (if bank == 0 { 1 } else { bank }) as u16;
+                self.update_rom_bank_base();
             }
             // Upper 2 bits of ROM bank or RAM bank select
             0x4000..=0x5FFF => {
@@ -59,6 +64,7 @@ impl Cartridge for Mbc1 {
                     self.ram_bank = value & 0x03;
                 } else {
                     self.rom_bank = (self.rom_bank & 0x1F) | ((value as u16 & 0x03) << 5);
+                    self.update_rom_bank_base();
                 }
             }
             // Banking mode select
@@ -71,6 +77,10 @@ impl Cartridge for Mbc1 {
         if !self.ram_enabled {
             return 0xFF;
         }
+        self.peek_ram(addr)
+    }
+
+    fn peek_ram(&self, addr: u16) -> u8 {
         let offset = self.ram_bank as usize * RAM_BANK_SIZE + (addr - 0xA000) as usize;
         self.ram.get(offset).copied().unwrap_or(0xFF)
     }
@@ -102,6 +112,10 @@ impl Cartridge for Mbc1 {
         self.rom.len() / ROM_BANK_SIZE
     }
 
+    fn rom_data(&self) -> &[u8] {
+        &self.rom
+    }
+
     fn current_rom_bank(&self) -> u16 {
         self.rom_bank
     }
@@ -0,0 +1,108 @@
+//! MBC2 cartridge implementation.
+//!
+//! Supports up to 256KB ROM (16 banks) and has 512×4 bits of RAM built into
+//! the MBC. Both registers sit in 0x0000-0x3FFF: address bit 8 clear selects
+//! RAM enable, set selects the ROM bank.
+
+use super::{Cartridge, MbcType, rom_bank_offset};
+
+const ROM_BANK_SIZE: usize = 0x4000; // 16KB
+const RAM_SIZE: usize = 512; // 4-bit cells, one per byte
+
+pub struct Mbc2 {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    rom_bank: u16,        // 4-bit bank number, never 0
+    rom_bank_base: usize, // Byte offset of `rom_bank`, updated on bank switch
+    ram_enabled: bool,
+}
+
+impl Mbc2 {
+    pub fn new(rom: Vec<u8>) -> Self {
+        Mbc2 {
+            rom,
+            ram: vec![0; RAM_SIZE],
+            rom_bank: 1,
+            rom_bank_base: ROM_BANK_SIZE,
+            ram_enabled: false,
+        }
+    }
+}
+
+impl Cartridge for Mbc2 {
+    fn read_rom(&self, addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x3FFF => self.rom.get(addr as usize).copied().unwrap_or(0xFF),
+            0x4000..=0x7FFF => {
+                let offset = self.rom_bank_base + (addr as usize - 0x4000);
+                self.rom.get(offset).copied().unwrap_or(0xFF)
+            }
+            _ => 0xFF,
+        }
+    }
+
+    fn write_rom(&mut self, addr: u16, value: u8) {
+        match addr {
+            // Address bit 8 picks the register: clear = RAM enable
+            0x0000..=0x3FFF if addr & 0x0100 == 0 => {
+                self.ram_enabled = (value & 0x0F) == 0x0A;
+            }
+            // Set = ROM bank number (lower 4 bits); 0 → 1
+            0x0000..=0x3FFF => {
+                self.rom_bank = (value & 0x0F).max(1) as u16;
+                self.rom_bank_base = rom_bank_offset(self.rom_bank, self.rom.len());
+            }
+            _ => {}
+        }
+    }
+
+    fn read_ram(&self, addr: u16) -> u8 {
+        if !self.ram_enabled {
+            return 0xFF;
+        }
+        self.peek_ram(addr)
+    }
+
+    /// Only the low 9 address bits are decoded, so 0xA000-0xA1FF repeats
+    /// through 0xBFFF; the missing upper nibble reads as 1s.
+    fn peek_ram(&self, addr: u16) -> u8 {
+        self.ram[(addr as usize - 0xA000) % RAM_SIZE] | 0xF0
+    }
+
+    fn write_ram(&mut self, addr: u16, value: u8) {
+        if !self.ram_enabled {
+            return;
+        }
+        self.ram[(addr as usize - 0xA000) % RAM_SIZE] = value & 0x0F;
+    }
+
+    fn ram_data(&self) -> &[u8] {
+        &self.ram
+    }
+
+    fn load_ram(&mut self, data: &[u8]) {
+        for (cell, &byte) in self.ram.iter_mut().zip(data) {
+            *cell = byte & 0x0F;
+        }
+    }
+
+    fn mbc_type(&self) -> MbcType {
+        MbcType::Mbc2
+    }
+
+    fn rom_bank_count(&self) -> usize {
+        self.rom.len() / ROM_BANK_SIZE
+    }
+
+    fn rom_data(&self) -> &[u8] {
+        &self.rom
+    }
+
+    fn current_rom_bank(&self) -> u16 {
+        self.rom_bank
+    }
+
+    fn is_ram_enabled(&self) -> bool {
+        self.ram_enabled
+    }
+}
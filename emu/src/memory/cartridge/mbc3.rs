@@ -3,7 +3,7 @@
 //! Supports up to 2MB ROM (128 banks), 64KB RAM (8 banks), and an RTC
 //! accessible via RAM bank registers 0x08-0x0C.
 
-use super::{Cartridge, MbcType};
+use super::{Cartridge, MbcType, rom_bank_offset};
 use crate::memory::rtc::Rtc;
 
 const ROM_BANK_SIZE: usize = 0x4000;
@@ -12,8 +12,9 @@ const RAM_BANK_SIZE: usize = 0x2000;
 pub struct Mbc3 {
     rom: Vec<u8>,
     ram: Vec<u8>,
-    rom_bank: u16, // 7-bit bank number
-    ram_bank: u8,  // 0x00-0x03 = RAM, 0x08-0x0C = RTC
+    rom_bank: u16,        // 7-bit bank number
+    rom_bank_base: usize, // Byte offset of `rom_bank`, updated on bank switch
+    ram_bank: u8,         // 0x00-0x03 = RAM, 0x08-0x0C = RTC
     ram_enabled: bool,
     rtc: Rtc,
 }
@@ -24,6 +25,7 @@ impl Mbc3 {
             rom,
             ram: vec![0; ram_size],
             rom_bank: 1,
+            rom_bank_base: ROM_BANK_SIZE,
             ram_bank: 0,
             ram_enabled: false,
             rtc: Rtc::new(),
@@ -36,8 +38,7 @@ impl Cartridge for Mbc3 {
         match addr {
             0x0000..=0x3FFF => self.rom.get(addr as usize).copied().unwrap_or(0xFF),
             0x4000..=0x7FFF => {
-                let bank = self.rom_bank.max(1) as usize;
-                let offset = bank * ROM_BANK_SIZE + (addr as usize - 0x4000);
+                let offset = self.rom_bank_base + (addr as usize - 0x4000);
                 self.rom.get(offset).copied().unwrap_or(0xFF)
             }
             _ => 0xFF,
@@ -50,6 +51,7 @@ impl Cartridge for Mbc3 {
             0x2000..=0x3FFF => {
                 let bank = value & 0x7F;
                 self.rom_bank = if bank == 0 { 1 } else { bank as u16 };
+                self.rom_bank_base = rom_bank_offset(self.rom_bank, self.rom.len());
             }
             // RAM bank or RTC register select
             0x4000..=0x5FFF => self.ram_bank = value,
@@ -59,23 +61,30 @@ impl Cartridge for Mbc3 {
         }
     }
 
+    /// The RAM-enable gate covers the RTC registers too. Banks 0x08-0x0C
+    /// read the snapshot taken by the last latch, never the live clock.
     fn read_ram(&self, addr: u16) -> u8 {
-        if Rtc::is_rtc_register(self.ram_bank) {
-            return self.rtc.read_register(self.ram_bank);
-        }
         if !self.ram_enabled {
             return 0xFF;
         }
+        self.peek_ram(addr)
+    }
+
+    fn peek_ram(&self, addr: u16) -> u8 {
+        if Rtc::is_rtc_register(self.ram_bank) {
+            return self.rtc.read_register(self.ram_bank);
+        }
         let offset = self.ram_bank as usize * RAM_BANK_SIZE + (addr - 0xA000) as usize;
         self.ram.get(offset).copied().unwrap_or(0xFF)
     }
 
+    /// Writes to banks 0x08-0x0C set the live clock.
     fn write_ram(&mut self, addr: u16, value: u8) {
-        if Rtc::is_rtc_register(self.ram_bank) {
-            self.rtc.write_register(self.ram_bank, value);
+        if !self.ram_enabled {
             return;
         }
-        if !self.ram_enabled {
+        if Rtc::is_rtc_register(self.ram_bank) {
+            self.rtc.write_register(self.ram_bank, value);
             return;
         }
         let offset = self.ram_bank as usize * RAM_BANK_SIZE + (addr - 0xA000) as usize;
@@ -93,6 +102,27 @@ impl Cartridge for Mbc3 {
         self.ram[..len].copy_from_slice(&data[..len]);
     }
 
+    fn export_ram_with_rtc(&self) -> Vec<u8> {
+        let mut out = self.ram.clone();
+        out.extend_from_slice(&self.rtc_save());
+        out
+    }
+
+    fn import_ram_with_rtc(&mut self, data: &[u8]) {
+        self.load_ram(data);
+        if let Some(footer) = data.get(self.ram.len()..) {
+            self.rtc_load(footer);
+        }
+    }
+
+    fn rtc_save(&self) -> Vec<u8> {
+        self.rtc.to_save_bytes().to_vec()
+    }
+
+    fn rtc_load(&mut self, data: &[u8]) -> bool {
+        self.rtc.load_save_bytes(data)
+    }
+
     fn mbc_type(&self) -> MbcType {
         MbcType::Mbc3
     }
@@ -101,6 +131,10 @@ impl Cartridge for Mbc3 {
         self.rom.len() / ROM_BANK_SIZE
     }
 
+    fn rom_data(&self) -> &[u8] {
+        &self.rom
+    }
+
     fn current_rom_bank(&self) -> u16 {
         self.rom_bank
     }
@@ -116,4 +150,12 @@ impl Cartridge for Mbc3 {
     fn tick_rtc(&mut self) {
         self.rtc.tick();
     }
+
+    fn set_rtc_paused(&mut self, paused: bool) {
+        self.rtc.set_paused(paused);
+    }
+
+    fn set_rtc_frames_per_second(&mut self, fps: Option<u32>) {
+        self.rtc.set_frames_per_second(fps);
+    }
 }
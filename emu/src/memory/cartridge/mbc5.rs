@@ -1,9 +1,10 @@
 //! MBC5 cartridge implementation.
 //!
 //! Supports up to 8MB ROM (512 banks, 9-bit bank number) and 128KB RAM
-//! (16 banks, 4-bit bank number).
+//! (16 banks, 4-bit bank number). Rumble carts (0x1C-0x1E) wire bit 3 of
+//! the RAM bank register to the motor instead, leaving 8 RAM banks.
 
-use super::{Cartridge, MbcType};
+use super::{Cartridge, MbcType, rom_bank_offset};
 
 const ROM_BANK_SIZE: usize = 0x4000;
 const RAM_BANK_SIZE: usize = 0x2000;
@@ -11,21 +12,31 @@ const RAM_BANK_SIZE: usize = 0x2000;
 pub struct Mbc5 {
     rom: Vec<u8>,
     ram: Vec<u8>,
-    rom_bank: u16, // 9-bit bank number (low 8 + high 1 bit)
-    ram_bank: u8,  // 4-bit bank number
+    rom_bank: u16,        // 9-bit bank number (low 8 + high 1 bit)
+    rom_bank_base: usize, // Byte offset of `rom_bank`, updated on bank switch
+    ram_bank: u8,         // 4-bit bank number (3-bit on rumble carts)
     ram_enabled: bool,
+    has_rumble: bool,
+    motor_on: bool,
 }
 
 impl Mbc5 {
     pub fn new(rom: Vec<u8>, ram_size: usize) -> Self {
         Mbc5 {
-            rom,
             ram: vec![0; ram_size],
             rom_bank: 1,
+            rom_bank_base: ROM_BANK_SIZE,
             ram_bank: 0,
             ram_enabled: false,
+            has_rumble: matches!(rom.get(0x0147), Some(0x1C..=0x1E)),
+            motor_on: false,
+            rom,
         }
     }
+
+    fn update_rom_bank_base(&mut self) {
+        self.rom_bank_base = rom_bank_offset(self.rom_bank, self.rom.len());
+    }
 }
 
 impl Cartridge for Mbc5 {
@@ -33,8 +44,7 @@ impl Cartridge for Mbc5 {
         match addr {
             0x0000..=0x3FFF => self.rom.get(addr as usize).copied().unwrap_or(0xFF),
             0x4000..=0x7FFF => {
-                let bank = self.rom_bank as usize;
-                let offset = bank * ROM_BANK_SIZE + (addr as usize - 0x4000);
+                let offset = self.rom_bank_base + (addr as usize - 0x4000);
                 self.rom.get(offset).copied().unwrap_or(0xFF)
             }
             _ => 0xFF,
@@ -47,12 +57,18 @@ impl Cartridge for Mbc5 {
             // Low 8 bits of ROM bank number (0x2000-0x2FFF)
             0x2000..=0x2FFF => {
                 self.rom_bank = (self.rom_bank & 0x100) | (value as u16);
+                self.update_rom_bank_base();
             }
             // High bit of ROM bank number (0x3000-0x3FFF)
             0x3000..=0x3FFF => {
                 self.rom_bank = (self.rom_bank & 0xFF) | ((value as u16 & 1) << 8);
+                self.update_rom_bank_base();
+            }
+            // RAM bank select (4-bit), or 3-bit plus the rumble motor
+            0x4000..=0x5FFF if self.has_rumble => {
+                self.ram_bank = value & 0x07;
+                self.motor_on = value & 0x08 != 0;
             }
-            // RAM bank select (4-bit)
             0x4000..=0x5FFF => self.ram_bank = value & 0x0F,
             _ => {}
         }
@@ -62,6 +78,10 @@ impl Cartridge for Mbc5 {
         if !self.ram_enabled {
             return 0xFF;
         }
+        self.peek_ram(addr)
+    }
+
+    fn peek_ram(&self, addr: u16) -> u8 {
         let offset = self.ram_bank as usize * RAM_BANK_SIZE + (addr - 0xA000) as usize;
         self.ram.get(offset).copied().unwrap_or(0xFF)
     }
@@ -93,6 +113,10 @@ impl Cartridge for Mbc5 {
         self.rom.len() / ROM_BANK_SIZE
     }
 
+    fn rom_data(&self) -> &[u8] {
+        &self.rom
+    }
+
     fn current_rom_bank(&self) -> u16 {
         self.rom_bank
     }
@@ -104,4 +128,8 @@ impl Cartridge for Mbc5 {
     fn is_ram_enabled(&self) -> bool {
         self.ram_enabled
     }
+
+    fn is_rumbling(&self) -> bool {
+        self.motor_on
+    }
 }
@@ -6,6 +6,7 @@
 
 mod camera;
 mod mbc1;
+mod mbc2;
 mod mbc3;
 mod mbc5;
 mod mbc7;
@@ -13,6 +14,7 @@ mod none;
 
 pub use camera::PocketCamera;
 pub use mbc1::Mbc1;
+pub use mbc2::Mbc2;
 pub use mbc3::Mbc3;
 pub use mbc5::Mbc5;
 pub use mbc7::Mbc7;
@@ -20,11 +22,21 @@ pub use none::NoMbc;
 
 use super::camera::Camera;
 
+/// Byte offset of switchable ROM `bank`, masked to the cartridge size the
+/// way unconnected upper bank lines wrap on hardware: on a 4-bank ROM,
+/// bank 7 reads bank 3.
+pub(super) fn rom_bank_offset(bank: u16, rom_len: usize) -> usize {
+    const ROM_BANK_SIZE: usize = 0x4000;
+    let banks = (rom_len / ROM_BANK_SIZE).max(1).next_power_of_two();
+    (bank as usize & (banks - 1)) * ROM_BANK_SIZE
+}
+
 /// Cartridge/MBC type identifier.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum MbcType {
     None,         // No MBC (32KB ROM only)
     Mbc1,         // MBC1
+    Mbc2,         // MBC2 (512×4-bit built-in RAM)
     Mbc3,         // MBC3 (with RTC support)
     Mbc5,         // MBC5
     Mbc7,         // MBC7 (accelerometer + EEPROM; Kirby's Tilt 'n' Tumble)
@@ -41,16 +53,55 @@ pub trait Cartridge {
     fn write_rom(&mut self, addr: u16, value: u8);
     /// Read from external RAM (0xA000-0xBFFF).
     fn read_ram(&self, addr: u16) -> u8;
+    /// Debug read of external RAM that ignores the RAM-enable gate.
+    /// Default: same as `read_ram` (carts without a gate).
+    fn peek_ram(&self, addr: u16) -> u8 {
+        self.read_ram(addr)
+    }
     /// Write to external RAM (0xA000-0xBFFF).
     fn write_ram(&mut self, addr: u16, value: u8);
     /// Borrow the full cartridge RAM slice (for save data export).
     fn ram_data(&self) -> &[u8];
     /// Load save data into cartridge RAM (truncated if too long).
     fn load_ram(&mut self, data: &[u8]);
+    /// Save data in the common emulator `.sav` layout: RAM plus any
+    /// RTC footer. Default: RAM only.
+    fn export_ram_with_rtc(&self) -> Vec<u8> {
+        self.ram_data().to_vec()
+    }
+    /// Load a `.sav` produced by `export_ram_with_rtc` (or another emulator).
+    fn import_ram_with_rtc(&mut self, data: &[u8]) {
+        self.load_ram(data);
+    }
+    /// The RTC on its own in the 48-byte BGB/VBA footer layout (see
+    /// `Rtc::to_save_bytes`). Default: empty (no RTC).
+    fn rtc_save(&self) -> Vec<u8> {
+        Vec::new()
+    }
+    /// Restore the RTC from `rtc_save` output; false if there is no RTC or
+    /// `data` is too short.
+    fn rtc_load(&mut self, _data: &[u8]) -> bool {
+        false
+    }
+    /// Whether RAM survives power-off, so a `.sav` is worth writing.
+    /// Default: derived from the header cartridge type byte (0x0147) of the
+    /// raw image, not through `read_rom`, which MBC1 mode 1 can remap.
+    fn is_battery_backed(&self) -> bool {
+        self.rom_data()
+            .get(0x0147)
+            .is_some_and(|&cart_type| has_battery(cart_type))
+    }
     /// MBC type identifier.
     fn mbc_type(&self) -> MbcType;
     /// Total number of 16KB ROM banks.
     fn rom_bank_count(&self) -> usize;
+    /// The full ROM image as loaded.
+    fn rom_data(&self) -> &[u8];
+    /// SHA-1 of the full ROM image, for matching database entries and
+    /// keying saves independently of the header title.
+    fn rom_sha1(&self) -> [u8; 20] {
+        crate::sha1::sha1(self.rom_data())
+    }
     /// Currently selected ROM bank (for debug).
     fn current_rom_bank(&self) -> u16 {
         1
@@ -65,6 +116,15 @@ pub trait Cartridge {
     }
     /// Advance the RTC by wall-clock time (no-op for non-MBC3 cartridges).
     fn tick_rtc(&mut self) {}
+    /// Freeze/resume the RTC while emulation is paused (no-op without an RTC).
+    fn set_rtc_paused(&mut self, _paused: bool) {}
+    /// Advance the RTC one second per `fps` ticks instead of by wall-clock
+    /// time, or follow the wall clock again (`None`). No-op without an RTC.
+    fn set_rtc_frames_per_second(&mut self, _fps: Option<u32>) {}
+    /// Whether the rumble motor is currently driven (MBC5 rumble carts).
+    fn is_rumbling(&self) -> bool {
+        false
+    }
     /// Return the inner `Camera` if this is a Pocket Camera cartridge.
     fn as_camera(&self) -> Option<&Camera> {
         None
@@ -92,15 +152,32 @@ pub fn ram_size_from_header(byte: u8) -> usize {
     }
 }
 
+/// Whether a header cartridge type byte (0x0147) includes a battery.
+pub fn has_battery(cart_type: u8) -> bool {
+    matches!(
+        cart_type,
+        0x03              // MBC1+RAM+BATTERY
+        | 0x06            // MBC2+BATTERY
+        | 0x09            // ROM+RAM+BATTERY
+        | 0x0D            // MMM01+RAM+BATTERY
+        | 0x0F | 0x10     // MBC3+TIMER+BATTERY, MBC3+TIMER+RAM+BATTERY
+        | 0x13            // MBC3+RAM+BATTERY
+        | 0x1B | 0x1E     // MBC5(+RUMBLE)+RAM+BATTERY
+        | 0x22            // MBC7+SENSOR+RUMBLE+RAM+BATTERY
+        | 0xFC // Pocket Camera
+    )
+}
+
 /// Create the appropriate cartridge implementation for a given ROM.
 pub fn make_cartridge(rom: Vec<u8>, cart_type: u8, ram_size: usize) -> Box<dyn Cartridge> {
     match cart_type {
         0x00 => Box::new(NoMbc::new(rom)),
         0x01..=0x03 => Box::new(Mbc1::new(rom, ram_size)),
+        0x05..=0x06 => Box::new(Mbc2::new(rom)),
         0x0F..=0x13 => Box::new(Mbc3::new(rom, ram_size)),
         0x19..=0x1E => Box::new(Mbc5::new(rom, ram_size)),
-        0x22        => Box::new(Mbc7::new(rom)),
-        0xFC        => Box::new(PocketCamera::new(rom)),
+        0x22 => Box::new(Mbc7::new(rom)),
+        0xFC => Box::new(PocketCamera::new(rom)),
         _ => Box::new(Mbc5::new(rom, ram_size)), // safe default for unknown types
     }
 }
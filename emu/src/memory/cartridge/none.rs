@@ -44,4 +44,8 @@ impl Cartridge for NoMbc {
     fn rom_bank_count(&self) -> usize {
         self.rom.len() / ROM_BANK_SIZE
     }
+
+    fn rom_data(&self) -> &[u8] {
+        &self.rom
+    }
 }
@@ -8,6 +8,8 @@
 //! accessed by the PPU and general bus; only the *control* state (bank index,
 //! armed flag, etc.) sits here.
 
+use std::hash::{Hash, Hasher};
+
 /// All Game Boy Color–specific emulator state.
 pub struct Cgb {
     /// GBC mode active (set explicitly by the caller, never auto-detected).
@@ -21,6 +23,10 @@ pub struct Cgb {
     pub bcps: u8,
     /// FF6A: OBJ palette index register (same layout as bcps).
     pub ocps: u8,
+    /// BCPD has been written since power-on (palette RAM holds real colours).
+    pub bg_palette_written: bool,
+    /// OCPD has been written since power-on.
+    pub obj_palette_written: bool,
 
     /// Current VRAM bank (0 or 1); controlled by VBK (0xFF4F).
     pub vram_bank: usize,
@@ -52,6 +58,8 @@ impl Cgb {
             obj_palette_ram: [0; 64],
             bcps: 0,
             ocps: 0,
+            bg_palette_written: false,
+            obj_palette_written: false,
             vram_bank: 0,
             wram_bank: 1,
             double_speed: false,
@@ -75,7 +83,28 @@ impl Cgb {
     #[inline]
     pub fn read_obj_palette(&self, palette: usize, color: usize) -> (u8, u8) {
         let offset = palette * 8 + color * 2;
-        (self.obj_palette_ram[offset], self.obj_palette_ram[offset + 1])
+        (
+            self.obj_palette_ram[offset],
+            self.obj_palette_ram[offset + 1],
+        )
+    }
+
+    /// Feed all GBC state into `h` (see `GameBoyCore::state_checksum`).
+    pub fn hash_state<H: Hasher>(&self, h: &mut H) {
+        self.mode.hash(h);
+        self.bg_palette_ram.hash(h);
+        self.obj_palette_ram.hash(h);
+        [self.bcps, self.ocps, self.hdma_len].hash(h);
+        [self.bg_palette_written, self.obj_palette_written].hash(h);
+        [self.vram_bank, self.wram_bank].hash(h);
+        [
+            self.double_speed,
+            self.speed_armed,
+            self.hdma_active,
+            self.hdma_hblank,
+        ]
+        .hash(h);
+        [self.hdma_source, self.hdma_dest].hash(h);
     }
 
     /// Toggle double-speed mode (invoked by the STOP opcode when KEY1 bit 0 is set).
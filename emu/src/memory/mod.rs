@@ -6,14 +6,19 @@
 //! `cgb.mode` so a DMG ROM cannot accidentally trigger GBC behaviour.
 
 pub(crate) mod camera;
+pub mod cartridge;
 mod cgb;
 pub(crate) mod rtc;
-pub mod cartridge;
 
 use std::fmt;
+use std::hash::{Hash, Hasher};
 
+use camera::PhotoError;
 use cgb::Cgb;
 
+use crate::apu::Apu;
+use crate::serial::SerialLink;
+
 pub use cartridge::MbcType;
 use cartridge::{Cartridge, make_cartridge, ram_size_from_header};
 
@@ -39,18 +44,18 @@ pub(crate) mod io {
     pub const WX: u8 = 0x4B;
     // GBC registers
     pub const KEY1: u8 = 0x4D; // speed switch
-    pub const VBK: u8 = 0x4F;  // VRAM bank
+    pub const VBK: u8 = 0x4F; // VRAM bank
     pub const HDMA1: u8 = 0x51; // DMA source high
     pub const HDMA2: u8 = 0x52; // DMA source low
     pub const HDMA3: u8 = 0x53; // DMA dest high
     pub const HDMA4: u8 = 0x54; // DMA dest low
     pub const HDMA5: u8 = 0x55; // DMA control/trigger
-    pub const RP: u8 = 0x56;    // Infrared (stub)
-    pub const BCPS: u8 = 0x68;  // BG palette index
-    pub const BCPD: u8 = 0x69;  // BG palette data
-    pub const OCPS: u8 = 0x6A;  // OBJ palette index
-    pub const OCPD: u8 = 0x6B;  // OBJ palette data
-    pub const SVBK: u8 = 0x70;  // WRAM bank
+    pub const RP: u8 = 0x56; // Infrared (stub)
+    pub const BCPS: u8 = 0x68; // BG palette index
+    pub const BCPD: u8 = 0x69; // BG palette data
+    pub const OCPS: u8 = 0x6A; // OBJ palette index
+    pub const OCPD: u8 = 0x6B; // OBJ palette data
+    pub const SVBK: u8 = 0x70; // WRAM bank
 }
 
 /// Debug state for Memory inspection.
@@ -106,13 +111,20 @@ impl fmt::Display for IoState {
     }
 }
 
+const DMG_VRAM_BANKS: usize = 1;
+const DMG_WRAM_BANKS: usize = 2;
+const CGB_VRAM_BANKS: usize = 2;
+const CGB_WRAM_BANKS: usize = 8;
+
 pub struct Memory {
     // Cartridge: owns ROM, RAM, and all MBC banking state
     cartridge: Box<dyn Cartridge>,
 
     // Internal memory
-    vram: [[u8; 0x2000]; 2], // 0x8000-0x9FFF; bank 0 = tiles, bank 1 = GBC tile attrs
-    wram: [[u8; 0x1000]; 8], // bank 0 fixed (0xC000-0xCFFF), banks 1-7 switchable (0xD000-0xDFFF)
+    // Bank counts are fixed at load_rom: DMG gets 1 VRAM + 2 WRAM banks,
+    // CGB 2 VRAM + 8 WRAM banks.
+    vram: Vec<[u8; 0x2000]>, // 0x8000-0x9FFF; bank 0 = tiles, bank 1 = GBC tile attrs
+    wram: Vec<[u8; 0x1000]>, // bank 0 fixed (0xC000-0xCFFF), banks 1-7 switchable (0xD000-0xDFFF)
     oam: [u8; 0xA0],         // 0xFE00-0xFE9F
     io: [u8; 0x80],          // 0xFF00-0xFF7F
     hram: [u8; 0x7F],        // 0xFF80-0xFFFE
@@ -121,25 +133,78 @@ pub struct Memory {
     // GBC-specific state (palette RAM, banking control, double-speed, HDMA)
     cgb: Cgb,
 
+    // Sound: owns 0xFF10-0xFF3F, advanced by `tick_apu`
+    apu: Apu,
+
     // Serial output buffer (for test ROM debugging)
     serial_output: Vec<u8>,
+    // Peer on the other end of the link cable (None = disconnected)
+    serial_link: Option<Box<dyn SerialLink>>,
+    // Cycles until the transfer started by SC bit 7 completes (0 = idle),
+    // advanced by `tick_serial`
+    serial_cycles_left: u32,
+    // SB latched when the transfer started: what the log records and the
+    // peer receives, whatever the CPU writes to SB meanwhile
+    serial_out: u8,
+
+    // Debugger override of the cart RAM-enable gate (affects `peek` only)
+    ram_enable_override: Option<bool>,
+    // Cart RAM size to allocate instead of the header's (survives load_rom)
+    ram_size_override: Option<usize>,
+    // Frame-driven RTC rate (survives load_rom; None = wall clock)
+    rtc_frames_per_second: Option<u32>,
+    // Lock CPU reads/writes of VRAM/OAM while the PPU owns them (accurate mode)
+    access_blocking: bool,
+
+    // (addr, value) pairs whose write stops `step_until_break`, and the
+    // last one that tripped
+    value_watchpoints: Vec<(u16, u8)>,
+    watch_hit: Option<(u16, u8)>,
+
+    // OAM DMA started by a 0xFF46 write, advanced by `tick_oam_dma`
+    oam_dma: Option<OamDma>,
+
+    // Boot ROM image (survives load_rom) and whether it is still mapped
+    // over the cartridge; a nonzero write to 0xFF50 unmaps it until reset
+    boot_rom: Option<Vec<u8>>,
+    boot_rom_mapped: bool,
+}
+
+/// An OAM DMA in progress: one byte per M-cycle (4 T-cycles) from `source`.
+#[derive(Clone, Copy)]
+struct OamDma {
+    source: u16,
+    copied: u8,
+    cycles: u32,
 }
 
 impl Memory {
     pub fn new() -> Self {
         // Default cartridge: NoMbc with empty ROM
-        let cartridge: Box<dyn Cartridge> =
-            Box::new(cartridge::NoMbc::new(vec![]));
+        let cartridge: Box<dyn Cartridge> = Box::new(cartridge::NoMbc::new(vec![]));
         let mut mem = Memory {
             cartridge,
-            vram: [[0; 0x2000]; 2],
-            wram: [[0; 0x1000]; 8],
+            vram: vec![[0; 0x2000]; DMG_VRAM_BANKS],
+            wram: vec![[0; 0x1000]; DMG_WRAM_BANKS],
             oam: [0; 0xA0],
             io: [0; 0x80],
             hram: [0; 0x7F],
             ie: 0,
             cgb: Cgb::new(),
+            apu: Apu::new(),
             serial_output: Vec::new(),
+            serial_link: None,
+            serial_cycles_left: 0,
+            serial_out: 0,
+            ram_enable_override: None,
+            ram_size_override: None,
+            rtc_frames_per_second: None,
+            access_blocking: false,
+            value_watchpoints: Vec::new(),
+            watch_hit: None,
+            oam_dma: None,
+            boot_rom: None,
+            boot_rom_mapped: false,
         };
         mem.init_io_defaults();
         mem
@@ -176,12 +241,18 @@ impl Memory {
         let ram_size = if cart_type == 0xFC {
             128 * 1024 // Game Boy Camera always has 128KB RAM
         } else {
-            ram_size_from_header(data[0x0149])
+            self.ram_size_override
+                .unwrap_or_else(|| ram_size_from_header(data[0x0149]))
         };
 
         // Reset hardware state (power cycle)
-        self.vram = [[0; 0x2000]; 2];
-        self.wram = [[0; 0x1000]; 8];
+        let (vram_banks, wram_banks) = if cgb_mode {
+            (CGB_VRAM_BANKS, CGB_WRAM_BANKS)
+        } else {
+            (DMG_VRAM_BANKS, DMG_WRAM_BANKS)
+        };
+        self.vram = vec![[0; 0x2000]; vram_banks];
+        self.wram = vec![[0; 0x1000]; wram_banks];
         self.oam = [0; 0xA0];
         self.io = [0; 0x80];
         self.hram = [0; 0x7F];
@@ -189,17 +260,68 @@ impl Memory {
         self.cgb = Cgb::new();
         self.cgb.mode = cgb_mode;
         self.init_io_defaults();
+        self.apu.reset();
+        self.oam_dma = None;
+        self.serial_cycles_left = 0;
 
         self.cartridge = make_cartridge(data.to_vec(), cart_type, ram_size);
+        if self.rtc_frames_per_second.is_some() {
+            self.cartridge
+                .set_rtc_frames_per_second(self.rtc_frames_per_second);
+        }
+        self.boot_rom_mapped = self.boot_rom.is_some();
+
+        Ok(())
+    }
 
+    /// Install a boot ROM: 256 bytes (DMG) mapped at 0x0000-0x00FF, or 2304
+    /// bytes (CGB) also covering 0x0200-0x08FF (0x0100-0x01FF stays the
+    /// cartridge header). Installing only stores the image: it is mapped by
+    /// the next `load_rom`, never over a running game's vectors, and stays
+    /// mapped until the boot code writes 0xFF50.
+    pub fn load_boot_rom(&mut self, data: &[u8]) -> Result<(), &'static str> {
+        if data.len() != 0x100 && data.len() != 0x900 {
+            return Err("Boot ROM must be 256 or 2304 bytes");
+        }
+        self.boot_rom = Some(data.to_vec());
         Ok(())
     }
 
+    /// Whether the boot ROM currently shadows the cartridge.
+    pub fn is_boot_rom_mapped(&self) -> bool {
+        self.boot_rom_mapped
+    }
+
+    /// Boot ROM byte shadowing `addr`, while the boot ROM is mapped.
+    #[inline]
+    fn boot_rom_byte(&self, addr: u16) -> Option<u8> {
+        if !self.boot_rom_mapped {
+            return None;
+        }
+        match addr {
+            0x0000..=0x00FF | 0x0200..=0x08FF => {
+                self.boot_rom.as_ref()?.get(addr as usize).copied()
+            }
+            _ => None,
+        }
+    }
+
+    /// Allocate `Some(bytes)` of cartridge RAM on subsequent `load_rom`
+    /// calls regardless of header byte 0x0149 (homebrew with a wrong
+    /// header), or follow the header again (`None`). Ignored for the camera
+    /// and MBC2, whose RAM sizes are fixed.
+    pub fn set_ram_size_override(&mut self, bytes: Option<usize>) {
+        self.ram_size_override = bytes;
+    }
+
     #[inline]
     pub fn read(&self, addr: u16) -> u8 {
         match addr {
-            // ROM (cartridge owns bank switching)
-            0x0000..=0x7FFF => self.cartridge.read_rom(addr),
+            // ROM (cartridge owns bank switching), unless the boot ROM covers it
+            0x0000..=0x7FFF => match self.boot_rom_byte(addr) {
+                Some(byte) => byte,
+                None => self.cartridge.read_rom(addr),
+            },
 
             // Video RAM (bank selected by VBK; DMG always uses bank 0)
             0x8000..=0x9FFF => {
@@ -228,7 +350,7 @@ impl Memory {
             0xFE00..=0xFE9F => self.oam[(addr - 0xFE00) as usize],
 
             // Unusable
-            0xFEA0..=0xFEFF => 0xFF,
+            0xFEA0..=0xFEFF => self.read_unusable(addr),
 
             // I/O Registers
             0xFF00..=0xFF7F => self.read_io(addr),
@@ -241,16 +363,133 @@ impl Memory {
         }
     }
 
+    /// 0xFEA0-0xFEFF. While the PPU owns OAM (modes 2 and 3) every model
+    /// reads 0xFF. Otherwise DMG still reads 0xFF here, while CGB (revision
+    /// E and later) returns the high nibble of the low address byte twice,
+    /// e.g. 0xFEB4 reads 0xBB. Earlier CGB revisions differ and are not
+    /// modelled.
+    fn read_unusable(&self, addr: u16) -> u8 {
+        if self.oam_blocked() || !self.cgb.mode {
+            return 0xFF;
+        }
+        let nibble = (addr as u8) & 0xF0;
+        nibble | (nibble >> 4)
+    }
+
+    /// The PPU owns OAM during OAM scan and drawing (modes 2 and 3).
+    fn oam_blocked(&self) -> bool {
+        let lcd_on = self.io[io::LCDC as usize] & 0x80 != 0;
+        lcd_on && matches!(self.io[io::STAT as usize] & 0x03, 2 | 3)
+    }
+
+    /// The PPU owns VRAM while drawing (mode 3).
+    fn vram_blocked(&self) -> bool {
+        let lcd_on = self.io[io::LCDC as usize] & 0x80 != 0;
+        lcd_on && self.io[io::STAT as usize] & 0x03 == 3
+    }
+
+    /// Lock VRAM during mode 3 and OAM during modes 2-3 against the CPU, as
+    /// the hardware does: writes are dropped and reads (through the bus)
+    /// return 0xFF. Off by default because mode 3 timing is approximate and
+    /// can lock out accesses real hardware would let through.
+    pub fn set_access_blocking(&mut self, enabled: bool) {
+        self.access_blocking = enabled;
+    }
+
+    /// Whether a CPU access to `addr` hits VRAM or OAM while the PPU has it
+    /// locked (see `set_access_blocking`).
+    pub(crate) fn cpu_access_blocked(&self, addr: u16) -> bool {
+        self.access_blocking
+            && match addr {
+                0x8000..=0x9FFF => self.vram_blocked(),
+                0xFE00..=0xFE9F => self.oam_blocked(),
+                _ => false,
+            }
+    }
+
+    /// Write VRAM `bank` regardless of PPU mode, for DMA/HDMA and debuggers.
+    /// `addr` is 0x8000-0x9FFF (or an offset into VRAM); writes to a bank
+    /// this mode doesn't have are dropped.
+    pub fn write_vram_unchecked(&mut self, bank: usize, addr: u16, value: u8) {
+        if let Some(vram) = self.vram.get_mut(bank) {
+            vram[(addr & 0x1FFF) as usize] = value;
+        }
+    }
+
+    /// Write OAM byte `index` (0-0x9F) regardless of PPU mode.
+    pub fn write_oam_unchecked(&mut self, index: u8, value: u8) {
+        if let Some(byte) = self.oam.get_mut(index as usize) {
+            *byte = value;
+        }
+    }
+
+    /// Debug read: same as `read`, except external RAM honours the
+    /// RAM-enable override. Never changes cartridge state.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: read_byte, read_range
+    pub fn peek(&self, addr: u16) -> u8 {
+        match (addr, self.ram_enable_override) {
+            (0xA000..=0xBFFF, Some(true)) => self.cartridge.peek_ram(addr),
+            (0xA000..=0xBFFF, Some(false)) => 0xFF,
+            _ => self.read(addr),
+        }
+    }
+
+    /// Debug write: same as `write`, except VRAM and OAM are written even
+    /// while the PPU would block the CPU.
+    pub fn poke(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x8000..=0x9FFF => {
+                let bank = if self.cgb.mode { self.cgb.vram_bank } else { 0 };
+                self.write_vram_unchecked(bank, addr, value);
+            }
+            0xFE00..=0xFE9F => self.write_oam_unchecked((addr - 0xFE00) as u8, value),
+            _ => self.write(addr, value),
+        }
+    }
+
+    /// Record a write of exactly `value` to `addr` (see `take_watch_hit`).
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: add_value_watchpoint
+    pub fn add_value_watchpoint(&mut self, addr: u16, value: u8) {
+        if !self.value_watchpoints.contains(&(addr, value)) {
+            self.value_watchpoints.push((addr, value));
+        }
+    }
+
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: clear_value_watchpoints
+    pub fn clear_value_watchpoints(&mut self) {
+        self.value_watchpoints.clear();
+        self.watch_hit = None;
+    }
+
+    /// The watched write seen since the last call, if any.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: step_until_break
+    pub fn take_watch_hit(&mut self) -> Option<(u16, u8)> {
+        self.watch_hit.take()
+    }
+
+    /// Force debug reads of external RAM on (`Some(true)`), off
+    /// (`Some(false)`), or follow the cart's enable register (`None`).
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: set_ram_enable_override
+    pub fn set_ram_enable_override(&mut self, value: Option<bool>) {
+        self.ram_enable_override = value;
+    }
+
     #[inline]
     pub fn write(&mut self, addr: u16, value: u8) {
+        if !self.value_watchpoints.is_empty() && self.value_watchpoints.contains(&(addr, value)) {
+            self.watch_hit = Some((addr, value));
+        }
         match addr {
             // MBC register writes (RAM enable, bank select, mode)
             0x0000..=0x7FFF => self.cartridge.write_rom(addr, value),
 
             // Video RAM (DMG always uses bank 0)
             0x8000..=0x9FFF => {
+                if self.cpu_access_blocked(addr) {
+                    return;
+                }
                 let bank = if self.cgb.mode { self.cgb.vram_bank } else { 0 };
-                self.vram[bank][(addr - 0x8000) as usize] = value;
+                self.write_vram_unchecked(bank, addr, value);
             }
 
             // External RAM / Camera registers
@@ -271,7 +510,11 @@ impl Memory {
             }
 
             // OAM
-            0xFE00..=0xFE9F => self.oam[(addr - 0xFE00) as usize] = value,
+            0xFE00..=0xFE9F => {
+                if !self.cpu_access_blocked(addr) {
+                    self.write_oam_unchecked((addr - 0xFE00) as u8, value);
+                }
+            }
 
             // Unusable
             0xFEA0..=0xFEFF => {}
@@ -337,7 +580,9 @@ impl Memory {
                     0xFF
                 }
             }
+            0x10..=0x3F => self.apu.read(addr),
             0x56 => 0xFF, // RP: infrared stub — open bus in both modes
+            0x50 => 0xFE | !self.boot_rom_mapped as u8, // BANK: bit 0 = boot ROM off
             0x70 => {
                 if self.cgb.mode {
                     self.cgb.wram_bank as u8 | 0xF8
@@ -355,19 +600,34 @@ impl Memory {
         match offset {
             // 0xFF00 (joypad) is intercepted by MemoryBus
             // 0xFF04-0xFF07 (timer) are intercepted by MemoryBus
-
             0x02 => {
-                // SC: when bit 7 set, transfer SB to serial output
+                // SC: setting bit 7 starts a transfer of SB (logged to the
+                // serial output now); `tick_serial` completes it 8 bits later.
+                // On the external clock (bit 0 clear) only an attached peer
+                // drives the clock; with no cable the transfer never ends.
                 self.io[0x02] = value;
+                self.serial_cycles_left = 0;
                 if value & 0x80 != 0 {
-                    let sb = self.io[0x01];
-                    self.serial_output.push(sb);
-                    self.io[0x02] &= 0x7F;
+                    self.serial_out = self.io[0x01];
+                    self.serial_output.push(self.serial_out);
+                    if value & 0x01 != 0 || self.serial_link.is_some() {
+                        // 8192 Hz, or 262144 Hz with the CGB fast clock (bit 1)
+                        let fast = self.cgb.mode && value & 0x02 != 0;
+                        self.serial_cycles_left = 8 * if fast { 16 } else { 512 };
+                    }
                 }
             }
             0x04 => self.io[0x04] = 0, // DIV: any write resets to 0
-            0x44 => {}                 // LY: read-only
+            0x10..=0x3F => self.apu.write(addr, value),
+            0x44 => {} // LY: read-only
             0x46 => self.dma_transfer(value),
+            // BANK: any nonzero write unmaps the boot ROM for good; without
+            // one it is a harmless store (the register already reads 0xFF)
+            0x50 => {
+                if value != 0 {
+                    self.boot_rom_mapped = false;
+                }
+            }
 
             // GBC-only registers — silently ignored in DMG mode
             0x4D => {
@@ -402,10 +662,9 @@ impl Memory {
             }
             0x55 => {
                 if self.cgb.mode {
-                    let source =
-                        ((self.io[0x51] as u16) << 8 | self.io[0x52] as u16) & 0xFFF0;
-                    let dest = 0x8000u16
-                        | (((self.io[0x53] as u16) << 8 | self.io[0x54] as u16) & 0x1FF0);
+                    let source = ((self.io[0x51] as u16) << 8 | self.io[0x52] as u16) & 0xFFF0;
+                    let dest =
+                        0x8000u16 | (((self.io[0x53] as u16) << 8 | self.io[0x54] as u16) & 0x1FF0);
                     self.cgb.hdma_source = source;
                     self.cgb.hdma_dest = dest;
                     if value & 0x80 == 0 {
@@ -414,7 +673,7 @@ impl Memory {
                         for i in 0..total_bytes {
                             let src_byte = self.read(self.cgb.hdma_source + i);
                             let dest_vram = (self.cgb.hdma_dest & 0x1FFF) + i;
-                            self.vram[self.cgb.vram_bank][dest_vram as usize] = src_byte;
+                            self.write_vram_unchecked(self.cgb.vram_bank, dest_vram, src_byte);
                         }
                         self.cgb.hdma_active = false;
                         self.io[0x55] = 0xFF;
@@ -435,9 +694,9 @@ impl Memory {
             0x69 => {
                 if self.cgb.mode {
                     self.cgb.bg_palette_ram[(self.cgb.bcps & 0x3F) as usize] = value;
+                    self.cgb.bg_palette_written = true;
                     if self.cgb.bcps & 0x80 != 0 {
-                        self.cgb.bcps =
-                            (self.cgb.bcps & 0x80) | ((self.cgb.bcps + 1) & 0x3F);
+                        self.cgb.bcps = (self.cgb.bcps & 0x80) | ((self.cgb.bcps + 1) & 0x3F);
                     }
                 }
             }
@@ -449,9 +708,9 @@ impl Memory {
             0x6B => {
                 if self.cgb.mode {
                     self.cgb.obj_palette_ram[(self.cgb.ocps & 0x3F) as usize] = value;
+                    self.cgb.obj_palette_written = true;
                     if self.cgb.ocps & 0x80 != 0 {
-                        self.cgb.ocps =
-                            (self.cgb.ocps & 0x80) | ((self.cgb.ocps + 1) & 0x3F);
+                        self.cgb.ocps = (self.cgb.ocps & 0x80) | ((self.cgb.ocps + 1) & 0x3F);
                     }
                 }
             }
@@ -464,11 +723,88 @@ impl Memory {
         }
     }
 
+    /// Start an OAM DMA of 160 bytes from `value << 8`; `tick_oam_dma`
+    /// copies them over the next 160 M-cycles. A write mid-transfer
+    /// restarts it. The DMA unit sees 0xE000-0xFFFF as a WRAM mirror, so
+    /// sources 0xE0-0xFF (including 0xFE/0xFF, which would otherwise hit OAM
+    /// and I/O) read from 0xC000-0xDFFF.
     fn dma_transfer(&mut self, value: u8) {
-        let source = (value as u16) << 8;
-        for i in 0..0xA0 {
-            self.oam[i] = self.read(source + i as u16);
+        let page = if value >= 0xE0 { value - 0x20 } else { value };
+        let source = (page as u16) << 8;
+        self.oam_dma = Some(OamDma {
+            source,
+            copied: 0,
+            cycles: 0,
+        });
+    }
+
+    /// Advance a serial transfer by `cycles` CPU cycles. On completion the
+    /// byte latched at the start goes to the peer, the peer's byte (0xFF
+    /// with no cable: the line floats high) replaces SB and SC bit 7
+    /// clears; returns true so the caller can request the Serial interrupt.
+    pub fn tick_serial(&mut self, cycles: u32) -> bool {
+        if self.serial_cycles_left == 0 {
+            return false;
+        }
+        self.serial_cycles_left = self.serial_cycles_left.saturating_sub(cycles);
+        if self.serial_cycles_left > 0 {
+            return false;
+        }
+        self.io[0x01] = match &mut self.serial_link {
+            Some(link) => link.exchange(self.serial_out),
+            None => 0xFF,
+        };
+        self.io[0x02] &= 0x7F;
+        true
+    }
+
+    /// Run the APU for `cycles` T-cycles of the normal-speed clock, first
+    /// stepping its frame sequencer if DIV's sequencer bit just fell.
+    pub fn tick_apu(&mut self, cycles: u32, frame_sequencer: bool) {
+        if frame_sequencer {
+            self.apu.clock_frame_sequencer();
+        }
+        self.apu.tick(cycles);
+    }
+
+    /// Copy buffered stereo frames (interleaved L, R) into `out`; returns
+    /// the frames written. See `Apu::drain_into`.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: drain_audio
+    pub fn drain_audio(&mut self, out: &mut [f32]) -> usize {
+        self.apu.drain_into(out)
+    }
+
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: audio_sample_rate
+    pub fn audio_sample_rate(&self) -> u32 {
+        self.apu.sample_rate()
+    }
+
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: set_audio_sample_rate
+    pub fn set_audio_sample_rate(&mut self, rate: u32) {
+        self.apu.set_sample_rate(rate);
+    }
+
+    /// Advance an OAM DMA by `cycles` CPU T-cycles, copying one byte per
+    /// M-cycle through the normal `read` path.
+    pub fn tick_oam_dma(&mut self, cycles: u32) {
+        let Some(mut dma) = self.oam_dma else {
+            return;
+        };
+        dma.cycles += cycles;
+        while dma.cycles >= 4 && dma.copied < 0xA0 {
+            dma.cycles -= 4;
+            let byte = self.read(dma.source + dma.copied as u16);
+            self.write_oam_unchecked(dma.copied, byte);
+            dma.copied += 1;
         }
+        self.oam_dma = (dma.copied < 0xA0).then_some(dma);
+    }
+
+    /// True while an OAM DMA owns the bus: the CPU then reads 0xFF from
+    /// everything below 0xFF00 (see `MemoryBus::read`).
+    #[inline]
+    pub fn oam_dma_active(&self) -> bool {
+        self.oam_dma.is_some()
     }
 
     // ── I/O register accessors for other components ──────────────────────────
@@ -493,6 +829,27 @@ impl Memory {
         &self.oam
     }
 
+    /// Write OAM entry `index` (0-39) as raw hardware values: `y` and `x` are
+    /// screen position + 16 / + 8. Out-of-range indices are ignored.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: set_sprite
+    pub fn set_sprite(&mut self, index: u8, y: u8, x: u8, tile: u8, attr: u8) {
+        if index < 40 {
+            let o = index as usize * 4;
+            self.oam[o..o + 4].copy_from_slice(&[y, x, tile, attr]);
+        }
+    }
+
+    /// Read OAM entry `index` as `[y, x, tile, attr]` (zeros if out of range).
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: get_sprite
+    pub fn get_sprite(&self, index: u8) -> [u8; 4] {
+        if index < 40 {
+            let o = index as usize * 4;
+            self.oam[o..o + 4].try_into().unwrap()
+        } else {
+            [0; 4]
+        }
+    }
+
     pub fn get_cartridge_ram(&self) -> &[u8] {
         self.cartridge.ram_data()
     }
@@ -501,6 +858,37 @@ impl Memory {
         self.cartridge.load_ram(data);
     }
 
+    /// Cartridge RAM plus the MBC3 RTC footer, if any (`.sav` layout).
+    pub fn export_cartridge_ram_with_rtc(&self) -> Vec<u8> {
+        self.cartridge.export_ram_with_rtc()
+    }
+
+    /// Load a `.sav` that may carry an MBC3 RTC footer after the RAM.
+    pub fn import_cartridge_ram_with_rtc(&mut self, data: &[u8]) {
+        self.cartridge.import_ram_with_rtc(data);
+    }
+
+    /// Whether the cartridge's rumble motor is on, for haptics.
+    #[cfg_attr(not(any(feature = "ios", feature = "wasm")), allow(dead_code))]
+    pub fn is_rumbling(&self) -> bool {
+        self.cartridge.is_rumbling()
+    }
+
+    /// The MBC3 clock alone as a 48-byte BGB/VBA RTC block, for frontends
+    /// that store it apart from RAM. Empty without an RTC.
+    #[cfg_attr(not(any(feature = "ios", feature = "wasm")), allow(dead_code))]
+    pub fn get_rtc_data(&self) -> Vec<u8> {
+        self.cartridge.rtc_save()
+    }
+
+    /// Restore a `get_rtc_data` block (or a 44/48-byte footer from another
+    /// emulator). The clock catches up on the time since its timestamp at
+    /// the next `tick_rtc`. Returns false without an RTC or on short data.
+    #[cfg_attr(not(any(feature = "ios", feature = "wasm")), allow(dead_code))]
+    pub fn load_rtc_data(&mut self, data: &[u8]) -> bool {
+        self.cartridge.rtc_load(data)
+    }
+
     /// Read a camera hardware register directly (index 0x00-0x7F).
     #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: camera_reg
     pub fn camera_reg(&self, index: u8) -> u8 {
@@ -518,12 +906,62 @@ impl Memory {
         }
     }
 
+    /// Flip/rotate future camera frames (see `Camera::set_orientation`).
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: set_camera_orientation
+    pub fn set_camera_orientation(&mut self, flip_h: bool, flip_v: bool, rotate_180: bool) {
+        if let Some(cam) = self.cartridge.as_camera_mut() {
+            cam.set_orientation(flip_h, flip_v, rotate_180);
+        }
+    }
+
+    /// Run the camera preview at reduced resolution (see `Camera::set_preview_downscale`).
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: set_camera_preview_downscale
+    pub fn set_camera_preview_downscale(&mut self, factor: u8) {
+        if let Some(cam) = self.cartridge.as_camera_mut() {
+            cam.set_preview_downscale(factor);
+        }
+    }
+
+    /// 128×112 RGBA preview of the next capture; empty without a camera.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: camera_preview
+    pub fn camera_preview_rgba(&self) -> Vec<u8> {
+        self.cartridge
+            .as_camera()
+            .map(|cam| cam.capture_to_rgba())
+            .unwrap_or_default()
+    }
+
+    /// Set the camera's brightness/contrast post adjustment (see `Camera::set_post_adjust`).
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: set_camera_post_adjust
+    pub fn set_camera_post_adjust(&mut self, brightness: i16, contrast: f32) {
+        if let Some(cam) = self.cartridge.as_camera_mut() {
+            cam.set_post_adjust(brightness, contrast);
+        }
+    }
+
     /// Get serial output as a string (for test ROM debugging).
     #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: get_serial_output
     pub fn get_serial_output_string(&self) -> String {
         String::from_utf8_lossy(&self.serial_output).to_string()
     }
 
+    /// Raw serial output bytes (everything shifted out since the last clear).
+    pub fn serial_output(&self) -> &[u8] {
+        &self.serial_output
+    }
+
+    /// Attach (or detach with `None`) the peer on the other end of the link cable.
+    #[cfg_attr(not(any(feature = "ios", feature = "wasm")), allow(dead_code))]
+    pub fn set_serial_link(&mut self, link: Option<Box<dyn SerialLink>>) {
+        self.serial_link = link;
+    }
+
+    /// Borrow the attached link peer (e.g. to read its transcript).
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: serial_transcript
+    pub fn serial_link(&self) -> Option<&dyn SerialLink> {
+        self.serial_link.as_deref()
+    }
+
     /// Clear the serial output buffer.
     #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: clear_serial_output
     pub fn clear_serial_output(&mut self) {
@@ -535,17 +973,40 @@ impl Memory {
         self.cartridge.tick_rtc();
     }
 
+    /// Freeze/resume the cartridge RTC (no-op for non-MBC3).
+    pub fn set_rtc_paused(&mut self, paused: bool) {
+        self.cartridge.set_rtc_paused(paused);
+    }
+
+    /// Advance the cartridge RTC one second per `fps` calls to `tick_rtc`
+    /// (i.e. frames) for reproducible runs, or follow the wall clock again
+    /// (`None`). Applies to the current and subsequently loaded cartridges.
+    pub fn set_rtc_frames_per_second(&mut self, fps: Option<u32>) {
+        self.rtc_frames_per_second = fps;
+        self.cartridge.set_rtc_frames_per_second(fps);
+    }
+
     /// Get the detected MBC type.
     pub fn get_mbc_type(&self) -> MbcType {
         self.cartridge.mbc_type()
     }
 
+    /// Whether the cartridge has a battery keeping its RAM across power-off.
+    pub fn has_battery(&self) -> bool {
+        self.cartridge.is_battery_backed()
+    }
+
     /// Get the number of ROM banks.
     #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: load_rom
     pub fn get_rom_bank_count(&self) -> usize {
         self.cartridge.rom_bank_count()
     }
 
+    /// SHA-1 of the loaded ROM image.
+    pub fn rom_sha1(&self) -> [u8; 20] {
+        self.cartridge.rom_sha1()
+    }
+
     /// Get current memory state for debugging.
     #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: log_frame_debug
     pub fn get_debug_state(&self) -> MemoryDebugState {
@@ -578,6 +1039,32 @@ impl Memory {
         self.io[0x40] & 0x80 != 0
     }
 
+    /// Feed all RAM regions, I/O, GBC state and cartridge banking into `h`
+    /// (see `GameBoyCore::state_checksum`). The serial log is excluded.
+    pub fn hash_state<H: Hasher>(&self, h: &mut H) {
+        self.vram.hash(h);
+        self.wram.hash(h);
+        self.oam.hash(h);
+        self.io.hash(h);
+        self.hram.hash(h);
+        self.ie.hash(h);
+        self.cgb.hash_state(h);
+        self.apu.hash_state(h);
+        self.oam_dma
+            .map(|dma| (dma.source, dma.copied, dma.cycles))
+            .hash(h);
+        self.serial_cycles_left.hash(h);
+        self.serial_out.hash(h);
+
+        self.cartridge.ram_data().hash(h);
+        self.cartridge.current_rom_bank().hash(h);
+        self.cartridge.current_ram_bank().hash(h);
+        self.cartridge.is_ram_enabled().hash(h);
+        if let Some(cam) = self.cartridge.as_camera() {
+            cam.regs.hash(h);
+        }
+    }
+
     // ── GBC accessors ────────────────────────────────────────────────────────
 
     /// Check if GBC mode is active for this ROM session.
@@ -599,7 +1086,10 @@ impl Memory {
     /// Read a byte directly from a specific VRAM bank (PPU bank-independent access).
     pub(crate) fn read_vram_bank(&self, bank: usize, addr: u16) -> u8 {
         if (0x8000..0xA000).contains(&addr) {
-            self.vram[bank & 1][(addr - 0x8000) as usize]
+            // DMG has no bank 1; CGB-mode rendering of DMG memory sees zeroes
+            self.vram
+                .get(bank & 1)
+                .map_or(0, |b| b[(addr - 0x8000) as usize])
         } else {
             0xFF
         }
@@ -611,6 +1101,18 @@ impl Memory {
         self.cgb.read_bg_palette(palette, color)
     }
 
+    /// True once the ROM has written BG colour palette data (BCPD).
+    #[inline]
+    pub(crate) fn bg_palette_written(&self) -> bool {
+        self.cgb.bg_palette_written
+    }
+
+    /// True once the ROM has written OBJ colour palette data (OCPD).
+    #[inline]
+    pub(crate) fn obj_palette_written(&self) -> bool {
+        self.cgb.obj_palette_written
+    }
+
     /// Read two bytes from the OBJ colour palette RAM (lo, hi) for palette + colour index.
     #[inline]
     pub(crate) fn read_obj_palette(&self, palette: usize, color: usize) -> (u8, u8) {
@@ -625,7 +1127,7 @@ impl Memory {
         for i in 0..16u16 {
             let byte = self.read(self.cgb.hdma_source + i);
             let dest_vram = (self.cgb.hdma_dest & 0x1FFF) + i;
-            self.vram[self.cgb.vram_bank][dest_vram as usize] = byte;
+            self.write_vram_unchecked(self.cgb.vram_bank, dest_vram, byte);
         }
         self.cgb.hdma_source += 16;
         self.cgb.hdma_dest += 16;
@@ -660,6 +1162,11 @@ impl Memory {
             .unwrap_or(false)
     }
 
+    /// Captures the camera ROM has completed since load (0 for other carts).
+    pub fn camera_capture_count(&self) -> u32 {
+        self.cartridge.as_camera().map(|c| c.captures).unwrap_or(0)
+    }
+
     pub fn clear_camera_capture_dirty(&mut self) {
         if let Some(cam) = self.cartridge.as_camera_mut() {
             cam.clear_capture_dirty();
@@ -674,6 +1181,13 @@ impl Memory {
             .unwrap_or(&EMPTY)
     }
 
+    /// Active capture as raw 2bpp tiles (16×14 tiles, 3584 bytes; empty for
+    /// non-camera carts). Decode with `graphics::decode_2bpp_tiles_to_rgba`.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: get_capture_tiles
+    pub fn camera_capture_tiles(&self) -> &[u8] {
+        self.camera_capture_sram()
+    }
+
     pub fn decode_camera_photo(&self, slot: u8) -> Vec<u8> {
         self.cartridge
             .as_camera()
@@ -688,12 +1202,40 @@ impl Memory {
             .unwrap_or(false)
     }
 
+    pub fn try_decode_camera_photo(&self, slot: u8) -> Result<Vec<u8>, PhotoError> {
+        self.cartridge
+            .as_camera()
+            .map(|c| c.try_decode_photo(slot))
+            .unwrap_or(Err(PhotoError::NoCamera))
+    }
+
+    pub fn try_encode_camera_photo(&mut self, slot: u8, rgba: &[u8]) -> Result<(), PhotoError> {
+        self.cartridge
+            .as_camera_mut()
+            .map(|c| c.try_encode_photo(slot, rgba))
+            .unwrap_or(Err(PhotoError::NoCamera))
+    }
+
     pub fn clear_camera_photo_slot(&mut self, slot: u8) {
         if let Some(cam) = self.cartridge.as_camera_mut() {
             cam.clear_photo_slot(slot);
         }
     }
 
+    pub fn clear_camera_roll(&mut self) {
+        if let Some(cam) = self.cartridge.as_camera_mut() {
+            cam.clear_all_slots();
+        }
+    }
+
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: move_camera_photo
+    pub fn move_camera_photo(&mut self, from: u8, to: u8) -> bool {
+        self.cartridge
+            .as_camera_mut()
+            .map(|c| c.move_photo(from, to))
+            .unwrap_or(false)
+    }
+
     pub fn camera_contrast(&self) -> i32 {
         self.cartridge
             .as_camera()
@@ -701,6 +1243,23 @@ impl Memory {
             .unwrap_or(-1)
     }
 
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: camera_dither_preview
+    pub fn camera_dither_preview(&self) -> Vec<u8> {
+        self.cartridge
+            .as_camera()
+            .map(|c| c.dither_preview())
+            .unwrap_or_default()
+    }
+
+    /// Focus metric of the current camera image (see `Camera::sharpness_metric`).
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: camera_sharpness
+    pub fn camera_sharpness(&self) -> f32 {
+        self.cartridge
+            .as_camera()
+            .map(|c| c.sharpness_metric())
+            .unwrap_or(0.0)
+    }
+
     #[cfg_attr(not(any(feature = "ios", feature = "wasm")), allow(dead_code))]
     pub fn camera_photo_count(&self) -> u8 {
         self.cartridge
@@ -709,6 +1268,14 @@ impl Memory {
             .unwrap_or(0)
     }
 
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: camera_occupancy
+    pub fn camera_occupancy(&self) -> u32 {
+        self.cartridge
+            .as_camera()
+            .map(|c| c.occupancy())
+            .unwrap_or(0)
+    }
+
     // ── MBC7 accelerometer accessor ──────────────────────────────────────────
 
     /// Feed accelerometer data to an MBC7 cartridge (Kirby's Tilt 'n' Tumble).
@@ -738,6 +1305,150 @@ mod tests {
         rom
     }
 
+    #[test]
+    fn test_unchecked_writes_bypass_mode_blocking() {
+        let mut mem = Memory::new();
+        mem.load_rom(&make_rom(0x00, 0x00), false).unwrap();
+        mem.set_access_blocking(true);
+        mem.write(0xFF40, 0x91);
+        mem.write_io_direct(io::STAT, 0x03); // mode 3: drawing
+
+        mem.write(0x8000, 0x12);
+        mem.write(0xFE00, 0x34);
+        assert_eq!(
+            mem.read_vram_bank(0, 0x8000),
+            0x00,
+            "CPU VRAM write blocked"
+        );
+        assert_eq!(mem.get_oam()[0], 0x00, "CPU OAM write blocked");
+
+        mem.write_vram_unchecked(0, 0x8000, 0x56);
+        mem.write_oam_unchecked(0, 0x78);
+        assert_eq!(mem.read_vram_bank(0, 0x8000), 0x56);
+        assert_eq!(mem.get_oam()[0], 0x78);
+        mem.poke(0x8001, 0x9A);
+        mem.poke(0xFE01, 0xBC);
+        assert_eq!(mem.read_vram_bank(0, 0x8001), 0x9A);
+        assert_eq!(mem.get_oam()[1], 0xBC);
+
+        // HBlank: the CPU owns both again
+        mem.write_io_direct(io::STAT, 0x00);
+        mem.write(0x8000, 0x12);
+        mem.write(0xFE00, 0x34);
+        assert_eq!(mem.read_vram_bank(0, 0x8000), 0x12);
+        assert_eq!(mem.get_oam()[0], 0x34);
+    }
+
+    #[test]
+    fn test_cpu_reads_locked_vram_and_oam_as_ff() {
+        use crate::bus::MemoryBus;
+        use crate::joypad::Joypad;
+        use crate::timer::Timer;
+
+        let mut mem = Memory::new();
+        mem.load_rom(&make_rom(0x00, 0x00), false).unwrap();
+        mem.write(0xFF40, 0x91);
+        mem.write(0x8000, 0x12);
+        mem.write(0xFE00, 0x34);
+        mem.set_access_blocking(true);
+        let (mut timer, mut joypad) = (Timer::new(), Joypad::new());
+
+        // (mode, VRAM, OAM) as the CPU sees them
+        let expected = [
+            (0, 0x12, 0x34),
+            (1, 0x12, 0x34),
+            (2, 0x12, 0xFF),
+            (3, 0xFF, 0xFF),
+        ];
+        for (mode, vram, oam) in expected {
+            mem.write_io_direct(io::STAT, mode);
+            let bus = MemoryBus::new(&mut mem, &mut timer, &mut joypad);
+            assert_eq!(bus.read(0x8000), vram, "VRAM in mode {}", mode);
+            assert_eq!(bus.read(0xFE00), oam, "OAM in mode {}", mode);
+        }
+
+        // The PPU and debuggers still see the real bytes
+        assert_eq!(mem.read(0x8000), 0x12);
+        assert_eq!(mem.read(0xFE00), 0x34);
+
+        mem.set_access_blocking(false);
+        let bus = MemoryBus::new(&mut mem, &mut timer, &mut joypad);
+        assert_eq!(bus.read(0x8000), 0x12);
+    }
+
+    #[test]
+    fn test_ram_size_override() {
+        let mut mem = Memory::new();
+        mem.set_ram_size_override(Some(32 * 1024));
+        mem.load_rom(&make_rom(0x1B, 0x02), false).unwrap(); // header: 8KB
+        assert_eq!(mem.get_cartridge_ram().len(), 32 * 1024);
+
+        mem.write(0x0000, 0x0A);
+        for bank in 0..4 {
+            mem.write(0x4000, bank);
+            mem.write(0xA000, 0x10 + bank);
+        }
+        for bank in 0..4 {
+            mem.write(0x4000, bank);
+            assert_eq!(mem.read(0xA000), 0x10 + bank, "bank {}", bank);
+        }
+
+        // The camera keeps its fixed 128KB
+        mem.load_rom(&make_rom(0xFC, 0x00), false).unwrap();
+        assert_eq!(mem.get_cartridge_ram().len(), 128 * 1024);
+
+        mem.set_ram_size_override(None);
+        mem.load_rom(&make_rom(0x1B, 0x02), false).unwrap();
+        assert_eq!(mem.get_cartridge_ram().len(), 8 * 1024);
+    }
+
+    #[test]
+    fn test_battery_backed_from_cart_type() {
+        let mut mem = Memory::new();
+        mem.load_rom(&make_rom(0x01, 0x00), false).unwrap();
+        assert!(!mem.has_battery(), "MBC1 without battery");
+        mem.load_rom(&make_rom(0x03, 0x02), false).unwrap();
+        assert!(mem.has_battery(), "MBC1+RAM+BATTERY");
+        mem.load_rom(&make_rom(0x11, 0x00), false).unwrap();
+        assert!(!mem.has_battery(), "plain MBC3");
+        mem.load_rom(&make_rom(0xFC, 0x04), false).unwrap();
+        assert!(mem.has_battery(), "Pocket Camera");
+    }
+
+    #[test]
+    fn test_has_battery_for_every_cart_type() {
+        const BATTERY_TYPES: [u8; 11] = [
+            0x03, 0x06, 0x09, 0x0D, 0x0F, 0x10, 0x13, 0x1B, 0x1E, 0x22, 0xFC,
+        ];
+        let mut mem = Memory::new();
+        for cart_type in 0x00..=0xFFu8 {
+            mem.load_rom(&make_rom(cart_type, 0x02), false).unwrap();
+            assert_eq!(
+                mem.has_battery(),
+                BATTERY_TYPES.contains(&cart_type),
+                "cart type {cart_type:#04X}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_oam_dma_from_echo_and_high_pages() {
+        let mut mem = Memory::new();
+        mem.load_rom(&make_rom(0x00, 0x00), false).unwrap();
+        for i in 0..0xA0u16 {
+            mem.write(0xC000 + i, i as u8);
+            mem.write(0xDE00 + i, 0xA0 - i as u8);
+        }
+
+        mem.write(0xFF46, 0xE0); // echo of 0xC000
+        mem.tick_oam_dma(160 * 4);
+        assert!((0..0xA0).all(|i| mem.get_oam()[i] == i as u8));
+
+        mem.write(0xFF46, 0xFE); // would be OAM itself; mirrors 0xDE00
+        mem.tick_oam_dma(160 * 4);
+        assert!((0..0xA0).all(|i| mem.get_oam()[i] == 0xA0 - i as u8));
+    }
+
     #[test]
     fn test_wram_read_write() {
         let mut mem = Memory::new();
@@ -796,11 +1507,88 @@ mod tests {
         assert_eq!(mem.read(0xFE9F), 0x20);
     }
 
+    #[test]
+    fn test_ff50_unmaps_boot_rom() {
+        let mut rom = make_rom(0x00, 0x00);
+        rom[0x0000] = 0xAB;
+        rom[0x0100] = 0xCD;
+        let mut mem = Memory::new();
+
+        // No boot ROM: 0xFF50 is a benign store and reads back as unmapped
+        mem.load_rom(&rom, false).unwrap();
+        mem.write(0xFF50, 0x01);
+        assert_eq!(mem.read(0xFF50), 0xFF);
+        assert_eq!(mem.read(0x0000), 0xAB);
+
+        assert!(mem.load_boot_rom(&[0x31; 0x80]).is_err());
+        mem.load_boot_rom(&[0x31; 0x100]).unwrap();
+        assert_eq!(mem.read(0x0000), 0xAB, "not mapped until the next load_rom");
+        mem.load_rom(&rom, false).unwrap();
+        assert_eq!(mem.read(0x0000), 0x31);
+        assert_eq!(mem.read(0x0100), 0xCD, "header is never shadowed");
+        assert_eq!(mem.read(0xFF50), 0xFE);
+
+        mem.write(0xFF50, 0x00); // zero does not unmap
+        assert_eq!(mem.read(0x0000), 0x31);
+        mem.write(0xFF50, 0x01);
+        assert_eq!(mem.read(0x0000), 0xAB);
+        assert_eq!(mem.read(0xFF50), 0xFF);
+        mem.write(0xFF50, 0x00); // and it stays unmapped
+        assert_eq!(mem.read(0x0000), 0xAB);
+    }
+
+    #[test]
+    fn test_serial_sends_sb_latched_at_transfer_start() {
+        use crate::serial::RecordingSerialLink;
+
+        let mut mem = Memory::new();
+        mem.load_rom(&make_rom(0x00, 0x00), false).unwrap();
+        mem.set_serial_link(Some(Box::new(RecordingSerialLink::disconnected())));
+        mem.write(0xFF01, 0x42);
+        mem.write(0xFF02, 0x81);
+        mem.tick_serial(4 * 512);
+        mem.write(0xFF01, 0x99); // mid-transfer write
+        assert!(mem.tick_serial(4 * 512));
+
+        assert_eq!(mem.serial_output(), &[0x42]);
+        assert_eq!(mem.serial_link().unwrap().transcript(), &[(0x42, 0xFF)]);
+        assert_eq!(mem.read(0xFF01), 0xFF);
+    }
+
     #[test]
     fn test_unusable_region() {
-        let mem = Memory::new();
+        let mut mem = Memory::new();
         assert_eq!(mem.read(0xFEA0), 0xFF);
         assert_eq!(mem.read(0xFEFF), 0xFF);
+
+        // Writes are ignored
+        mem.write(0xFEB0, 0x12);
+        assert_eq!(mem.read(0xFEB0), 0xFF);
+
+        // DMG reads 0xFF in every PPU mode
+        for mode in 0..4 {
+            mem.write_io_direct(io::STAT, 0x80 | mode);
+            assert_eq!(mem.read(0xFEC3), 0xFF, "DMG mode {}", mode);
+        }
+    }
+
+    #[test]
+    fn test_unusable_region_cgb() {
+        let mut mem = Memory::new();
+        mem.load_rom(&make_rom(0x00, 0x00), true).unwrap();
+        mem.write_io_direct(io::LCDC, 0x91);
+
+        // HBlank: high nibble of the low address byte, repeated
+        mem.write_io_direct(io::STAT, 0x80);
+        assert_eq!(mem.read(0xFEA0), 0xAA);
+        assert_eq!(mem.read(0xFEB4), 0xBB);
+        assert_eq!(mem.read(0xFEFF), 0xFF);
+
+        // OAM scan / drawing: blocked like OAM itself
+        mem.write_io_direct(io::STAT, 0x82);
+        assert_eq!(mem.read(0xFEB4), 0xFF);
+        mem.write_io_direct(io::STAT, 0x83);
+        assert_eq!(mem.read(0xFEB4), 0xFF);
     }
 
     #[test]
@@ -881,6 +1669,12 @@ mod tests {
             mem.write(0xC000 + i as u16, i as u8);
         }
         mem.write(0xFF46, 0xC0);
+        mem.tick_oam_dma(80 * 4 + 3);
+        assert!(mem.oam_dma_active());
+        assert_eq!(mem.get_oam()[79], 79);
+        assert_eq!(mem.get_oam()[80], 0, "one byte per M-cycle");
+        mem.tick_oam_dma(80 * 4);
+        assert!(!mem.oam_dma_active());
         for i in 0..0xA0 {
             assert_eq!(mem.read(0xFE00 + i as u16), i as u8);
         }
@@ -971,6 +1765,30 @@ mod tests {
         assert_eq!(mem.read(0xC100), 0x11);
     }
 
+    #[test]
+    fn test_bank_arrays_sized_by_mode() {
+        let mut mem = Memory::new();
+        mem.load_rom(&vec![0u8; 0x8000], false).unwrap();
+        assert_eq!((mem.vram.len(), mem.wram.len()), (1, 2));
+
+        // DMG: SVBK/VBK are ignored, 0xD000 is the one switchable bank
+        mem.write(0xD000, 0x44);
+        mem.write(0xFF70, 0x05);
+        mem.write(0xFF4F, 0x01);
+        assert_eq!(mem.read(0xD000), 0x44);
+        assert_eq!(mem.read(0xF000), 0x44);
+        assert_eq!(mem.read_vram_bank(1, 0x8000), 0x00);
+
+        mem.load_rom(&vec![0u8; 0x8000], true).unwrap();
+        assert_eq!((mem.vram.len(), mem.wram.len()), (2, 8));
+        mem.write(0xFF70, 0x07);
+        mem.write(0xD000, 0x77);
+        mem.write(0xFF70, 0x00); // bank 0 selects bank 1
+        assert_eq!(mem.read(0xD000), 0x00);
+        mem.write(0xFF70, 0x07);
+        assert_eq!(mem.read(0xF000), 0x77);
+    }
+
     #[test]
     fn test_cgb_bg_palette_write_read() {
         let mut mem = Memory::new();
@@ -1053,4 +1871,339 @@ mod tests {
         assert_eq!(mem.read(0xFF68), 0xFF); // BCPS
         assert_eq!(mem.read(0xFF70), 0xFF); // SVBK
     }
+
+    #[test]
+    fn test_ram_enable_override_peek() {
+        let mut mem = Memory::new();
+        mem.load_rom(&make_rom(0x1B, 0x03), false).unwrap(); // MBC5+RAM+BATTERY
+        mem.write(0x0000, 0x0A);
+        mem.write(0xA123, 0x5A);
+        mem.write(0x0000, 0x00); // game disables RAM
+
+        assert_eq!(mem.read(0xA123), 0xFF);
+        assert_eq!(mem.peek(0xA123), 0xFF, "no override follows the cart gate");
+
+        mem.set_ram_enable_override(Some(true));
+        assert_eq!(mem.peek(0xA123), 0x5A);
+        assert_eq!(mem.read(0xA123), 0xFF, "normal reads still see the gate");
+        assert!(
+            !mem.get_debug_state().ram_enabled,
+            "cart register untouched"
+        );
+
+        mem.set_ram_enable_override(Some(false));
+        mem.write(0x0000, 0x0A);
+        assert_eq!(mem.peek(0xA123), 0xFF);
+        assert_eq!(mem.read(0xA123), 0x5A);
+    }
+
+    /// ROM whose switchable-bank bytes encode (bank, offset) so any
+    /// mis-mapped read is detectable.
+    fn make_banked_rom(cart_type: u8, banks: usize) -> Vec<u8> {
+        let mut rom: Vec<u8> = (0..banks * 0x4000)
+            .map(|i| ((i / 0x4000) as u8).wrapping_mul(31) ^ (i as u8))
+            .collect();
+        rom[0x0147] = cart_type;
+        rom[0x0149] = 0x00;
+        rom
+    }
+
+    #[test]
+    fn test_mbc3_rtc_reads_latched_until_relatched() {
+        let mut mem = Memory::new();
+        mem.set_rtc_frames_per_second(Some(1)); // one second per tick_rtc
+        mem.load_rom(&make_rom(0x10, 0x03), false).unwrap();
+        let latch = |mem: &mut Memory| {
+            mem.write(0x6000, 0x00);
+            mem.write(0x6000, 0x01);
+        };
+
+        // Gated like RAM
+        mem.write(0x4000, 0x08);
+        mem.write(0xA000, 30);
+        latch(&mut mem);
+        assert_eq!(mem.read(0xA000), 0xFF);
+
+        mem.write(0x0000, 0x0A);
+        for (reg, value) in [
+            (0x08, 30),
+            (0x09, 59),
+            (0x0A, 23),
+            (0x0B, 0xFF),
+            (0x0C, 0x00),
+        ] {
+            mem.write(0x4000, reg);
+            mem.write(0xA000, value);
+        }
+        latch(&mut mem);
+        for _ in 0..45 {
+            mem.tick_rtc();
+        }
+
+        // Live is now day 256 00:00:15, but reads keep the latched values
+        let read_all = |mem: &mut Memory| {
+            [0x08, 0x09, 0x0A, 0x0B, 0x0C].map(|reg| {
+                mem.write(0x4000, reg);
+                mem.read(0xA000)
+            })
+        };
+        assert_eq!(read_all(&mut mem), [30, 59, 23, 0xFF, 0x00]);
+        latch(&mut mem);
+        assert_eq!(read_all(&mut mem), [15, 0, 0, 0x00, 0x01]);
+    }
+
+    #[test]
+    fn test_rtc_data_round_trip() {
+        let mut mem = Memory::new();
+        mem.load_rom(&make_rom(0x10, 0x03), false).unwrap();
+        assert!(
+            Memory::new().get_rtc_data().is_empty(),
+            "no RTC without MBC3"
+        );
+        mem.write(0x0000, 0x0A);
+        mem.write(0x4000, 0x0A);
+        mem.write(0xA000, 13); // hours
+        mem.write(0x6000, 0x00);
+        mem.write(0x6000, 0x01);
+
+        let data = mem.get_rtc_data();
+        assert_eq!(data.len(), 48);
+        assert_eq!(data[8], 13, "live hours");
+        assert_eq!(data[28], 13, "latched hours");
+
+        let mut other = Memory::new();
+        other.load_rom(&make_rom(0x10, 0x03), false).unwrap();
+        assert!(other.load_rtc_data(&data));
+        assert_eq!(other.get_rtc_data(), data);
+        assert!(!other.load_rtc_data(&data[..40]));
+    }
+
+    #[test]
+    fn test_rtc_data_from_the_past_catches_up() {
+        let mut mem = Memory::new();
+        mem.load_rom(&make_rom(0x10, 0x03), false).unwrap();
+
+        // Saved at 00:00:00 one day, one hour, one minute and 10 seconds ago
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let mut data = vec![0u8; 48];
+        data[40..48].copy_from_slice(&(now - 90_070).to_le_bytes());
+        assert!(mem.load_rtc_data(&data));
+        mem.tick_rtc();
+
+        mem.write(0x0000, 0x0A);
+        mem.write(0x6000, 0x00);
+        mem.write(0x6000, 0x01);
+        let [s, m, h, dl] = [0x08, 0x09, 0x0A, 0x0B].map(|reg| {
+            mem.write(0x4000, reg);
+            mem.read(0xA000)
+        });
+        assert!((10..=12).contains(&s), "seconds {}", s);
+        assert_eq!((m, h, dl), (1, 1, 1));
+    }
+
+    /// One 93LC56 transaction on the MBC7 EEPROM port: CS high, clock out
+    /// `bits` MSB first, then `read_bits` more clocks sampling DO, CS low.
+    fn mbc7_eeprom_command(mem: &mut Memory, bits: u32, len: u8, read_bits: u8) -> u32 {
+        mem.write(0xA080, 0x80);
+        let clock = |mem: &mut Memory, di: bool| {
+            let di = if di { 0x02 } else { 0x00 };
+            mem.write(0xA080, 0x80 | di);
+            mem.write(0xA080, 0xC0 | di);
+            mem.read(0xA080) as u32 & 1
+        };
+        for i in (0..len).rev() {
+            clock(mem, bits >> i & 1 != 0);
+        }
+        let mut out = 0;
+        for _ in 0..read_bits {
+            out = out << 1 | clock(mem, false);
+        }
+        mem.write(0xA080, 0x00);
+        out
+    }
+
+    #[test]
+    fn test_mbc7_eeprom_survives_save_round_trip() {
+        let open = |mem: &mut Memory| {
+            mem.load_rom(&make_rom(0x22, 0x00), false).unwrap();
+            mem.write(0x0000, 0x0A);
+            mem.write(0x4000, 0x40);
+        };
+        // Start bit, 2-bit opcode, 7-bit address
+        const EWEN: u32 = 0b100 << 7 | 0x60;
+        const WRITE: u32 = 0b101 << 7;
+        const READ: u32 = 0b110 << 7;
+
+        let mut mem = Memory::new();
+        open(&mut mem);
+        mbc7_eeprom_command(&mut mem, EWEN, 10, 0);
+        mbc7_eeprom_command(&mut mem, (WRITE | 5) << 16 | 0xBEEF, 26, 0);
+
+        let save = mem.get_cartridge_ram().to_vec();
+        assert_eq!(save.len(), 256);
+        assert_eq!(save[10..12], [0xEF, 0xBE], "word 5, little-endian");
+
+        let mut fresh = Memory::new();
+        open(&mut fresh);
+        assert_eq!(mbc7_eeprom_command(&mut fresh, READ | 5, 10, 16), 0xFFFF);
+        fresh.load_cartridge_ram(&save);
+        // READ 5: the dummy 0 bit is shifted out on the address clock
+        assert_eq!(mbc7_eeprom_command(&mut fresh, READ | 5, 10, 16), 0xBEEF);
+        assert_eq!(fresh.get_cartridge_ram(), &save[..]);
+    }
+
+    #[test]
+    fn test_mbc5_rumble_bit_leaves_ram_bank() {
+        let mut mem = Memory::new();
+        mem.load_rom(&make_rom(0x1E, 0x04), false).unwrap(); // 128KB RAM
+        mem.write(0x0000, 0x0A);
+        mem.write(0x4000, 0x03);
+        mem.write(0xA000, 0x33);
+
+        mem.write(0x4000, 0x0B); // bank 3, motor on
+        assert!(mem.is_rumbling());
+        assert_eq!(mem.cartridge.current_ram_bank(), 3);
+        assert_eq!(mem.read(0xA000), 0x33);
+        mem.write(0x4000, 0x03);
+        assert!(!mem.is_rumbling());
+        assert_eq!(mem.read(0xA000), 0x33);
+
+        // Without a motor bit 3 is a RAM bank bit
+        let mut mem = Memory::new();
+        mem.load_rom(&make_rom(0x1B, 0x04), false).unwrap();
+        mem.write(0x4000, 0x0B);
+        assert!(!mem.is_rumbling());
+        assert_eq!(mem.cartridge.current_ram_bank(), 0x0B);
+    }
+
+    #[test]
+    fn test_mbc2_register_select_by_address_bit_8() {
+        let rom = make_banked_rom(0x06, 16);
+        let mut mem = Memory::new();
+        mem.load_rom(&rom, false).unwrap();
+        assert_eq!(mem.get_mbc_type(), MbcType::Mbc2);
+
+        // Bit 8 set: ROM bank (low 4 bits, 0 → 1), anywhere in 0x0000-0x3FFF
+        mem.write(0x2100, 0x05);
+        assert_eq!(mem.read(0x4000), rom[5 * 0x4000]);
+        mem.write(0x0100, 0xF3);
+        assert_eq!(mem.read(0x4000), rom[3 * 0x4000]);
+        mem.write(0x3FFF, 0x00);
+        assert_eq!(mem.read(0x4000), rom[0x4000]);
+        assert!(
+            !mem.cartridge.is_ram_enabled(),
+            "bank writes leave RAM alone"
+        );
+
+        // Bit 8 clear: RAM enable, including in 0x2000-0x3FFF
+        mem.write(0x2000, 0x0A);
+        assert!(mem.cartridge.is_ram_enabled());
+        assert_eq!(
+            mem.read(0x4000),
+            rom[0x4000],
+            "RAM enable leaves the bank alone"
+        );
+        mem.write(0x00FF, 0x00);
+        assert!(!mem.cartridge.is_ram_enabled());
+    }
+
+    #[test]
+    fn test_mbc2_ram_is_4_bit_and_mirrored() {
+        let mut mem = Memory::new();
+        mem.load_rom(&make_rom(0x06, 0x00), false).unwrap();
+        assert_eq!(mem.get_cartridge_ram().len(), 512);
+        mem.write(0x0000, 0x0A);
+
+        mem.write(0xA000, 0x5C);
+        assert_eq!(mem.read(0xA000), 0xFC, "upper nibble reads as 1s");
+        mem.write(0xA1FF, 0x03);
+        for base in (0xA000..0xC000).step_by(0x200) {
+            assert_eq!(mem.read(base), 0xFC, "mirror at {:04X}", base);
+            assert_eq!(mem.read(base + 0x1FF), 0xF3, "mirror at {:04X}", base);
+        }
+        mem.write(0xBE00, 0x07); // mirrors 0xA000
+        assert_eq!(mem.read(0xA000), 0xF7);
+
+        mem.write(0x0000, 0x00);
+        assert_eq!(mem.read(0xA000), 0xFF);
+    }
+
+    #[test]
+    fn test_cached_rom_bank_matches_arithmetic() {
+        for cart_type in [0x01, 0x13, 0x1B, 0xFC] {
+            let rom = make_banked_rom(cart_type, 8);
+            let mut mem = Memory::new();
+            mem.load_rom(&rom, false).unwrap();
+            for bank in [1usize, 5, 2, 7, 3] {
+                mem.write(0x2000, bank as u8);
+                for off in [0usize, 1, 0x1234, 0x3FFF] {
+                    assert_eq!(
+                        mem.read(0x4000 + off as u16),
+                        rom[bank * 0x4000 + off],
+                        "type {:02X} bank {} off {:04X}",
+                        cart_type,
+                        bank,
+                        off
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rom_bank_past_end_wraps() {
+        // MBC1, MBC3, MBC5 with 4 banks (64KB): bank numbers wrap mod 4
+        for cart_type in [0x01, 0x13, 0x1B] {
+            let rom = make_banked_rom(cart_type, 4);
+            let mut mem = Memory::new();
+            mem.load_rom(&rom, false).unwrap();
+            mem.write(0x2000, 0x03);
+            assert_eq!(mem.read(0x7FFF), rom[3 * 0x4000 + 0x3FFF]);
+            mem.write(0x2000, 0x07);
+            assert_eq!(mem.read(0x4000), rom[3 * 0x4000], "type {:02X}", cart_type);
+            mem.write(0x2000, 0x05);
+            assert_eq!(
+                mem.read(0x7FFF),
+                rom[0x4000 + 0x3FFF],
+                "type {:02X}",
+                cart_type
+            );
+        }
+
+        let rom = make_banked_rom(0x1B, 4);
+        let mut mem = Memory::new();
+        mem.load_rom(&rom, false).unwrap();
+        mem.write(0x2000, 0x04);
+        assert_eq!(mem.read(0x4000), rom[0]);
+        mem.write(0x3000, 0x01); // bank 0x104
+        assert_eq!(mem.read(0x7FFF), rom[0x3FFF]);
+    }
+
+    /// Micro-benchmark: `cargo test --release bench_rom_bank_reads -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_rom_bank_reads() {
+        let rom = make_banked_rom(0x1B, 64);
+        let mut mem = Memory::new();
+        mem.load_rom(&rom, false).unwrap();
+        let start = std::time::Instant::now();
+        let mut sum = 0u32;
+        for pass in 0..200u16 {
+            mem.write(0x2000, (pass % 63 + 1) as u8);
+            for addr in 0x4000..=0x7FFFu16 {
+                sum = sum.wrapping_add(mem.read(addr) as u32);
+            }
+        }
+        let elapsed = start.elapsed();
+        println!(
+            "{} reads in {:?} ({:.2} ns/read, checksum {})",
+            200 * 0x4000,
+            elapsed,
+            elapsed.as_nanos() as f64 / (200.0 * 0x4000 as f64),
+            sum
+        );
+    }
 }
@@ -18,6 +18,9 @@ fn now_secs() -> u64 {
         .as_secs()
 }
 
+/// Size of the RTC footer appended to MBC3 `.sav` files.
+pub(crate) const RTC_SAVE_LEN: usize = 48;
+
 pub(crate) struct Rtc {
     // Live registers
     s: u8,
@@ -38,6 +41,14 @@ pub(crate) struct Rtc {
 
     // Unix timestamp (seconds) when live registers were last synced
     base_timestamp: u64,
+
+    // Unix timestamp (seconds) when emulation was paused, if paused
+    paused_at: Option<u64>,
+
+    // Deterministic mode: advance one second per this many ticks (frames)
+    // instead of following the wall clock
+    frames_per_second: Option<u32>,
+    frame_counter: u32,
 }
 
 impl Rtc {
@@ -55,24 +66,55 @@ impl Rtc {
             latched_dh: 0,
             latch_ready: false,
             base_timestamp: now_secs(),
+            paused_at: None,
+            frames_per_second: None,
+            frame_counter: 0,
         }
     }
 
-    /// Advance live registers based on wall-clock elapsed time.
+    /// Drive the clock from `tick` calls instead of the wall clock: every
+    /// `fps` ticks advance exactly one second. `None` (or 0) returns to
+    /// wall-clock time, continuing from the current register values.
+    pub fn set_frames_per_second(&mut self, fps: Option<u32>) {
+        self.frames_per_second = fps.filter(|&fps| fps > 0);
+        self.frame_counter = 0;
+        self.base_timestamp = now_secs();
+    }
+
+    /// Advance live registers based on wall-clock elapsed time, or by one
+    /// frame in `set_frames_per_second` mode.
     pub fn tick(&mut self) {
+        // Emulation paused — time is accounted for on resume
+        if self.paused_at.is_some() {
+            return;
+        }
+
         // Halted — don't advance
         if self.dh & 0x40 != 0 {
             self.base_timestamp = now_secs();
             return;
         }
 
+        if let Some(fps) = self.frames_per_second {
+            self.frame_counter += 1;
+            if self.frame_counter >= fps {
+                self.frame_counter = 0;
+                self.advance(1);
+            }
+            return;
+        }
+
         let now = now_secs();
         let elapsed = now.saturating_sub(self.base_timestamp);
         if elapsed == 0 {
             return;
         }
         self.base_timestamp = now;
+        self.advance(elapsed);
+    }
 
+    /// Add `elapsed` seconds to the live registers.
+    fn advance(&mut self, elapsed: u64) {
         // Convert current registers to total seconds
         let day = ((self.dh as u32 & 0x01) << 8) | self.dl as u32;
         let mut total_secs =
@@ -87,7 +129,7 @@ impl Rtc {
         self.h = (total_secs % 24) as u8;
         total_secs /= 24;
 
-        let days = total_secs as u32 + day;
+        let days = total_secs as u32;
         if days > 511 {
             // Day counter overflow — set carry, wrap to 0
             self.dh = (self.dh & 0x40) | 0x80; // preserve halt, set carry, clear day MSB
@@ -98,6 +140,26 @@ impl Rtc {
         }
     }
 
+    /// Freeze the clock while emulation is paused. On resume the paused
+    /// duration is skipped, so the clock continues from where it stopped.
+    pub fn set_paused(&mut self, paused: bool) {
+        match (paused, self.paused_at) {
+            (true, None) => {
+                // Catch up on wall-clock time first; frame-driven time only
+                // moves per emulated frame, and pausing is not a frame
+                if self.frames_per_second.is_none() {
+                    self.tick();
+                }
+                self.paused_at = Some(now_secs());
+            }
+            (false, Some(at)) => {
+                self.paused_at = None;
+                self.base_timestamp += now_secs().saturating_sub(at);
+            }
+            _ => {}
+        }
+    }
+
     /// Handle writes to 0x6000-0x7FFF for latch. Write 0x00 then 0x01 to latch.
     pub fn write_latch(&mut self, value: u8) {
         if value == 0x00 {
@@ -141,6 +203,56 @@ impl Rtc {
         self.base_timestamp = now_secs();
     }
 
+    /// Serialize in the common `.sav` RTC footer layout (VBA-M/BGB/mGBA):
+    /// live S/M/H/DL/DH then latched S/M/H/DL/DH as little-endian u32s,
+    /// followed by the Unix timestamp as a little-endian u64 — 48 bytes.
+    pub fn to_save_bytes(&self) -> [u8; RTC_SAVE_LEN] {
+        let regs = [
+            self.s,
+            self.m,
+            self.h,
+            self.dl,
+            self.dh,
+            self.latched_s,
+            self.latched_m,
+            self.latched_h,
+            self.latched_dl,
+            self.latched_dh,
+        ];
+        let mut out = [0u8; RTC_SAVE_LEN];
+        for (chunk, &r) in out.chunks_exact_mut(4).zip(regs.iter()) {
+            chunk.copy_from_slice(&(r as u32).to_le_bytes());
+        }
+        out[40..48].copy_from_slice(&self.base_timestamp.to_le_bytes());
+        out
+    }
+
+    /// Restore from a `.sav` RTC footer. Accepts the 48-byte layout and the
+    /// older 44-byte variant with a 32-bit timestamp. Returns false (leaving
+    /// the clock untouched) if `data` is too short.
+    pub fn load_save_bytes(&mut self, data: &[u8]) -> bool {
+        if data.len() < RTC_SAVE_LEN - 4 {
+            return false;
+        }
+        let reg = |i: usize| data[i * 4];
+        self.s = reg(0) & 0x3F;
+        self.m = reg(1) & 0x3F;
+        self.h = reg(2) & 0x1F;
+        self.dl = reg(3);
+        self.dh = reg(4) & 0xC1;
+        self.latched_s = reg(5) & 0x3F;
+        self.latched_m = reg(6) & 0x3F;
+        self.latched_h = reg(7) & 0x1F;
+        self.latched_dl = reg(8);
+        self.latched_dh = reg(9) & 0xC1;
+        self.base_timestamp = if data.len() >= RTC_SAVE_LEN {
+            u64::from_le_bytes(data[40..48].try_into().unwrap())
+        } else {
+            u32::from_le_bytes(data[40..44].try_into().unwrap()) as u64
+        };
+        true
+    }
+
     /// Returns true when the selected bank maps to an RTC register.
     pub fn is_rtc_register(bank: u8) -> bool {
         (0x08..=0x0C).contains(&bank)
@@ -293,6 +405,25 @@ mod tests {
         assert_eq!(rtc.read_register(0x0B), 1);
     }
 
+    #[test]
+    fn test_pause_in_frame_mode_does_not_advance() {
+        let mut rtc = Rtc::new();
+        rtc.set_frames_per_second(Some(1)); // every tick is one second
+        rtc.write_register(0x08, 10);
+
+        rtc.set_paused(true);
+        rtc.tick();
+        rtc.set_paused(false);
+        rtc.write_latch(0x00);
+        rtc.write_latch(0x01);
+        assert_eq!(rtc.read_register(0x08), 10);
+
+        rtc.tick();
+        rtc.write_latch(0x00);
+        rtc.write_latch(0x01);
+        assert_eq!(rtc.read_register(0x08), 11);
+    }
+
     #[test]
     fn test_halt_prevents_advance() {
         let mut rtc = Rtc::new();
@@ -327,6 +458,65 @@ mod tests {
         assert_eq!(rtc.read_register(0x0C) & 0x01, 0x00); // day MSB cleared
     }
 
+    #[test]
+    fn test_pause_freezes_clock() {
+        let mut rtc = Rtc::new();
+        rtc.write_register(0x0B, 5); // day 5
+
+        rtc.set_paused(true);
+        // Simulate the pause having started two days ago
+        rtc.paused_at = Some(now_secs() - 2 * 86400);
+        rtc.base_timestamp -= 2 * 86400;
+        rtc.tick();
+        rtc.write_latch(0x00);
+        rtc.write_latch(0x01);
+        assert_eq!(rtc.read_register(0x0B), 5, "no advance while paused");
+
+        rtc.set_paused(false);
+        rtc.tick();
+        rtc.write_latch(0x00);
+        rtc.write_latch(0x01);
+        assert_eq!(
+            rtc.read_register(0x0B),
+            5,
+            "paused time is skipped on resume"
+        );
+
+        // Running again: one more day elapses normally
+        rtc.base_timestamp -= 86400;
+        rtc.tick();
+        rtc.write_latch(0x00);
+        rtc.write_latch(0x01);
+        assert_eq!(rtc.read_register(0x0B), 6);
+    }
+
+    #[test]
+    fn test_save_bytes_round_trip() {
+        let mut rtc = Rtc::new();
+        rtc.write_register(0x08, 12);
+        rtc.write_register(0x0A, 7);
+        rtc.write_register(0x0C, 0x41); // halted, day MSB
+        rtc.write_latch(0x00);
+        rtc.write_latch(0x01);
+        rtc.write_register(0x09, 33); // live differs from latched
+        rtc.base_timestamp = 1_700_000_000;
+
+        let bytes = rtc.to_save_bytes();
+        assert_eq!(&bytes[0..4], &[12, 0, 0, 0]);
+        assert_eq!(&bytes[40..48], &1_700_000_000u64.to_le_bytes());
+
+        let mut restored = Rtc::new();
+        assert!(restored.load_save_bytes(&bytes));
+        assert_eq!(restored.to_save_bytes(), bytes);
+        assert_eq!(restored.read_register(0x09), 0, "latched copy restored");
+
+        // 44-byte variant with a 32-bit timestamp
+        let mut short = Rtc::new();
+        assert!(short.load_save_bytes(&bytes[..44]));
+        assert_eq!(short.base_timestamp, 1_700_000_000);
+        assert!(!short.load_save_bytes(&bytes[..40]));
+    }
+
     #[test]
     fn test_read_invalid_register() {
         let rtc = Rtc::new();
@@ -4,17 +4,53 @@
 //! and enforces GBC sprite priority rules (force-priority, OAM bg-priority, LCDC master).
 
 use super::{Ppu, SCREEN_WIDTH};
+use crate::color::rgb555_to_rgba8888;
 use crate::memory::Memory;
 use crate::memory::io;
 
 impl Ppu {
-    /// Convert a 15-bit RGB555 little-endian pair to RGBA.
+    /// Colour and index-buffer byte for a BG/window pixel: palette RAM, or
+    /// BGP grayscale when the DMG fallback is on and the ROM never wrote BG
+    /// palettes.
     #[inline]
-    pub(super) fn rgb555_to_rgba(lo: u8, hi: u8) -> [u8; 4] {
-        let r5 = lo & 0x1F;
-        let g5 = ((lo >> 5) | (hi << 3)) & 0x1F;
-        let b5 = (hi >> 2) & 0x1F;
-        [r5 << 3 | r5 >> 2, g5 << 3 | g5 >> 2, b5 << 3 | b5 >> 2, 255]
+    fn bg_color(&self, memory: &Memory, palette: usize, color_idx: usize) -> ([u8; 4], u8) {
+        if self.cgb_dmg_fallback && !memory.bg_palette_written() {
+            let bgp = memory.read_io_direct(io::BGP);
+            return Self::dmg_shade(bgp, color_idx);
+        }
+        let (lo, hi) = memory.read_bg_palette(palette, color_idx);
+        (
+            rgb555_to_rgba8888(lo, hi, self.color_correction),
+            0x80 | (palette as u8) << 2 | color_idx as u8,
+        )
+    }
+
+    /// Colour for a sprite pixel: palette RAM, or OBP0/OBP1 (attribute bit 4)
+    /// grayscale when the DMG fallback is on and OBJ palettes were never written.
+    #[inline]
+    fn obj_color(&self, memory: &Memory, flags: u8, color_idx: usize) -> ([u8; 4], u8) {
+        if self.cgb_dmg_fallback && !memory.obj_palette_written() {
+            let obp = if flags & 0x10 != 0 {
+                io::OBP1
+            } else {
+                io::OBP0
+            };
+            return Self::dmg_shade(memory.read_io_direct(obp), color_idx);
+        }
+        let palette = flags & 0x07;
+        let (lo, hi) = memory.read_obj_palette(palette as usize, color_idx);
+        (
+            rgb555_to_rgba8888(lo, hi, self.color_correction),
+            0xA0 | palette << 2 | color_idx as u8,
+        )
+    }
+
+    #[inline]
+    fn dmg_shade(palette: u8, color_idx: usize) -> ([u8; 4], u8) {
+        const GRAY: [u8; 4] = [0xFF, 0xAA, 0x55, 0x00];
+        let shade = (palette >> (color_idx * 2)) & 0x03;
+        let g = GRAY[shade as usize];
+        ([g, g, g, 255], shade)
     }
 
     pub(super) fn render_background_gbc(&mut self, memory: &Memory, line: usize) {
@@ -60,10 +96,8 @@ impl Ppu {
             let pixel_col = if x_flip { x & 7 } else { 7 - (x & 7) };
             let color_idx = (((high >> pixel_col) & 1) << 1 | ((low >> pixel_col) & 1)) as usize;
 
-            let (lo, hi) = memory.read_bg_palette(palette, color_idx);
-            let rgba = Self::rgb555_to_rgba(lo, hi);
-            let offset = (line * SCREEN_WIDTH + screen_x) * 4;
-            self.buffer[offset..offset + 4].copy_from_slice(&rgba);
+            let (rgba, index) = self.bg_color(memory, palette, color_idx);
+            self.put_pixel(line, screen_x, rgba, index);
             self.scanline_bg_info[screen_x] =
                 (color_idx == 0) as u8 | ((force_priority as u8) << 1);
         }
@@ -92,7 +126,7 @@ impl Ppu {
         let start_x = wx.max(0) as usize;
 
         for screen_x in start_x..SCREEN_WIDTH {
-            let window_x = screen_x - start_x;
+            let window_x = (screen_x as i16 - wx) as usize;
             let tile_col = window_x >> 3;
             let tile_map_addr = tile_map_base + (tile_row * 32 + tile_col) as u16;
 
@@ -123,10 +157,8 @@ impl Ppu {
             let high = memory.read_vram_bank(tile_bank, tile_data_addr + 1);
             let color_idx = (((high >> pixel_col) & 1) << 1 | ((low >> pixel_col) & 1)) as usize;
 
-            let (lo, hi) = memory.read_bg_palette(palette, color_idx);
-            let rgba = Self::rgb555_to_rgba(lo, hi);
-            let offset = (line * SCREEN_WIDTH + screen_x) * 4;
-            self.buffer[offset..offset + 4].copy_from_slice(&rgba);
+            let (rgba, index) = self.bg_color(memory, palette, color_idx);
+            self.put_pixel(line, screen_x, rgba, index);
             self.scanline_bg_info[screen_x] =
                 (color_idx == 0) as u8 | ((force_priority as u8) << 1);
         }
@@ -143,27 +175,13 @@ impl Ppu {
         let sprite_height: i16 = if lcdc & 0x04 != 0 { 16 } else { 8 };
         let oam = memory.get_oam();
 
-        let mut sprites: [(u8, i16, u8, u8); 10] = [(0, 0, 0, 0); 10];
-        let mut sprite_count: usize = 0;
-
-        for i in 0..40 {
-            let o = i * 4;
-            let screen_y = oam[o] as i16 - 16;
-            if (line as i16) >= screen_y && (line as i16) < screen_y + sprite_height {
-                sprites[sprite_count] = (oam[o + 1], screen_y, oam[o + 2], oam[o + 3]);
-                sprite_count += 1;
-                if sprite_count >= 10 {
-                    break;
-                }
-            }
-        }
+        let (sprites, sprite_count) = self.scan_oam(oam, line, sprite_height);
 
         for &(x, screen_y, mut tile, flags) in sprites[..sprite_count].iter().rev() {
             let flip_x = flags & 0x20 != 0;
             let flip_y = flags & 0x40 != 0;
             let bg_priority = flags & 0x80 != 0;
             let tile_bank = ((flags >> 3) & 1) as usize;
-            let cgb_palette = (flags & 0x07) as usize;
 
             let mut sprite_row = (line as i16) - screen_y;
             if flip_y {
@@ -213,9 +231,8 @@ impl Ppu {
                     continue;
                 }
 
-                let (lo, hi) = memory.read_obj_palette(cgb_palette, color_idx as usize);
-                let offset = (line * SCREEN_WIDTH + sx) * 4;
-                self.buffer[offset..offset + 4].copy_from_slice(&Self::rgb555_to_rgba(lo, hi));
+                let (rgba, index) = self.obj_color(memory, flags, color_idx as usize);
+                self.put_pixel(line, sx, rgba, index);
             }
         }
     }
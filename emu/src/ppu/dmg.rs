@@ -3,9 +3,9 @@
 //! All methods write RGBA directly to `self.buffer` and update
 //! `self.scanline_bg_info` for downstream sprite priority checks.
 
-use crate::memory::io;
-use crate::memory::Memory;
 use super::{Ppu, SCREEN_WIDTH};
+use crate::memory::Memory;
+use crate::memory::io;
 
 impl Ppu {
     pub(super) fn render_background_dmg(&mut self, memory: &Memory, line: usize) {
@@ -43,8 +43,7 @@ impl Ppu {
             let shade = (bgp >> (color_idx * 2)) & 0x03;
             const GRAY: [u8; 4] = [0xFF, 0xAA, 0x55, 0x00];
             let g = GRAY[shade as usize];
-            let offset = (line * SCREEN_WIDTH + screen_x) * 4;
-            self.buffer[offset..offset + 4].copy_from_slice(&[g, g, g, 255]);
+            self.put_pixel(line, screen_x, [g, g, g, 255], shade);
             self.scanline_bg_info[screen_x] = (color_idx == 0) as u8;
         }
     }
@@ -89,8 +88,7 @@ impl Ppu {
             let shade = (bgp >> (color_idx * 2)) & 0x03;
             const GRAY: [u8; 4] = [0xFF, 0xAA, 0x55, 0x00];
             let g = GRAY[shade as usize];
-            let offset = (line * SCREEN_WIDTH + screen_x) * 4;
-            self.buffer[offset..offset + 4].copy_from_slice(&[g, g, g, 255]);
+            self.put_pixel(line, screen_x, [g, g, g, 255], shade);
             self.scanline_bg_info[screen_x] = (color_idx == 0) as u8;
         }
 
@@ -104,20 +102,7 @@ impl Ppu {
         let obp0 = memory.read_io_direct(io::OBP0);
         let obp1 = memory.read_io_direct(io::OBP1);
 
-        let mut sprites: [(u8, i16, u8, u8); 10] = [(0, 0, 0, 0); 10];
-        let mut sprite_count: usize = 0;
-
-        for i in 0..40 {
-            let o = i * 4;
-            let screen_y = oam[o] as i16 - 16;
-            if (line as i16) >= screen_y && (line as i16) < screen_y + sprite_height {
-                sprites[sprite_count] = (oam[o + 1], screen_y, oam[o + 2], oam[o + 3]);
-                sprite_count += 1;
-                if sprite_count >= 10 {
-                    break;
-                }
-            }
-        }
+        let (mut sprites, sprite_count) = self.scan_oam(oam, line, sprite_height);
 
         sprites[..sprite_count].sort_by_key(|s| s.0);
 
@@ -166,8 +151,7 @@ impl Ppu {
                 let palette = if flags & 0x10 != 0 { obp1 } else { obp0 };
                 let shade = (palette >> (color_idx * 2)) & 0x03;
                 let g = GRAY[shade as usize];
-                let offset = (line * SCREEN_WIDTH + sx) * 4;
-                self.buffer[offset..offset + 4].copy_from_slice(&[g, g, g, 255]);
+                self.put_pixel(line, sx, [g, g, g, 255], shade);
             }
         }
     }
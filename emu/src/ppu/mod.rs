@@ -11,8 +11,12 @@
 mod cgb;
 mod dmg;
 
+use std::collections::{HashMap, VecDeque};
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
 
+use crate::color::{ColorCorrection, rgb555_to_rgba8888};
 use crate::interrupts::{Interrupt, InterruptController};
 use crate::memory::Memory;
 use crate::memory::io;
@@ -37,6 +41,47 @@ impl fmt::Display for PpuDebugState {
     }
 }
 
+/// Scroll/window/palette registers as they were when a scanline rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IoSnapshot {
+    pub lcdc: u8,
+    pub scy: u8,
+    pub scx: u8,
+    pub wy: u8,
+    pub wx: u8,
+    pub bgp: u8,
+}
+
+impl IoSnapshot {
+    fn capture(memory: &Memory) -> Self {
+        IoSnapshot {
+            lcdc: memory.read_io_direct(io::LCDC),
+            scy: memory.read_io_direct(io::SCY),
+            scx: memory.read_io_direct(io::SCX),
+            wy: memory.read_io_direct(io::WY),
+            wx: memory.read_io_direct(io::WX),
+            bgp: memory.read_io_direct(io::BGP),
+        }
+    }
+}
+
+/// A PPU mode change recorded by the mode trace (see `Ppu::set_mode_trace`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModeEvent {
+    /// Dots since tracing was enabled at which the new mode began.
+    pub dot: u64,
+    /// LY when the mode was entered.
+    pub line: u8,
+    /// New STAT mode (0 = HBlank, 1 = VBlank, 2 = OAM, 3 = Drawing).
+    pub mode: u8,
+}
+
+/// Most recent mode changes kept by the trace (about 4 frames' worth).
+const MODE_TRACE_CAP: usize = 4096;
+
+/// Called after each visible scanline renders, with LY and its registers.
+pub type ScanlineCallback = Box<dyn FnMut(u8, &IoSnapshot)>;
+
 impl PpuMode {
     #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: PpuDebugState
     fn name(self) -> &'static str {
@@ -66,49 +111,198 @@ enum PpuMode {
 
 // Mode durations in cycles
 const OAM_SCAN_CYCLES: u32 = 80;
-const DRAWING_CYCLES: u32 = 172; // Variable, but we use fixed for simplicity
-const HBLANK_CYCLES: u32 = 204;
+const DRAWING_CYCLES: u32 = 172; // Minimum; see `drawing_length`
 const SCANLINE_CYCLES: u32 = 456;
+/// Mode 3 dots added per object fetched, and when the window starts.
+const OBJ_FETCH_CYCLES: u32 = 6;
+const WINDOW_START_CYCLES: u32 = 6;
 
 pub struct Ppu {
     /// RGBA frame buffer — 160×144×4 bytes written directly by render functions.
     pub(super) buffer: Box<[u8; SCREEN_WIDTH * SCREEN_HEIGHT * 4]>,
+    /// One byte per pixel, written alongside `buffer`: see [`Ppu::index_to_rgba`]
+    /// for the encoding.
+    index_buffer: Box<[u8; SCREEN_WIDTH * SCREEN_HEIGHT]>,
     /// Per-pixel BG info for the current scanline — used for sprite priority.
     /// Bit 0 = pixel is BG colour 0 (transparent for sprites).
     /// Bit 1 = tile has GBC force-priority flag set.
     pub(super) scanline_bg_info: [u8; SCREEN_WIDTH],
     mode: PpuMode,
     pub(super) cycles: u32,
+    /// Mode 3 length for the current line; H-blank gets the rest of the 456.
+    drawing_cycles: u32,
     pub(super) line: u8,
     pub(super) window_line_counter: u8,
+    /// Internal STAT interrupt line (all enabled sources OR'd together) as of
+    /// the last tick; `LcdStat` is only requested on its rising edge.
+    stat_line: bool,
+    /// LCDC.7 as of the last tick, to catch the LCD being switched back on.
+    lcd_on: bool,
+    /// First frame after the LCD was switched on: nothing is drawn (the
+    /// previous picture stays up, as while the LCD is off) and line 0 never
+    /// sets the LY=LYC coincidence flag.
+    enable_frame: bool,
     pub(crate) frame_ready: bool,
     /// Set to true for one tick whenever the PPU transitions Drawing → HBlank.
     /// Consumed by the core to trigger an H-blank HDMA step.
     hblank_this_tick: bool,
     /// GBC colour mode — set once at load_rom time, never changes mid-session.
     pub(super) cgb_mode: bool,
+    /// Optional per-channel output remap: `lut[v][c]` replaces value `v` in
+    /// channel `c` (R/G/B; the alpha column is unused). Survives `reset`.
+    output_lut: Option<Box<[[u8; 4]; 256]>>,
+    /// In CGB mode, shade BG/OBJ through BGP/OBP0/OBP1 in grayscale while
+    /// the ROM has not written the matching colour palette RAM (DMG games
+    /// forced into CGB mode). Survives `reset`.
+    pub(super) cgb_dmg_fallback: bool,
+    /// Sprites intersecting each line in the last frame, before and after
+    /// the 10-per-line limit (see `sprites_per_line`).
+    sprites_found: [u8; SCREEN_HEIGHT],
+    sprites_drawn: [u8; SCREEN_HEIGHT],
+    /// LCD ghosting: fraction of the previous frame's level kept per pixel
+    /// (0 = off). Survives `reset`; the history does not.
+    ghosting: f32,
+    /// Per-pixel colour level (0.0..=3.0, the index buffer's low 2 bits)
+    /// last shown, allocated while ghosting is on.
+    ghost_levels: Vec<f32>,
+    /// RGB555 → RGBA mapping for CGB palette colours. Survives `reset`.
+    pub(super) color_correction: ColorCorrection,
+    /// Opt-in raster-effect probe (see `set_scanline_callback`). Survives `reset`.
+    scanline_callback: Option<ScanlineCallback>,
+    /// Opt-in mode change log (see `set_mode_trace`), with the dot clock it
+    /// timestamps against. Enabled state survives `reset`; events do not.
+    mode_trace: Option<VecDeque<ModeEvent>>,
+    trace_dots: u64,
 }
 
 impl Ppu {
     pub fn new() -> Self {
         Ppu {
             buffer: Box::new([0; SCREEN_WIDTH * SCREEN_HEIGHT * 4]),
+            index_buffer: Box::new([0; SCREEN_WIDTH * SCREEN_HEIGHT]),
             scanline_bg_info: [0; SCREEN_WIDTH],
             mode: PpuMode::OamScan,
             cycles: 0,
+            drawing_cycles: DRAWING_CYCLES,
             line: 0,
             window_line_counter: 0,
+            stat_line: false,
+            lcd_on: true,
+            enable_frame: false,
             frame_ready: false,
             hblank_this_tick: false,
             cgb_mode: false,
+            output_lut: None,
+            cgb_dmg_fallback: false,
+            ghosting: 0.0,
+            ghost_levels: Vec::new(),
+            color_correction: ColorCorrection::None,
+            sprites_found: [0; SCREEN_HEIGHT],
+            sprites_drawn: [0; SCREEN_HEIGHT],
+            scanline_callback: None,
+            mode_trace: None,
+            trace_dots: 0,
         }
     }
 
     /// Reset PPU to power-on state for the given mode.
     /// Called by GameBoyCore::load_rom() on every ROM load.
     pub fn reset(&mut self, cgb_mode: bool) {
+        let output_lut = self.output_lut.take();
+        let cgb_dmg_fallback = self.cgb_dmg_fallback;
+        let color_correction = self.color_correction;
+        let ghosting = self.ghosting;
+        let scanline_callback = self.scanline_callback.take();
+        let mode_trace = self.mode_trace.is_some();
         *self = Self::new();
         self.cgb_mode = cgb_mode;
+        self.output_lut = output_lut;
+        self.cgb_dmg_fallback = cgb_dmg_fallback;
+        self.color_correction = color_correction;
+        self.set_lcd_ghosting(ghosting);
+        self.scanline_callback = scanline_callback;
+        self.set_mode_trace(mode_trace);
+    }
+
+    /// Start (clearing any previous events) or stop recording mode changes,
+    /// for diagnosing STAT timing. Keeps the last `MODE_TRACE_CAP` events.
+    pub fn set_mode_trace(&mut self, enabled: bool) {
+        self.mode_trace = enabled.then(VecDeque::new);
+        self.trace_dots = 0;
+    }
+
+    /// Recorded mode changes, oldest first (empty when tracing is off).
+    pub fn mode_trace(&self) -> Vec<ModeEvent> {
+        self.mode_trace.iter().flatten().copied().collect()
+    }
+
+    /// Switch mode, logging the change if tracing. `self.cycles` is how far
+    /// into the new mode this tick already ran, so the event is backdated.
+    fn enter_mode(&mut self, mode: PpuMode) {
+        if self.mode == mode {
+            return;
+        }
+        self.mode = mode;
+        if let Some(trace) = &mut self.mode_trace {
+            if trace.len() == MODE_TRACE_CAP {
+                trace.pop_front();
+            }
+            trace.push_back(ModeEvent {
+                dot: self.trace_dots.saturating_sub(self.cycles as u64),
+                line: self.line,
+                mode: mode as u8,
+            });
+        }
+    }
+
+    /// Install (or remove with `None`) a callback run at the end of every
+    /// rendered scanline, for logging mid-frame register changes.
+    pub fn set_scanline_callback(&mut self, callback: Option<ScanlineCallback>) {
+        self.scanline_callback = callback;
+    }
+
+    /// Enable the grayscale BGP/OBP fallback for uninitialised CGB palettes.
+    pub fn set_cgb_dmg_fallback(&mut self, enabled: bool) {
+        self.cgb_dmg_fallback = enabled;
+    }
+
+    /// Emulate the DMG LCD's slow pixel response: each frame a pixel's
+    /// level moves only `1 - factor` of the way from the level it showed
+    /// last frame to the newly rendered one (an exponential decay).
+    /// `factor` is clamped to 0.0..=0.95; 0 disables it. The decay runs on
+    /// the index buffer's level (the shade on DMG, the colour number on
+    /// CGB) and the decayed level is then mapped through the pixel's
+    /// palette, so colours never smear across channels. Unlike frame
+    /// blending this accumulates over many frames.
+    #[cfg_attr(not(any(feature = "ios", feature = "wasm")), allow(dead_code))]
+    pub fn set_lcd_ghosting(&mut self, factor: f32) {
+        self.ghosting = if factor.is_finite() {
+            factor.clamp(0.0, 0.95)
+        } else {
+            0.0
+        };
+        if self.ghosting == 0.0 {
+            self.ghost_levels = Vec::new();
+        } else if self.ghost_levels.is_empty() {
+            self.ghost_levels = vec![f32::NAN; SCREEN_WIDTH * SCREEN_HEIGHT];
+        }
+    }
+
+    /// Choose how CGB palette colours are converted for display.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: set_color_correction
+    pub fn set_color_correction(&mut self, correction: ColorCorrection) {
+        self.color_correction = correction;
+    }
+
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: get_*_palette_color
+    pub fn color_correction(&self) -> ColorCorrection {
+        self.color_correction
+    }
+
+    /// Install (or clear with `None`) a colour LUT applied to every rendered
+    /// scanline — for colour-blindness filters and custom tints.
+    pub fn set_output_lut(&mut self, lut: Option<[[u8; 4]; 256]>) {
+        self.output_lut = lut.map(Box::new);
     }
 
     pub fn tick(&mut self, cycles: u32, memory: &mut Memory, interrupts: &InterruptController) {
@@ -116,61 +310,64 @@ impl Ppu {
 
         // LCD disabled - keep the last frame visible (don't clear buffer)
         if lcdc & 0x80 == 0 {
-            self.mode = PpuMode::HBlank;
             self.cycles = 0;
+            self.enter_mode(PpuMode::HBlank);
             self.line = 0;
+            self.stat_line = false;
+            self.lcd_on = false;
             memory.write_io_direct(io::LY, 0);
             return;
         }
 
+        // Switched back on: start a fresh frame at the top of line 0 rather
+        // than wherever the H-blank the LCD was parked in would lead
+        if !self.lcd_on {
+            self.lcd_on = true;
+            self.enable_frame = true;
+            self.cycles = 0;
+            self.window_line_counter = 0;
+            self.enter_mode(PpuMode::OamScan);
+        }
+
         self.cycles += cycles;
+        if self.mode_trace.is_some() {
+            self.trace_dots += cycles as u64;
+        }
 
         match self.mode {
             PpuMode::OamScan => {
                 if self.cycles >= OAM_SCAN_CYCLES {
                     self.cycles -= OAM_SCAN_CYCLES;
-                    self.mode = PpuMode::Drawing;
+                    self.drawing_cycles = self.drawing_length(memory);
+                    self.enter_mode(PpuMode::Drawing);
                 }
             }
             PpuMode::Drawing => {
-                if self.cycles >= DRAWING_CYCLES {
-                    self.cycles -= DRAWING_CYCLES;
-                    self.mode = PpuMode::HBlank;
+                if self.cycles >= self.drawing_cycles {
+                    self.cycles -= self.drawing_cycles;
+                    self.enter_mode(PpuMode::HBlank);
                     self.hblank_this_tick = true;
 
-                    self.render_scanline(memory);
-
-                    let stat = memory.read_io_direct(io::STAT);
-                    if stat & 0x08 != 0 {
-                        interrupts.request(Interrupt::LcdStat, memory);
+                    if !self.enable_frame {
+                        self.render_scanline(memory);
                     }
                 }
             }
             PpuMode::HBlank => {
-                if self.cycles >= HBLANK_CYCLES {
-                    self.cycles -= HBLANK_CYCLES;
+                let hblank_cycles = SCANLINE_CYCLES - OAM_SCAN_CYCLES - self.drawing_cycles;
+                if self.cycles >= hblank_cycles {
+                    self.cycles -= hblank_cycles;
                     self.line += 1;
                     memory.write_io_direct(io::LY, self.line);
 
-                    self.check_lyc_coincidence(memory, interrupts);
-
                     if self.line >= SCREEN_HEIGHT as u8 {
-                        self.mode = PpuMode::VBlank;
+                        self.enter_mode(PpuMode::VBlank);
                         self.window_line_counter = 0;
+                        self.enable_frame = false;
                         self.frame_ready = true;
                         interrupts.request(Interrupt::VBlank, memory);
-
-                        let stat = memory.read_io_direct(io::STAT);
-                        if stat & 0x10 != 0 {
-                            interrupts.request(Interrupt::LcdStat, memory);
-                        }
                     } else {
-                        self.mode = PpuMode::OamScan;
-
-                        let stat = memory.read_io_direct(io::STAT);
-                        if stat & 0x20 != 0 {
-                            interrupts.request(Interrupt::LcdStat, memory);
-                        }
+                        self.enter_mode(PpuMode::OamScan);
                     }
                 }
             }
@@ -181,40 +378,77 @@ impl Ppu {
 
                     if self.line >= TOTAL_LINES as u8 {
                         self.line = 0;
-                        self.mode = PpuMode::OamScan;
-
-                        let stat = memory.read_io_direct(io::STAT);
-                        if stat & 0x20 != 0 {
-                            interrupts.request(Interrupt::LcdStat, memory);
-                        }
+                        self.enter_mode(PpuMode::OamScan);
                     }
 
                     memory.write_io_direct(io::LY, self.line);
-                    self.check_lyc_coincidence(memory, interrupts);
                 }
             }
         }
 
-        // Update STAT register mode bits
+        // Update STAT register mode and coincidence bits
         let mut stat = memory.read_io_direct(io::STAT);
         stat = (stat & 0xFC) | self.mode as u8;
+        let suppress_lyc = self.enable_frame && self.line == 0;
+        if self.line == memory.read_io_direct(io::LYC) && !suppress_lyc {
+            stat |= 0x04;
+        } else {
+            stat &= !0x04;
+        }
         memory.write_io_direct(io::STAT, stat);
+        self.update_stat_line(stat, memory, interrupts);
     }
 
-    fn check_lyc_coincidence(&self, memory: &mut Memory, interrupts: &InterruptController) {
-        let lyc = memory.read_io_direct(io::LYC);
-        let mut stat = memory.read_io_direct(io::STAT);
+    /// OR the enabled STAT sources into the internal interrupt line and
+    /// request `LcdStat` only when it goes low → high ("STAT blocking"): a
+    /// source that turns on while another is already holding the line high
+    /// does not interrupt again.
+    fn update_stat_line(
+        &mut self,
+        stat: u8,
+        memory: &mut Memory,
+        interrupts: &InterruptController,
+    ) {
+        let line = (stat & 0x40 != 0 && stat & 0x04 != 0)
+            || match self.mode {
+                PpuMode::HBlank => stat & 0x08 != 0,
+                PpuMode::VBlank => stat & 0x10 != 0,
+                PpuMode::OamScan => stat & 0x20 != 0,
+                PpuMode::Drawing => false,
+            };
+        if line && !self.stat_line {
+            interrupts.request(Interrupt::LcdStat, memory);
+        }
+        self.stat_line = line;
+    }
 
-        if self.line == lyc {
-            stat |= 0x04;
-            if stat & 0x40 != 0 {
-                interrupts.request(Interrupt::LcdStat, memory);
-            }
-        } else {
-            stat &= !0x04;
+    /// Mode 3 length for the current line: 172 dots, plus the SCX & 7
+    /// pixels discarded for fine scroll, `OBJ_FETCH_CYCLES` per object the
+    /// OAM scan selected (the minimum; fetch alignment penalties are not
+    /// modelled) and `WINDOW_START_CYCLES` if the window starts.
+    fn drawing_length(&self, memory: &Memory) -> u32 {
+        let lcdc = memory.read_io_direct(io::LCDC);
+        let mut dots = DRAWING_CYCLES + (memory.read_io_direct(io::SCX) & 7) as u32;
+
+        if lcdc & 0x02 != 0 {
+            let height: i16 = if lcdc & 0x04 != 0 { 16 } else { 8 };
+            let line = self.line as i16;
+            let objects = memory
+                .get_oam()
+                .chunks_exact(4)
+                .filter(|o| (0..height).contains(&(line - (o[0] as i16 - 16))))
+                .take(10)
+                .count();
+            dots += OBJ_FETCH_CYCLES * objects as u32;
         }
 
-        memory.write_io_direct(io::STAT, stat);
+        let window = lcdc & 0x20 != 0
+            && self.line >= memory.read_io_direct(io::WY)
+            && memory.read_io_direct(io::WX) < 167;
+        if window {
+            dots += WINDOW_START_CYCLES;
+        }
+        dots
     }
 
     fn render_scanline(&mut self, memory: &Memory) {
@@ -237,10 +471,8 @@ impl Ppu {
             }
         } else {
             // Background disabled — fill scanline with white
-            let start = line * SCREEN_WIDTH * 4;
             for px in 0..SCREEN_WIDTH {
-                self.buffer[start + px * 4..start + px * 4 + 4]
-                    .copy_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF]);
+                self.put_pixel(line, px, [0xFF, 0xFF, 0xFF, 0xFF], 0);
             }
         }
 
@@ -254,6 +486,8 @@ impl Ppu {
         }
 
         // Sprites
+        self.sprites_found[line] = 0;
+        self.sprites_drawn[line] = 0;
         if lcdc & 0x02 != 0 {
             if self.cgb_mode {
                 self.render_sprites_gbc(memory, line);
@@ -261,6 +495,68 @@ impl Ppu {
                 self.render_sprites_dmg(memory, line);
             }
         }
+
+        self.apply_ghosting(memory, line);
+        self.apply_output_lut(line);
+
+        if let Some(callback) = self.scanline_callback.as_mut() {
+            callback(self.line, &IoSnapshot::capture(memory));
+        }
+    }
+
+    /// Write one pixel to the RGBA buffer and its index to the index buffer.
+    #[inline]
+    pub(super) fn put_pixel(&mut self, line: usize, x: usize, rgba: [u8; 4], index: u8) {
+        let i = line * SCREEN_WIDTH + x;
+        self.buffer[i * 4..i * 4 + 4].copy_from_slice(&rgba);
+        self.index_buffer[i] = index;
+    }
+
+    /// Decay each pixel's level toward the one just rendered, then redraw
+    /// it by interpolating between the two palette entries around the
+    /// decayed level.
+    fn apply_ghosting(&mut self, memory: &Memory, line: usize) {
+        if self.ghosting == 0.0 {
+            return;
+        }
+        let keep = self.ghosting;
+        for i in line * SCREEN_WIDTH..(line + 1) * SCREEN_WIDTH {
+            let index = self.index_buffer[i];
+            let target = (index & 0x03) as f32;
+            // NaN = no history yet (first frame after enabling)
+            let prev = self.ghost_levels[i];
+            let level = if prev.is_nan() {
+                target
+            } else {
+                prev * keep + target * (1.0 - keep)
+            };
+            self.ghost_levels[i] = level;
+            if level == target {
+                continue; // already drawn in exactly this colour
+            }
+
+            let low = (level.floor() as u8).min(3);
+            let t = level - low as f32;
+            let palette = index & !0x03;
+            let a = Self::resolve_index(memory, palette | low, self.color_correction);
+            let b = Self::resolve_index(memory, palette | (low + 1).min(3), self.color_correction);
+            for c in 0..3 {
+                let mixed = a[c] as f32 * (1.0 - t) + b[c] as f32 * t;
+                self.buffer[i * 4 + c] = mixed.round() as u8;
+            }
+        }
+    }
+
+    fn apply_output_lut(&mut self, line: usize) {
+        let Some(lut) = &self.output_lut else {
+            return;
+        };
+        let start = line * SCREEN_WIDTH * 4;
+        for px in self.buffer[start..start + SCREEN_WIDTH * 4].chunks_exact_mut(4) {
+            for c in 0..3 {
+                px[c] = lut[px[c] as usize][c];
+            }
+        }
     }
 
     /// Returns true (and clears the flag) if the PPU entered H-blank this tick.
@@ -278,10 +574,165 @@ impl Ppu {
         r
     }
 
+    /// OAM scan for `line`: the first 10 sprites (in OAM order) that
+    /// intersect it as (x, screen_y, tile, flags), and how many there are.
+    /// Records the found/drawn counts for `sprites_per_line`.
+    pub(super) fn scan_oam(
+        &mut self,
+        oam: &[u8],
+        line: usize,
+        sprite_height: i16,
+    ) -> ([(u8, i16, u8, u8); 10], usize) {
+        let mut sprites: [(u8, i16, u8, u8); 10] = [(0, 0, 0, 0); 10];
+        let mut found: usize = 0;
+
+        for i in 0..40 {
+            let o = i * 4;
+            let screen_y = oam[o] as i16 - 16;
+            if (line as i16) >= screen_y && (line as i16) < screen_y + sprite_height {
+                if found < 10 {
+                    sprites[found] = (oam[o + 1], screen_y, oam[o + 2], oam[o + 3]);
+                }
+                found += 1;
+            }
+        }
+
+        let drawn = found.min(10);
+        self.sprites_found[line] = found as u8;
+        self.sprites_drawn[line] = drawn as u8;
+        (sprites, drawn)
+    }
+
+    /// Sprites that intersected each scanline in the last frame, ignoring
+    /// the hardware limit — values above 10 mean sprites were dropped.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: get_sprites_per_line
+    pub fn sprites_per_line(&self) -> [u8; SCREEN_HEIGHT] {
+        self.sprites_found
+    }
+
+    /// Sprites actually drawn on each scanline (at most 10).
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: get_sprites_drawn_per_line
+    pub fn sprites_drawn_per_line(&self) -> [u8; SCREEN_HEIGHT] {
+        self.sprites_drawn
+    }
+
+    /// Read part of the active BG tilemap as text, mapping each tile index
+    /// through `tile_to_char` (unmapped tiles become spaces). `cols` and
+    /// `rows` are in tiles (0..32); rows are joined with `\n`. Lets tests
+    /// assert on what a ROM printed without comparing pixels.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: read_text_region
+    pub fn read_text_region(
+        &self,
+        memory: &Memory,
+        tile_to_char: &HashMap<u8, char>,
+        cols: Range<u8>,
+        rows: Range<u8>,
+    ) -> String {
+        let lcdc = memory.read_io_direct(io::LCDC);
+        let map_base: u16 = if lcdc & 0x08 != 0 { 0x9C00 } else { 0x9800 };
+        rows.map(|row| {
+            cols.clone()
+                .map(|col| {
+                    let addr = map_base + (row as u16 % 32) * 32 + col as u16 % 32;
+                    let tile = memory.read_vram_bank(0, addr);
+                    tile_to_char.get(&tile).copied().unwrap_or(' ')
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+    }
+
+    /// Dump the on-screen background as reusable GB assets: the 256 tiles
+    /// the BG can address (16 bytes each, in tile-index order, so they load
+    /// at 0x8000 with unsigned addressing whatever LCDC bit 4 says) and the
+    /// 20×18 tilemap window at SCX/SCY (row-major, wrapping at the 32×32 map
+    /// edge). Reads VRAM bank 0 only; CGB attributes are not included.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: export_frame_as_tiles
+    pub fn export_frame_as_tiles(&self, memory: &Memory) -> (Vec<u8>, Vec<u8>) {
+        let lcdc = memory.read_io_direct(io::LCDC);
+        let map_base: u16 = if lcdc & 0x08 != 0 { 0x9C00 } else { 0x9800 };
+        let tile_addr = |index: u8| {
+            if lcdc & 0x10 != 0 {
+                0x8000 + index as u16 * 16
+            } else {
+                (0x9000i32 + (index as i8) as i32 * 16) as u16
+            }
+        };
+
+        let tiles: Vec<u8> = (0..=255u8)
+            .flat_map(|index| (0..16).map(move |b| tile_addr(index) + b))
+            .map(|addr| memory.read_vram_bank(0, addr))
+            .collect();
+
+        let (scx, scy) = (
+            memory.read_io_direct(io::SCX),
+            memory.read_io_direct(io::SCY),
+        );
+        let (col0, row0) = ((scx / 8) as u16, (scy / 8) as u16);
+        let map: Vec<u8> = (0..18u16)
+            .flat_map(|row| (0..20u16).map(move |col| (row, col)))
+            .map(|(row, col)| {
+                let addr = map_base + (row0 + row) % 32 * 32 + (col0 + col) % 32;
+                memory.read_vram_bank(0, addr)
+            })
+            .collect();
+
+        (tiles, map)
+    }
+
     pub fn get_buffer(&self) -> &[u8] {
         &*self.buffer
     }
 
+    /// 160×144 palette indices for the last rendered frame, 1 byte/pixel, for
+    /// compact recording. Expand with [`Ppu::index_to_rgba`].
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: get_index_buffer
+    pub fn get_index_buffer(&self) -> &[u8] {
+        &*self.index_buffer
+    }
+
+    /// Resolve an index-buffer byte to RGBA (before any output LUT or colour
+    /// correction):
+    /// - `0x00..=0x03`: gray shade after BGP/OBP mapping (DMG, or the CGB
+    ///   DMG fallback), drawn from the fixed gray ramp.
+    /// - `0x80 | obj << 5 | palette << 2 | colour`: CGB colour from BG
+    ///   (`obj` = 0) or OBJ palette RAM, looked up in `memory` now.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: index_to_rgba
+    pub fn index_to_rgba(memory: &Memory, index: u8) -> [u8; 4] {
+        Self::resolve_index(memory, index, ColorCorrection::None)
+    }
+
+    /// `index_to_rgba` with CGB colours converted through `correction`.
+    fn resolve_index(memory: &Memory, index: u8, correction: ColorCorrection) -> [u8; 4] {
+        const GRAY: [u8; 4] = [0xFF, 0xAA, 0x55, 0x00];
+        if index & 0x80 == 0 {
+            let g = GRAY[(index & 0x03) as usize];
+            return [g, g, g, 255];
+        }
+        let palette = ((index >> 2) & 0x07) as usize;
+        let color = (index & 0x03) as usize;
+        let (lo, hi) = if index & 0x20 != 0 {
+            memory.read_obj_palette(palette, color)
+        } else {
+            memory.read_bg_palette(palette, color)
+        };
+        rgb555_to_rgba8888(lo, hi, correction)
+    }
+
+    /// Feed mode/timing counters into `h` (see `GameBoyCore::state_checksum`).
+    /// The frame buffer is derived from memory and is not included.
+    pub fn hash_state<H: Hasher>(&self, h: &mut H) {
+        (self.mode as u8).hash(h);
+        self.cycles.hash(h);
+        self.line.hash(h);
+        self.window_line_counter.hash(h);
+        self.drawing_cycles.hash(h);
+        self.stat_line.hash(h);
+        self.lcd_on.hash(h);
+        self.enable_frame.hash(h);
+    }
+
     /// Get current PPU state for debugging.
     #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: ppu_* accessors
     pub fn get_debug_state(&self) -> PpuDebugState {
@@ -364,38 +815,828 @@ mod tests {
         assert!(!ppu.frame_ready(), "flag must clear after first read");
     }
 
+    /// CGB-mode memory with BG palette 0 = white, red, green, blue and
+    /// tile 1 written to both VRAM banks with different rows:
+    /// bank 0 = colour 3 everywhere, bank 1 = row 0 colour 1, row 7 colour 2.
+    fn cgb_attr_memory() -> Memory {
+        let mut mem = Memory::new();
+        mem.load_rom(&vec![0u8; 0x8000], true).unwrap();
+
+        mem.write(0xFF68, 0x80); // BCPS: index 0, auto-increment
+        for b in [0xFF, 0x7F, 0x1F, 0x00, 0xE0, 0x03, 0x00, 0x7C] {
+            mem.write(0xFF69, b);
+        }
+
+        for row in 0..8u16 {
+            mem.write(0x8010 + row * 2, 0xFF);
+            mem.write(0x8011 + row * 2, 0xFF);
+        }
+        mem.write(0xFF4F, 0x01);
+        mem.write(0x8010, 0xFF); // row 0: colour 1
+        mem.write(0x801E, 0x00); // row 7: colour 2
+        mem.write(0x801F, 0xFF);
+        mem.write(0xFF4F, 0x00);
+        mem
+    }
+
+    fn pixel(ppu: &Ppu, x: usize, y: usize) -> [u8; 4] {
+        let i = (y * SCREEN_WIDTH + x) * 4;
+        ppu.buffer[i..i + 4].try_into().unwrap()
+    }
+
+    const RED: [u8; 4] = [0xFF, 0, 0, 0xFF];
+    const GREEN: [u8; 4] = [0, 0xFF, 0, 0xFF];
+    const BLUE: [u8; 4] = [0, 0, 0xFF, 0xFF];
+
+    #[test]
+    fn test_cgb_bg_attr_bank_and_y_flip() {
+        let mut mem = cgb_attr_memory();
+        mem.write(0x9800, 0x01); // tile index (bank 0)
+        mem.write(0xFF4F, 0x01);
+        mem.write(0x9800, 0x48); // attr: Y-flip + tile bank 1
+        mem.write(0xFF4F, 0x00);
+
+        let mut ppu = Ppu::new();
+        ppu.reset(true);
+        ppu.line = 0;
+        ppu.render_scanline(&mem);
+
+        // Line 0 with Y-flip shows bank 1's row 7
+        for x in 0..8 {
+            assert_eq!(pixel(&ppu, x, 0), GREEN, "x={}", x);
+        }
+    }
+
+    #[test]
+    fn test_cgb_bg_attr_bank_0_without_flip() {
+        let mut mem = cgb_attr_memory();
+        mem.write(0x9800, 0x01);
+
+        let mut ppu = Ppu::new();
+        ppu.reset(true);
+        ppu.render_scanline(&mem);
+        assert_eq!(pixel(&ppu, 0, 0), BLUE);
+
+        mem.write(0xFF4F, 0x01);
+        mem.write(0x9800, 0x08); // bank 1, no flip → row 0
+        mem.write(0xFF4F, 0x00);
+        ppu.render_scanline(&mem);
+        assert_eq!(pixel(&ppu, 0, 0), RED);
+    }
+
+    #[test]
+    fn test_cgb_bg_attr_x_flip_and_priority() {
+        let mut mem = cgb_attr_memory();
+        // Bank 1 tile 1 row 0: only leftmost pixel set (colour 1)
+        mem.write(0xFF4F, 0x01);
+        mem.write(0x8010, 0x80);
+        mem.write(0x9800, 0xA8); // BG priority + X-flip + bank 1
+        mem.write(0xFF4F, 0x00);
+        mem.write(0x9800, 0x01);
+
+        let mut ppu = Ppu::new();
+        ppu.reset(true);
+        ppu.render_scanline(&mem);
+        assert_eq!(pixel(&ppu, 7, 0), RED, "X-flip moves pixel 0 to 7");
+        assert_eq!(
+            ppu.scanline_bg_info[7], 0x02,
+            "priority flag, non-zero colour"
+        );
+        assert_eq!(ppu.scanline_bg_info[0], 0x03, "priority flag, colour 0");
+    }
+
+    #[test]
+    fn test_cgb_window_attr_bank_and_y_flip() {
+        let mut mem = cgb_attr_memory();
+        mem.write(0xFF40, 0xF1); // LCD + window (map 0x9C00) + BG
+        mem.write(0xFF4A, 0x00); // WY
+        mem.write(0xFF4B, 0x07); // WX = screen x 0
+        mem.write(0x9C00, 0x01);
+        mem.write(0xFF4F, 0x01);
+        mem.write(0x9C00, 0x48);
+        mem.write(0xFF4F, 0x00);
+
+        let mut ppu = Ppu::new();
+        ppu.reset(true);
+        ppu.render_scanline(&mem);
+        for x in 0..8 {
+            assert_eq!(pixel(&ppu, x, 0), GREEN, "x={}", x);
+        }
+    }
+
+    #[test]
+    fn test_set_sprite_renders_at_offset_position() {
+        let mut mem = Memory::new();
+        mem.load_rom(&vec![0u8; 0x8000], false).unwrap();
+        mem.write(0xFF40, 0x93); // LCD + BG + OBJ, 0x8000 addressing
+        mem.write(0xFF47, 0xE4);
+        mem.write(0xFF48, 0xE4);
+        for b in 0..16 {
+            mem.write(0x8010 + b, 0xFF); // tile 1: solid colour 3
+        }
+        mem.set_sprite(0, 16 + 4, 8 + 10, 1, 0x00); // screen (10, 4)
+        assert_eq!(mem.get_sprite(0), [20, 18, 1, 0]);
+        assert_eq!(&mem.get_oam()[..4], &[20, 18, 1, 0]);
+
+        let mut ppu = Ppu::new();
+        let black = [0x00, 0x00, 0x00, 0xFF];
+        let white = [0xFF, 0xFF, 0xFF, 0xFF];
+        for line in [3u8, 4, 11, 12] {
+            ppu.line = line;
+            ppu.render_scanline(&mem);
+        }
+        assert_eq!(pixel(&ppu, 10, 3), white);
+        assert_eq!(pixel(&ppu, 9, 4), white);
+        assert_eq!(pixel(&ppu, 10, 4), black);
+        assert_eq!(pixel(&ppu, 17, 4), black);
+        assert_eq!(pixel(&ppu, 18, 4), white);
+        assert_eq!(pixel(&ppu, 10, 11), black);
+        assert_eq!(pixel(&ppu, 10, 12), white);
+    }
+
+    #[test]
+    fn test_sprites_per_line_reports_found_and_drawn() {
+        let mut mem = Memory::new();
+        mem.load_rom(&vec![0u8; 0x8000], false).unwrap();
+        mem.write(0xFF40, 0x93);
+        mem.write(0xFF47, 0xE4);
+        mem.write(0xFF48, 0xE4);
+        for b in 0..16 {
+            mem.write(0x8010 + b, 0xFF);
+        }
+        // 12 sprites side by side on lines 50-57
+        for i in 0..12u8 {
+            mem.set_sprite(i, 16 + 50, 8 + i * 8, 1, 0x00);
+        }
+
+        let mut ppu = Ppu::new();
+        for line in [49u8, 50] {
+            ppu.line = line;
+            ppu.render_scanline(&mem);
+        }
+        assert_eq!(ppu.sprites_per_line()[50], 12);
+        assert_eq!(ppu.sprites_drawn_per_line()[50], 10);
+        assert_eq!(ppu.sprites_per_line()[49], 0);
+        // The 11th and 12th sprites in OAM order are dropped
+        assert_eq!(pixel(&ppu, 72, 50), BLACK);
+        assert_eq!(pixel(&ppu, 80, 50), WHITE);
+        assert_eq!(pixel(&ppu, 88, 50), WHITE);
+    }
+
+    /// DMG memory with the window enabled on map 0x9C00: window tile column 0
+    /// is solid black (tile 1), columns 1+ are light gray (tile 2). BG is white.
+    fn dmg_window_memory(wx: u8) -> Memory {
+        let mut mem = Memory::new();
+        mem.load_rom(&vec![0u8; 0x8000], false).unwrap();
+        mem.write(0xFF40, 0xF1); // LCD + window map 0x9C00 + window + BG
+        mem.write(0xFF47, 0xE4);
+        mem.write(0xFF4A, 0x00);
+        mem.write(0xFF4B, wx);
+        for b in 0..16 {
+            mem.write(0x8010 + b, 0xFF); // tile 1: colour 3
+            mem.write(0x8020 + b, if b % 2 == 0 { 0xFF } else { 0x00 }); // tile 2: colour 1
+        }
+        mem.write(0x9C00, 0x01);
+        for col in 1..32 {
+            mem.write(0x9C00 + col, 0x02);
+        }
+        mem
+    }
+
+    #[test]
+    fn test_lcd_ghosting_settles_flicker() {
+        let mut mem = Memory::new();
+        mem.load_rom(&vec![0u8; 0x8000], false).unwrap();
+        mem.write(0xFF40, 0x91); // BG of tile 0 = colour 0 everywhere
+
+        let mut ppu = Ppu::new();
+        let mut shown = Vec::new();
+        for ghosting in [0.0, 0.8] {
+            ppu.set_lcd_ghosting(ghosting);
+            shown.clear();
+            for frame in 0..40 {
+                // Alternate the whole screen black / white each frame
+                mem.write(0xFF47, if frame % 2 == 0 { 0xFF } else { 0x00 });
+                ppu.line = 0;
+                ppu.render_scanline(&mem);
+                shown.push(pixel(&ppu, 0, 0)[0]);
+            }
+            if ghosting == 0.0 {
+                assert_eq!(&shown[38..], &[0x00, 0xFF]);
+            }
+        }
+        // Steady state alternates between ~113 and ~142 instead of 0/255
+        for &v in &shown[30..] {
+            assert!((100..=160).contains(&v), "{:?}", &shown[30..]);
+        }
+    }
+
+    #[test]
+    fn test_lcd_ghosting_decays_level_not_rgb() {
+        // CGB BG palette 0: colour 0 red, colour 3 blue
+        let mut mem = Memory::new();
+        mem.load_rom(&vec![0u8; 0x8000], true).unwrap();
+        mem.write(0xFF40, 0x91);
+        let set_colors = |mem: &mut Memory, c0: [u8; 2], c3: [u8; 2]| {
+            mem.write(0xFF68, 0x80);
+            for b in [c0[0], c0[1], 0xFF, 0x7F, 0xFF, 0x7F, c3[0], c3[1]] {
+                mem.write(0xFF69, b);
+            }
+        };
+        set_colors(&mut mem, [0x1F, 0x00], [0x00, 0x7C]);
+
+        let mut ppu = Ppu::new();
+        ppu.reset(true);
+        ppu.set_lcd_ghosting(0.8);
+        for _ in 0..10 {
+            ppu.line = 0;
+            ppu.render_scanline(&mem);
+        }
+        assert_eq!(pixel(&ppu, 0, 0), RED);
+
+        // A palette change keeps the level, so the new colour shows at once
+        set_colors(&mut mem, [0xE0, 0x03], [0x00, 0x7C]);
+        ppu.render_scanline(&mem);
+        assert_eq!(pixel(&ppu, 0, 0), [0, 0xFF, 0, 0xFF]);
+
+        // A level change moves along the palette ramp (0.6 of the way from
+        // colour 0, now green, to colour 1, white), not straight to blue
+        mem.write(0x8000, 0xFF);
+        mem.write(0x8001, 0xFF); // tile 0 row 0 = colour 3
+        ppu.render_scanline(&mem);
+        assert_eq!(pixel(&ppu, 0, 0), [0x99, 0xFF, 0x99, 0xFF]);
+    }
+
+    fn render_line0(mem: &Memory, cgb: bool) -> Ppu {
+        let mut ppu = Ppu::new();
+        ppu.reset(cgb);
+        ppu.render_scanline(mem);
+        ppu
+    }
+
+    const BLACK: [u8; 4] = [0x00, 0x00, 0x00, 0xFF];
+    const LIGHT: [u8; 4] = [0xAA, 0xAA, 0xAA, 0xFF];
+    const WHITE: [u8; 4] = [0xFF, 0xFF, 0xFF, 0xFF];
+
+    #[test]
+    fn test_hblank_stat_requested_once_per_line() {
+        let mut mem = Memory::new();
+        mem.load_rom(&vec![0u8; 0x8000], false).unwrap();
+        mem.write(0xFF40, 0x91);
+        mem.write_io_direct(io::STAT, 0x08); // mode-0 source only
+        mem.write_io_direct(io::IF, 0x00);
+        let ic = InterruptController::new();
+        let mut ppu = Ppu::new();
+
+        // Stop 4 dots short of line 1 so only line 0's H-blank is seen
+        let mut requests = 0;
+        for _ in 0..SCANLINE_CYCLES / 4 - 1 {
+            ppu.tick(4, &mut mem, &ic);
+            if mem.read_io_direct(io::IF) & 0x02 != 0 {
+                requests += 1;
+                mem.write_io_direct(io::IF, 0x00);
+            }
+        }
+        assert_eq!(ppu.mode, PpuMode::HBlank);
+        assert_eq!(requests, 1);
+    }
+
+    #[test]
+    fn test_lyc_stat_requested_once_while_held() {
+        let mut mem = Memory::new();
+        mem.load_rom(&vec![0u8; 0x8000], false).unwrap();
+        mem.write(0xFF40, 0x91);
+        mem.write_io_direct(io::LYC, 0);
+        mem.write_io_direct(io::STAT, 0x48); // LYC and mode-0 sources
+        mem.write_io_direct(io::IF, 0x00);
+        let ic = InterruptController::new();
+        let mut ppu = Ppu::new();
+
+        // LY=LYC holds for all of line 0; H-blank turning on while the line
+        // is already high must not interrupt again
+        let mut requests = 0;
+        for _ in 0..SCANLINE_CYCLES / 4 - 1 {
+            ppu.tick(4, &mut mem, &ic);
+            if mem.read_io_direct(io::IF) & 0x02 != 0 {
+                requests += 1;
+                mem.write_io_direct(io::IF, 0x00);
+            }
+        }
+        assert_eq!(ppu.mode, PpuMode::HBlank);
+        assert_ne!(mem.read_io_direct(io::STAT) & 0x04, 0);
+        assert_eq!(requests, 1);
+    }
+
+    #[test]
+    fn test_lcd_reenable_starts_fresh_frame() {
+        let mut mem = Memory::new();
+        mem.load_rom(&vec![0u8; 0x8000], false).unwrap();
+        mem.write(0xFF40, 0x91);
+        mem.write_io_direct(io::LYC, 0);
+        mem.write_io_direct(io::STAT, 0x40);
+        mem.write_io_direct(io::IF, 0x00);
+        let ic = InterruptController::new();
+        let mut ppu = Ppu::new();
+
+        // Switch off part-way through line 5 and back on
+        for _ in 0..(5 * SCANLINE_CYCLES + 300) / 4 {
+            ppu.tick(4, &mut mem, &ic);
+        }
+        mem.write(0xFF40, 0x11);
+        ppu.tick(4, &mut mem, &ic);
+        assert_eq!(mem.read_io_direct(io::LY), 0);
+        mem.write(0xFF40, 0x91);
+        mem.write_io_direct(io::IF, 0x00);
+
+        // Line 0 starts in mode 2 with no coincidence flag or STAT request
+        ppu.tick(4, &mut mem, &ic);
+        assert_eq!(ppu.mode, PpuMode::OamScan);
+        assert_eq!(mem.read_io_direct(io::STAT) & 0x07, 0x02);
+        assert_eq!(mem.read_io_direct(io::IF) & 0x02, 0);
+
+        // LY advances on full 456-dot lines from the enable point; V-blank
+        // arrives at line 144, not early
+        let mut dots = 4;
+        while mem.read_io_direct(io::IF) & 0x01 == 0 {
+            ppu.tick(4, &mut mem, &ic);
+            dots += 4;
+            let ly = mem.read_io_direct(io::LY) as u32;
+            assert_eq!(ly, dots / SCANLINE_CYCLES, "LY at dot {}", dots);
+        }
+        assert_eq!(dots, 144 * SCANLINE_CYCLES);
+        assert_eq!(ppu.mode, PpuMode::VBlank);
+        assert!(ppu.frame_ready());
+    }
+
+    /// (dot, mode) changes over line 0 and into line 1.
+    fn line0_mode_changes(mem: &mut Memory) -> Vec<(u64, u8)> {
+        let ic = InterruptController::new();
+        let mut ppu = Ppu::new();
+        ppu.set_mode_trace(true);
+        for _ in 0..SCANLINE_CYCLES / 4 {
+            ppu.tick(4, mem, &ic);
+        }
+        ppu.mode_trace().iter().map(|e| (e.dot, e.mode)).collect()
+    }
+
+    #[test]
+    fn test_mode3_length_follows_scx() {
+        let mut mem = Memory::new();
+        mem.load_rom(&vec![0u8; 0x8000], false).unwrap();
+        mem.write(0xFF40, 0x91);
+        assert_eq!(line0_mode_changes(&mut mem), [(80, 3), (252, 0), (456, 2)]);
+
+        mem.write(0xFF43, 5);
+        assert_eq!(
+            line0_mode_changes(&mut mem),
+            [(80, 3), (257, 0), (456, 2)],
+            "SCX & 7 lengthens mode 3; H-blank shrinks to match"
+        );
+        mem.write(0xFF43, 8);
+        assert_eq!(
+            line0_mode_changes(&mut mem)[1],
+            (252, 0),
+            "whole tiles cost nothing"
+        );
+    }
+
+    #[test]
+    fn test_mode3_length_follows_sprite_count() {
+        let mut mem = Memory::new();
+        mem.load_rom(&vec![0u8; 0x8000], false).unwrap();
+        mem.write(0xFF40, 0x93); // OBJ on
+        assert_eq!(line0_mode_changes(&mut mem)[1], (252, 0), "no sprites");
+
+        for i in 0..12u16 {
+            mem.write(0xFE00 + i * 4, 16); // Y: covers line 0
+            mem.write(0xFE01 + i * 4, 8 + i as u8 * 8);
+        }
+        assert_eq!(
+            line0_mode_changes(&mut mem),
+            [(80, 3), (252 + 60, 0), (456, 2)],
+            "10 sprites fetched (the 11th and 12th are dropped)"
+        );
+
+        mem.write(0xFF40, 0x91); // OBJ off: nothing fetched
+        assert_eq!(line0_mode_changes(&mut mem)[1], (252, 0));
+
+        mem.write(0xFF40, 0xB1); // window from line 0
+        mem.write(0xFF4A, 0);
+        mem.write(0xFF4B, 7);
+        assert_eq!(line0_mode_changes(&mut mem)[1], (258, 0), "window start");
+    }
+
+    #[test]
+    fn test_mode_trace_one_scanline() {
+        let mut mem = Memory::new();
+        mem.load_rom(&vec![0u8; 0x8000], false).unwrap();
+        mem.write(0xFF40, 0x91);
+        let ic = InterruptController::new();
+        let mut ppu = Ppu::new();
+        ppu.set_mode_trace(true);
+
+        for _ in 0..SCANLINE_CYCLES / 4 {
+            ppu.tick(4, &mut mem, &ic);
+        }
+        let modes = |events: &[ModeEvent]| {
+            events
+                .iter()
+                .map(|e| (e.dot, e.line, e.mode))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            modes(&ppu.mode_trace()),
+            vec![(80, 0, 3), (252, 0, 0), (456, 1, 2)],
+            "OAM -> Drawing -> HBlank -> next line's OAM"
+        );
+
+        // Coarse ticks overshoot the boundary but events are backdated
+        ppu.set_mode_trace(true);
+        ppu.tick(100, &mut mem, &ic);
+        assert_eq!(modes(&ppu.mode_trace()), vec![(80, 1, 3)]);
+
+        ppu.set_mode_trace(false);
+        ppu.tick(400, &mut mem, &ic);
+        assert!(ppu.mode_trace().is_empty());
+    }
+
+    #[test]
+    fn test_read_text_region() {
+        let mut mem = Memory::new();
+        mem.load_rom(&vec![0u8; 0x8000], false).unwrap();
+        mem.write(0xFF40, 0x99); // BG map at 0x9C00
+        // Font: tiles 0x41.. are 'A'.., tile 0 is blank
+        let font: HashMap<u8, char> = (b'A'..=b'Z').map(|c| (c, c as char)).collect();
+        for (i, &c) in b"PASSED".iter().enumerate() {
+            mem.write(0x9C00 + 33 + i as u16, c); // row 1, col 1
+        }
+        mem.write(0x9C00 + 65, b'O');
+        mem.write(0x9C00 + 66, b'K');
+
+        let ppu = Ppu::new();
+        assert_eq!(ppu.read_text_region(&mem, &font, 1..7, 1..2), "PASSED");
+        assert_eq!(ppu.read_text_region(&mem, &font, 0..4, 1..3), " PAS\n OK ");
+
+        // The other map is blank
+        mem.write(0xFF40, 0x91);
+        assert_eq!(ppu.read_text_region(&mem, &font, 1..7, 1..2), "      ");
+    }
+
+    #[test]
+    fn test_export_frame_as_tiles() {
+        let mut mem = Memory::new();
+        mem.load_rom(&vec![0u8; 0x8000], false).unwrap();
+        mem.write(0xFF40, 0x91);
+        let tile_data: Vec<u8> = (0..64).map(|i| (i * 3 + 1) as u8).collect();
+        for (i, &b) in tile_data.iter().enumerate() {
+            mem.write(0x8000 + i as u16, b); // tiles 0-3
+        }
+        for i in 0..32 * 32 {
+            mem.write(0x9800 + i, (i % 4) as u8);
+        }
+        mem.write(0x9800 + 33, 2);
+
+        let ppu = Ppu::new();
+        let (tiles, map) = ppu.export_frame_as_tiles(&mem);
+        assert_eq!(tiles.len(), 256 * 16);
+        assert_eq!(tiles[..64], tile_data[..]);
+        assert_eq!(map.len(), 20 * 18);
+        assert_eq!(map[..4], [0, 1, 2, 3]);
+        assert_eq!(map[21], 2, "row 1, col 1");
+
+        // Scrolled by one tile each way: the window moves with SCX/SCY
+        mem.write(0xFF42, 8);
+        mem.write(0xFF43, 8);
+        let (_, map) = ppu.export_frame_as_tiles(&mem);
+        assert_eq!(map[0], 2);
+        assert_eq!(map[1], 2, "col 2 of the map");
+
+        // 0x8800 addressing: index 0 is read from 0x9000
+        mem.write(0xFF40, 0x81);
+        for b in 0..16 {
+            mem.write(0x9000 + b, 0xA5);
+        }
+        let (tiles, _) = ppu.export_frame_as_tiles(&mem);
+        assert_eq!(tiles[..16], [0xA5; 16]);
+        assert_eq!(
+            tiles[0x80 * 16..0x80 * 16 + 16],
+            [0; 16],
+            "index 0x80 is 0x8800"
+        );
+    }
+
+    #[test]
+    fn test_sprites_ignore_lcdc_tile_data_select() {
+        for cgb in [false, true] {
+            let mut mem = Memory::new();
+            mem.load_rom(&vec![0u8; 0x8000], cgb).unwrap();
+            mem.write(0xFF40, 0x83); // LCD + OBJ + BG, BG tiles from 0x8800
+            mem.write(0xFF47, 0xE4);
+            mem.write(0xFF48, 0xE4);
+            for b in 0..16 {
+                mem.write(0x8000 + b, 0xFF); // 0x8000 tile 0: colour 3
+                mem.write(0x9000 + b, if b % 2 == 0 { 0xFF } else { 0x00 }); // signed tile 0: colour 1
+            }
+            mem.set_sprite(0, 16, 8, 0x00, 0x00); // top-left, tile 0
+
+            let mut ppu = Ppu::new();
+            ppu.reset(cgb);
+            ppu.set_cgb_dmg_fallback(true);
+            ppu.render_scanline(&mem);
+            assert_eq!(pixel(&ppu, 0, 0), BLACK, "sprite uses 0x8000 (cgb={})", cgb);
+            assert_eq!(pixel(&ppu, 8, 0), LIGHT, "BG uses 0x9000 (cgb={})", cgb);
+        }
+    }
+
+    #[test]
+    fn test_dmg_sprite_priority_by_x_then_oam_index() {
+        for cgb in [false, true] {
+            let mut mem = Memory::new();
+            mem.load_rom(&vec![0u8; 0x8000], cgb).unwrap();
+            mem.write(0xFF40, 0x83);
+            mem.write(0xFF48, 0xE4);
+            for b in 0..16 {
+                mem.write(0x8000 + b, 0xFF); // tile 0: colour 3
+                mem.write(0x8010 + b, if b % 2 == 0 { 0xFF } else { 0x00 }); // tile 1: colour 1
+            }
+            // OAM 0 further right, OAM 1 further left; they overlap at x=4..9
+            mem.set_sprite(0, 16, 12, 0x01, 0x00);
+            mem.set_sprite(1, 16, 10, 0x00, 0x00);
+
+            let mut ppu = Ppu::new();
+            ppu.reset(cgb);
+            ppu.set_cgb_dmg_fallback(true);
+            ppu.render_scanline(&mem);
+            let (winner, label) = if cgb {
+                (LIGHT, "CGB: OAM 0")
+            } else {
+                (BLACK, "DMG: left X")
+            };
+            assert_eq!(pixel(&ppu, 4, 0), winner, "{} wins", label);
+            assert_eq!(pixel(&ppu, 2, 0), BLACK, "only OAM 1 (cgb={})", cgb);
+            assert_eq!(pixel(&ppu, 10, 0), LIGHT, "only OAM 0 (cgb={})", cgb);
+
+            // Same X: the lower OAM index wins on both
+            mem.set_sprite(1, 16, 12, 0x00, 0x00);
+            ppu.render_scanline(&mem);
+            assert_eq!(pixel(&ppu, 4, 0), LIGHT, "tie goes to OAM 0 (cgb={})", cgb);
+        }
+    }
+
+    #[test]
+    fn test_signed_tile_data_addressing() {
+        let mut mem = Memory::new();
+        mem.load_rom(&vec![0u8; 0x8000], false).unwrap();
+        mem.write(0xFF47, 0xE4);
+        mem.write(0xFF4A, 0x00);
+        mem.write(0xFF4B, 0x07);
+        for b in 0..16 {
+            mem.write(0x9000 + b, 0xFF); // signed index 0: colour 3
+            mem.write(0x8800 + b, if b % 2 == 0 { 0xFF } else { 0x00 }); // index 0x80: colour 1
+        }
+        mem.write(0x9800, 0x00);
+        mem.write(0x9801, 0x80);
+
+        // BG, then the window over the same map, with 0x8800 addressing
+        for lcdc in [0x81, 0xA1] {
+            mem.write(0xFF40, lcdc);
+            let ppu = render_line0(&mem, false);
+            assert_eq!(pixel(&ppu, 0, 0), BLACK, "lcdc={:02X}", lcdc);
+            assert_eq!(pixel(&ppu, 8, 0), LIGHT, "lcdc={:02X}", lcdc);
+        }
+
+        // 0x8000 addressing: index 0 is the empty tile at 0x8000, 0x80 is still 0x8800
+        for lcdc in [0x91, 0xB1] {
+            mem.write(0xFF40, lcdc);
+            let ppu = render_line0(&mem, false);
+            assert_eq!(pixel(&ppu, 0, 0), WHITE, "lcdc={:02X}", lcdc);
+            assert_eq!(pixel(&ppu, 8, 0), LIGHT, "lcdc={:02X}", lcdc);
+        }
+    }
+
+    #[test]
+    fn test_window_wx0_clips_left_edge() {
+        let ppu = render_line0(&dmg_window_memory(0), false);
+        // Window starts 7px left of the screen: only column 7 of tile 1 is visible
+        assert_eq!(pixel(&ppu, 0, 0), BLACK);
+        for x in 1..SCREEN_WIDTH {
+            assert_eq!(pixel(&ppu, x, 0), LIGHT, "x={}", x);
+        }
+        assert_eq!(ppu.window_line_counter, 1);
+    }
+
+    #[test]
+    fn test_window_wx7_starts_at_column_0() {
+        let ppu = render_line0(&dmg_window_memory(7), false);
+        for x in 0..8 {
+            assert_eq!(pixel(&ppu, x, 0), BLACK, "x={}", x);
+        }
+        assert_eq!(pixel(&ppu, 8, 0), LIGHT);
+        assert_eq!(pixel(&ppu, SCREEN_WIDTH - 1, 0), LIGHT);
+    }
+
+    #[test]
+    fn test_window_wx167_hidden() {
+        let ppu = render_line0(&dmg_window_memory(167), false);
+        for x in 0..SCREEN_WIDTH {
+            assert_eq!(pixel(&ppu, x, 0), WHITE, "x={}", x);
+        }
+        assert_eq!(ppu.window_line_counter, 0, "hidden window does not advance");
+    }
+
+    #[test]
+    fn test_window_wx166_shows_last_column() {
+        let ppu = render_line0(&dmg_window_memory(166), false);
+        assert_eq!(pixel(&ppu, SCREEN_WIDTH - 2, 0), WHITE);
+        assert_eq!(pixel(&ppu, SCREEN_WIDTH - 1, 0), BLACK);
+    }
+
     #[test]
-    fn test_rgb555_black() {
-        let rgba = Ppu::rgb555_to_rgba(0x00, 0x00);
-        assert_eq!(rgba, [0, 0, 0, 255]);
+    fn test_cgb_window_wx0_clips_left_edge() {
+        let mut mem = cgb_attr_memory();
+        mem.write(0xFF40, 0xF1);
+        mem.write(0xFF4A, 0x00);
+        mem.write(0xFF4B, 0x00);
+        mem.write(0x9C00, 0x01); // tile 1 bank 0: colour 3 (blue); col 1+ tile 0 (white)
+
+        let ppu = render_line0(&mem, true);
+        assert_eq!(pixel(&ppu, 0, 0), BLUE);
+        assert_eq!(pixel(&ppu, 1, 0), WHITE);
+
+        mem.write(0xFF4B, 167);
+        mem.write(0x9800, 0x01); // BG would show blue only where the window isn't
+        let ppu = render_line0(&mem, true);
+        assert_eq!(pixel(&ppu, 1, 0), BLUE, "BG visible: window hidden");
+        assert_eq!(pixel(&ppu, 8, 0), WHITE);
+    }
+
+    fn identity_lut() -> [[u8; 4]; 256] {
+        std::array::from_fn(|v| [v as u8; 4])
+    }
+
+    #[test]
+    fn test_output_lut_identity_is_noop() {
+        let mem = cgb_attr_memory();
+        let mut plain = Ppu::new();
+        plain.reset(true);
+        plain.render_scanline(&mem);
+
+        let mut lut = Ppu::new();
+        lut.set_output_lut(Some(identity_lut()));
+        lut.reset(true);
+        lut.render_scanline(&mem);
+        assert_eq!(
+            &plain.buffer[..SCREEN_WIDTH * 4],
+            &lut.buffer[..SCREEN_WIDTH * 4]
+        );
+    }
+
+    #[test]
+    fn test_output_lut_swaps_red_and_blue() {
+        let mut mem = cgb_attr_memory();
+        mem.write(0x9800, 0x01);
+        mem.write(0xFF4F, 0x01);
+        mem.write(0x9800, 0x08); // bank 1 row 0: colour 1 (red)
+        mem.write(0xFF4F, 0x00);
+
+        // Invert R and B, keep G: pure red <-> pure blue
+        let lut = std::array::from_fn(|v| [255 - v as u8, v as u8, 255 - v as u8, v as u8]);
+        let mut ppu = Ppu::new();
+        ppu.reset(true);
+        ppu.set_output_lut(Some(lut));
+        ppu.render_scanline(&mem);
+        assert_eq!(pixel(&ppu, 0, 0), BLUE);
+        assert_eq!(pixel(&ppu, 8, 0), GREEN, "white keeps only green");
+
+        ppu.set_output_lut(None);
+        ppu.render_scanline(&mem);
+        assert_eq!(pixel(&ppu, 0, 0), RED);
+    }
+
+    #[test]
+    fn test_cgb_dmg_fallback_uses_bgp_until_palette_written() {
+        // DMG ROM forced into CGB mode: palette RAM never written (all zero)
+        let mut mem = Memory::new();
+        mem.load_rom(&vec![0u8; 0x8000], true).unwrap();
+        mem.write(0xFF40, 0x91);
+        mem.write(0xFF47, 0xE4);
+        for row in 0..8u16 {
+            mem.write(0x8000 + row * 2, 0xFF); // tile 0: colour 1
+        }
+
+        let ppu = render_line0(&mem, true);
+        assert_eq!(pixel(&ppu, 0, 0), BLACK, "zeroed palette RAM renders black");
+
+        let mut ppu = Ppu::new();
+        ppu.set_cgb_dmg_fallback(true);
+        ppu.reset(true);
+        ppu.render_scanline(&mem);
+        assert!((0..SCREEN_WIDTH).all(|x| pixel(&ppu, x, 0) == LIGHT));
+
+        // Once the ROM sets up colour palettes, palette RAM wins again
+        mem.write(0xFF68, 0x82); // BG palette 0, colour 1
+        mem.write(0xFF69, 0x1F);
+        mem.write(0xFF69, 0x00);
+        ppu.render_scanline(&mem);
+        assert_eq!(pixel(&ppu, 0, 0), RED);
     }
 
     #[test]
-    fn test_rgb555_white() {
-        // RGB555 white: R=31 G=31 B=31
-        // lo = 0xFF (R[4:0]=11111, G[2:0]=111 in bits 7:5)
-        // hi = 0x7F (G[4:3]=11 in bits 1:0, B[4:0]=11111 in bits 6:2)
-        let rgba = Ppu::rgb555_to_rgba(0xFF, 0x7F);
-        assert_eq!(rgba, [255, 255, 255, 255]);
+    fn test_scanline_callback_sees_per_line_scx() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let mut mem = Memory::new();
+        mem.load_rom(&vec![0u8; 0x8000], false).unwrap();
+        mem.write(0xFF40, 0x91);
+
+        let seen: Rc<RefCell<Vec<(u8, u8)>>> = Rc::default();
+        let log = Rc::clone(&seen);
+        let mut ppu = Ppu::new();
+        ppu.set_scanline_callback(Some(Box::new(move |ly, io: &IoSnapshot| {
+            log.borrow_mut().push((ly, io.scx));
+        })));
+
+        for line in 0..4u8 {
+            mem.write(0xFF43, line * 8); // SCX changed between scanlines
+            ppu.line = line;
+            ppu.render_scanline(&mem);
+        }
+        assert_eq!(*seen.borrow(), vec![(0, 0), (1, 8), (2, 16), (3, 24)]);
+
+        ppu.set_scanline_callback(None);
+        ppu.render_scanline(&mem);
+        assert_eq!(seen.borrow().len(), 4);
     }
 
     #[test]
-    fn test_rgb555_pure_red() {
-        // R=31, G=0, B=0 → lo = 0x1F, hi = 0x00
-        let rgba = Ppu::rgb555_to_rgba(0x1F, 0x00);
-        assert_eq!(rgba[0], 0xFF, "red channel");
-        assert_eq!(rgba[1], 0x00, "green channel");
-        assert_eq!(rgba[2], 0x00, "blue channel");
-        assert_eq!(rgba[3], 0xFF, "alpha");
+    fn test_index_buffer_reproduces_rgba() {
+        // CGB: BG colours from palette RAM plus a sprite from OBJ palette 2
+        let mut mem = cgb_attr_memory();
+        mem.write(0xFF40, 0x93); // LCD + BG + OBJ, 0x8000 tile data
+        mem.write(0x9800, 0x01);
+        mem.write(0x9801, 0x01);
+        mem.write(0xFF4F, 0x01);
+        mem.write(0x9801, 0x08); // second tile from bank 1
+        mem.write(0xFF4F, 0x00);
+        mem.write(0xFF6A, 0x80 | 0x12); // OBJ palette 2, colour 1
+        for b in [0x1F, 0x00, 0xE0, 0x03, 0x00, 0x7C] {
+            mem.write(0xFF6B, b);
+        }
+        mem.set_sprite(0, 16, 24, 1, 0x02);
+
+        let mut ppu = Ppu::new();
+        ppu.reset(true);
+        for line in 0..8u8 {
+            ppu.line = line;
+            ppu.render_scanline(&mem);
+        }
+        assert_ne!(pixel(&ppu, 0, 0), pixel(&ppu, 8, 0));
+        assert_eq!(
+            ppu.get_index_buffer()[16],
+            0xA0 | 2 << 2 | 3,
+            "OBJ palette 2 colour 3"
+        );
+        for y in 0..8 {
+            for x in 0..SCREEN_WIDTH {
+                let idx = ppu.get_index_buffer()[y * SCREEN_WIDTH + x];
+                assert_eq!(
+                    Ppu::index_to_rgba(&mem, idx),
+                    pixel(&ppu, x, y),
+                    "({x},{y})"
+                );
+            }
+        }
+
+        // DMG: indices are post-BGP gray shades
+        let mem = dmg_window_memory(7);
+        let ppu = render_line0(&mem, false);
+        assert_eq!(ppu.get_index_buffer()[0], 3);
+        for x in 0..SCREEN_WIDTH {
+            let idx = ppu.get_index_buffer()[x];
+            assert_eq!(Ppu::index_to_rgba(&mem, idx), pixel(&ppu, x, 0));
+        }
     }
 
     #[test]
-    fn test_rgb555_pure_blue() {
-        // R=0, G=0, B=31 → lo = 0x00, hi = 0x7C (B[4:0]=11111 in bits 6:2)
-        let rgba = Ppu::rgb555_to_rgba(0x00, 0x7C);
-        assert_eq!(rgba[0], 0x00, "red");
-        assert_eq!(rgba[1], 0x00, "green");
-        assert_eq!(rgba[2], 0xFF, "blue");
-        assert_eq!(rgba[3], 0xFF, "alpha");
+    fn test_hash_state_covers_timing_and_stat_fields() {
+        fn hash(ppu: &Ppu) -> u64 {
+            let mut h = std::collections::hash_map::DefaultHasher::new();
+            ppu.hash_state(&mut h);
+            h.finish()
+        }
+        type Toggle = fn(&mut Ppu);
+        let base = Ppu::new();
+        let toggles: [(&str, Toggle); 4] = [
+            ("drawing_cycles", |p| p.drawing_cycles += 4),
+            ("stat_line", |p| p.stat_line = !p.stat_line),
+            ("lcd_on", |p| p.lcd_on = !p.lcd_on),
+            ("enable_frame", |p| p.enable_frame = !p.enable_frame),
+        ];
+        for (name, toggle) in toggles {
+            let mut ppu = Ppu::new();
+            toggle(&mut ppu);
+            assert_ne!(hash(&ppu), hash(&base), "{name} is hashed");
+        }
     }
 }
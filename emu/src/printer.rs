@@ -0,0 +1,328 @@
+//! Game Boy Printer, as a peer on the serial link.
+//!
+//! The Game Boy clocks every exchange. A packet is the magic bytes 0x88 0x33,
+//! a command, a compression flag, a little-endian payload length, the
+//! payload, and a little-endian checksum (the sum of every byte from the
+//! command through the payload). The printer answers 0x00 throughout, then
+//! 0x81 ("alive") and its status byte for the two trailing bytes the Game
+//! Boy sends. DATA packets accumulate 2bpp tiles, 20 per 160-pixel row;
+//! PRINT turns them into an image on the paper strip.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::graphics::{GRAY, unpack_2bpp_tile};
+use crate::serial::SerialLink;
+
+const MAGIC: [u8; 2] = [0x88, 0x33];
+const ALIVE: u8 = 0x81;
+
+const CMD_INIT: u8 = 0x01;
+const CMD_PRINT: u8 = 0x02;
+const CMD_DATA: u8 = 0x04;
+const CMD_STATUS: u8 = 0x0F;
+
+// Status byte bits
+const STATUS_CHECKSUM_ERROR: u8 = 0x01;
+const STATUS_PRINTING: u8 = 0x02;
+const STATUS_FULL: u8 = 0x04;
+const STATUS_UNPROCESSED: u8 = 0x08;
+
+/// Paper width in pixels (20 tiles).
+pub const PRINTER_WIDTH: usize = 160;
+const TILES_PER_ROW: usize = PRINTER_WIDTH / 8;
+/// Image RAM: nine DATA packets of two tile rows, a full 160×144 screen.
+const BUFFER_SIZE: usize = 9 * 640;
+
+/// Where the printer is within the packet it is receiving.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum State {
+    Magic0,
+    Magic1,
+    Command,
+    Compression,
+    LengthLo,
+    LengthHi,
+    Payload,
+    ChecksumLo,
+    ChecksumHi,
+    Alive,
+    Status,
+}
+
+pub struct Printer {
+    state: State,
+    command: u8,
+    compressed: bool,
+    length: usize,
+    payload: Vec<u8>,
+    checksum: u16,
+    received_checksum: u16,
+    status: u8,
+    /// Decompressed tile data received since the last INIT or PRINT.
+    buffer: Vec<u8>,
+    /// Printed shades (0 = white .. 3 = black), `PRINTER_WIDTH` per row.
+    paper: Vec<u8>,
+    /// The last print fed paper after itself, so the next one starts a new sheet.
+    fed_after_last: bool,
+}
+
+impl Printer {
+    pub fn new() -> Self {
+        Printer {
+            state: State::Magic0,
+            command: 0,
+            compressed: false,
+            length: 0,
+            payload: Vec::new(),
+            checksum: 0,
+            received_checksum: 0,
+            status: 0,
+            buffer: Vec::with_capacity(BUFFER_SIZE),
+            paper: Vec::new(),
+            fed_after_last: true,
+        }
+    }
+
+    /// Height in pixels of the current printout (0 before the first print).
+    pub fn image_height(&self) -> usize {
+        self.paper.len() / PRINTER_WIDTH
+    }
+
+    /// The current printout as `PRINTER_WIDTH × image_height()` RGBA. Prints
+    /// with no paper fed in between (both margins 0) continue one sheet.
+    pub fn image_rgba(&self) -> Vec<u8> {
+        self.paper
+            .iter()
+            .flat_map(|&shade| {
+                let gray = GRAY[shade as usize];
+                [gray, gray, gray, 255]
+            })
+            .collect()
+    }
+
+    /// Run the packet's command once its checksum has arrived.
+    fn execute(&mut self) {
+        if self.checksum != self.received_checksum {
+            self.status |= STATUS_CHECKSUM_ERROR;
+            return;
+        }
+        self.status &= !STATUS_CHECKSUM_ERROR;
+
+        match self.command {
+            CMD_INIT => {
+                self.buffer.clear();
+                self.status = 0;
+            }
+            CMD_DATA => {
+                let data = if self.compressed {
+                    decompress(&self.payload)
+                } else {
+                    std::mem::take(&mut self.payload)
+                };
+                let room = BUFFER_SIZE - self.buffer.len();
+                self.buffer.extend_from_slice(&data[..data.len().min(room)]);
+                if !self.buffer.is_empty() {
+                    self.status |= STATUS_UNPROCESSED;
+                }
+                if self.buffer.len() == BUFFER_SIZE {
+                    self.status |= STATUS_FULL;
+                }
+            }
+            CMD_PRINT if self.payload.len() >= 4 => {
+                let (sheets, margins, palette) =
+                    (self.payload[0], self.payload[1], self.payload[2]);
+                if sheets > 0 {
+                    self.print(margins, palette);
+                }
+                self.buffer.clear();
+                self.status &= !(STATUS_UNPROCESSED | STATUS_FULL);
+                self.status |= STATUS_PRINTING;
+            }
+            _ => {} // STATUS (and unknown commands) only report
+        }
+    }
+
+    /// Render the buffered tiles onto the paper. `margins` holds the lines
+    /// fed before (high nibble) and after (low nibble); `palette` maps
+    /// colour indices to shades like BGP (0 means the default 0xE4).
+    fn print(&mut self, margins: u8, palette: u8) {
+        if margins >> 4 != 0 || self.fed_after_last {
+            self.paper.clear();
+        }
+        self.fed_after_last = margins & 0x0F != 0;
+
+        let palette = if palette == 0 { 0xE4 } else { palette };
+        let tile_rows = self.buffer.len() / (TILES_PER_ROW * 16);
+        let top = self.paper.len();
+        self.paper.resize(top + tile_rows * 8 * PRINTER_WIDTH, 0);
+
+        for (i, tile) in self
+            .buffer
+            .chunks_exact(16)
+            .take(tile_rows * TILES_PER_ROW)
+            .enumerate()
+        {
+            let (tile_x, tile_y) = (i % TILES_PER_ROW, i / TILES_PER_ROW);
+            let tile: &[u8; 16] = tile.try_into().unwrap();
+            for (p, &color) in unpack_2bpp_tile(tile).iter().enumerate() {
+                let row = tile_y * 8 + p / 8;
+                let col = tile_x * 8 + p % 8;
+                self.paper[top + row * PRINTER_WIDTH + col] = (palette >> (color * 2)) & 0x03;
+            }
+        }
+    }
+}
+
+/// Expand the printer's run-length encoding: a control byte with bit 7 set
+/// repeats the next byte `(n & 0x7F) + 2` times, otherwise `n + 1` literal
+/// bytes follow.
+fn decompress(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(BUFFER_SIZE);
+    let mut i = 0;
+    while i < data.len() {
+        let control = data[i];
+        i += 1;
+        if control & 0x80 != 0 {
+            let Some(&byte) = data.get(i) else { break };
+            out.extend(std::iter::repeat_n(byte, (control & 0x7F) as usize + 2));
+            i += 1;
+        } else {
+            let end = (i + control as usize + 1).min(data.len());
+            out.extend_from_slice(&data[i..end]);
+            i = end;
+        }
+    }
+    out
+}
+
+impl SerialLink for Printer {
+    fn exchange(&mut self, out: u8) -> u8 {
+        let mut reply = 0x00;
+        self.state = match self.state {
+            State::Magic0 if out == MAGIC[0] => State::Magic1,
+            State::Magic0 => State::Magic0,
+            State::Magic1 if out == MAGIC[1] => State::Command,
+            State::Magic1 if out == MAGIC[0] => State::Magic1,
+            State::Magic1 => State::Magic0,
+            State::Command => {
+                self.command = out;
+                self.checksum = out as u16;
+                State::Compression
+            }
+            State::Compression => {
+                self.compressed = out & 0x01 != 0;
+                self.checksum = self.checksum.wrapping_add(out as u16);
+                State::LengthLo
+            }
+            State::LengthLo => {
+                self.length = out as usize;
+                self.checksum = self.checksum.wrapping_add(out as u16);
+                State::LengthHi
+            }
+            State::LengthHi => {
+                self.length |= (out as usize) << 8;
+                self.checksum = self.checksum.wrapping_add(out as u16);
+                self.payload.clear();
+                if self.length == 0 {
+                    State::ChecksumLo
+                } else {
+                    State::Payload
+                }
+            }
+            State::Payload => {
+                self.payload.push(out);
+                self.checksum = self.checksum.wrapping_add(out as u16);
+                if self.payload.len() == self.length {
+                    State::ChecksumLo
+                } else {
+                    State::Payload
+                }
+            }
+            State::ChecksumLo => {
+                self.received_checksum = out as u16;
+                State::ChecksumHi
+            }
+            State::ChecksumHi => {
+                self.received_checksum |= (out as u16) << 8;
+                self.execute();
+                State::Alive
+            }
+            State::Alive => {
+                reply = ALIVE;
+                State::Status
+            }
+            State::Status => {
+                reply = self.status;
+                // The print "finishes" once a status poll has seen it busy
+                if self.command == CMD_STATUS {
+                    self.status &= !STATUS_PRINTING;
+                }
+                State::Magic0
+            }
+        };
+        reply
+    }
+}
+
+/// A printer shared with the core, so it can read the printout while
+/// `Memory` drives the link.
+pub struct SharedPrinter(Rc<RefCell<Printer>>);
+
+impl SharedPrinter {
+    pub fn new(printer: Rc<RefCell<Printer>>) -> Self {
+        SharedPrinter(printer)
+    }
+}
+
+impl SerialLink for SharedPrinter {
+    fn exchange(&mut self, out: u8) -> u8 {
+        self.0.borrow_mut().exchange(out)
+    }
+}
+
+/// Frame a packet the way the Game Boy sends it, including the two
+/// trailing bytes that clock out the printer's reply.
+#[cfg(test)]
+pub(crate) fn packet(command: u8, compressed: bool, payload: &[u8]) -> Vec<u8> {
+    let len = payload.len() as u16;
+    let mut body = vec![command, compressed as u8, len as u8, (len >> 8) as u8];
+    body.extend_from_slice(payload);
+    let checksum = body.iter().fold(0u16, |sum, &b| sum.wrapping_add(b as u16));
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend(body);
+    bytes.extend([checksum as u8, (checksum >> 8) as u8, 0x00, 0x00]);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Send a packet and return the (alive, status) reply.
+    fn send(printer: &mut Printer, bytes: &[u8]) -> (u8, u8) {
+        let replies: Vec<u8> = bytes.iter().map(|&b| printer.exchange(b)).collect();
+        assert!(replies[..replies.len() - 2].iter().all(|&r| r == 0x00));
+        (replies[replies.len() - 2], replies[replies.len() - 1])
+    }
+
+    #[test]
+    fn test_rle_decompress() {
+        // 3 literals, then 0xAA repeated 5 times, then 1 literal
+        let data = [0x02, 1, 2, 3, 0x83, 0xAA, 0x00, 9];
+        assert_eq!(
+            decompress(&data),
+            vec![1, 2, 3, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 9]
+        );
+    }
+
+    #[test]
+    fn test_bad_checksum_sets_error_and_skips_command() {
+        let mut printer = Printer::new();
+        let mut bytes = packet(CMD_DATA, false, &[0xFF; 640]);
+        let checksum_at = bytes.len() - 4;
+        bytes[checksum_at] ^= 0x01;
+        assert_eq!(send(&mut printer, &bytes), (ALIVE, STATUS_CHECKSUM_ERROR));
+        assert!(printer.buffer.is_empty());
+    }
+}
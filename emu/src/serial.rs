@@ -0,0 +1,106 @@
+//! Serial link cable peers.
+//!
+//! A transfer started by writing SC (0xFF02) bit 7 shifts SB (0xFF01) out to
+//! the peer and the peer's byte in. [`SerialLink`] is the peer on the other
+//! end of the cable; with none attached the line floats high and 0xFF is
+//! received. [`RecordingSerialLink`] and [`ReplaySerialLink`] act as a
+//! "null modem" for deterministic link-cable tests: record one session, then
+//! replay the peer's side into another run.
+
+/// One byte swapped over the cable: (sent by this Game Boy, received from peer).
+pub type SerialExchange = (u8, u8);
+
+/// The device at the other end of the link cable.
+pub trait SerialLink {
+    /// Shift `out` to the peer and return the byte shifted back in.
+    fn exchange(&mut self, out: u8) -> u8;
+    /// Bytes exchanged so far, for links that keep a transcript.
+    #[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: serial_transcript
+    fn transcript(&self) -> &[SerialExchange] {
+        &[]
+    }
+}
+
+/// Any `FnMut(u8) -> u8` can act as a peer (handy for scripted tests).
+impl<F: FnMut(u8) -> u8> SerialLink for F {
+    fn exchange(&mut self, out: u8) -> u8 {
+        self(out)
+    }
+}
+
+/// Forwards to an inner peer (or a disconnected cable) and logs every exchange.
+#[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: record_serial_link
+pub struct RecordingSerialLink {
+    peer: Option<Box<dyn SerialLink>>,
+    log: Vec<SerialExchange>,
+}
+
+#[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: record_serial_link
+impl RecordingSerialLink {
+    /// Record exchanges with `peer`.
+    #[cfg(test)]
+    pub fn new(peer: Box<dyn SerialLink>) -> Self {
+        RecordingSerialLink {
+            peer: Some(peer),
+            log: Vec::new(),
+        }
+    }
+
+    /// Record exchanges with nothing attached (every received byte is 0xFF).
+    pub fn disconnected() -> Self {
+        RecordingSerialLink {
+            peer: None,
+            log: Vec::new(),
+        }
+    }
+}
+
+impl SerialLink for RecordingSerialLink {
+    fn exchange(&mut self, out: u8) -> u8 {
+        let incoming = self.peer.as_mut().map(|p| p.exchange(out)).unwrap_or(0xFF);
+        self.log.push((out, incoming));
+        incoming
+    }
+
+    fn transcript(&self) -> &[SerialExchange] {
+        &self.log
+    }
+}
+
+/// Plays back a prerecorded sequence of received bytes, ignoring what is
+/// sent (0xFF once exhausted). Logs exchanges so runs can be compared.
+#[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: replay_serial_link
+pub struct ReplaySerialLink {
+    incoming: Vec<u8>,
+    pos: usize,
+    log: Vec<SerialExchange>,
+}
+
+#[cfg_attr(not(feature = "wasm"), allow(dead_code))] // wasm: replay_serial_link
+impl ReplaySerialLink {
+    pub fn new(incoming: Vec<u8>) -> Self {
+        ReplaySerialLink {
+            incoming,
+            pos: 0,
+            log: Vec::new(),
+        }
+    }
+
+    /// Replay the peer's side of a recorded transcript.
+    pub fn from_transcript(transcript: &[SerialExchange]) -> Self {
+        Self::new(transcript.iter().map(|&(_, incoming)| incoming).collect())
+    }
+}
+
+impl SerialLink for ReplaySerialLink {
+    fn exchange(&mut self, out: u8) -> u8 {
+        let incoming = self.incoming.get(self.pos).copied().unwrap_or(0xFF);
+        self.pos += 1;
+        self.log.push((out, incoming));
+        incoming
+    }
+
+    fn transcript(&self) -> &[SerialExchange] {
+        &self.log
+    }
+}
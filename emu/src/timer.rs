@@ -4,6 +4,8 @@
 //! controlled by TAC. When TIMA overflows, it reloads from TMA and
 //! requests a Timer interrupt. DIV increments at a fixed 16384 Hz rate.
 
+use std::hash::{Hash, Hasher};
+
 use crate::interrupts::{Interrupt, InterruptController};
 use crate::memory::Memory;
 
@@ -13,6 +15,7 @@ pub struct Timer {
     tma: u8,             // Timer modulo (0xFF06)
     tac: u8,             // Timer control (0xFF07)
     overflow_cycles: u8, // Cycles until TIMA reload after overflow
+    reload_cycles: u8,   // Cycles left in the M-cycle that reloads TIMA from TMA
 }
 
 impl Timer {
@@ -23,9 +26,13 @@ impl Timer {
             tma: 0,
             tac: 0xF8,
             overflow_cycles: 0,
+            reload_cycles: 0,
         }
     }
 
+    /// Advance by `cycles` CPU T-cycles. The divider counts CPU cycles, so
+    /// in CGB double speed DIV and TIMA run twice as fast in real time with
+    /// no special casing here; dot-clocked callers must not halve `cycles`.
     pub fn tick(&mut self, cycles: u32, memory: &mut Memory, interrupts: &InterruptController) {
         for _ in 0..cycles {
             self.tick_once(memory, interrupts);
@@ -34,37 +41,44 @@ impl Timer {
 
     #[inline]
     fn tick_once(&mut self, memory: &mut Memory, interrupts: &InterruptController) {
+        if self.reload_cycles > 0 {
+            self.reload_cycles -= 1;
+        }
+
         // Handle delayed TIMA reload
         if self.overflow_cycles > 0 {
             self.overflow_cycles -= 1;
             if self.overflow_cycles == 0 {
                 self.tima = self.tma;
+                self.reload_cycles = 4;
                 interrupts.request(Interrupt::Timer, memory);
             }
         }
 
-        let old_div = self.div_counter;
+        let old_signal = self.signal();
         self.div_counter = self.div_counter.wrapping_add(1);
+        self.detect_falling_edge(old_signal);
+    }
 
-        // Check if timer is enabled
-        if self.tac & 0x04 == 0 {
-            return;
-        }
-
-        // Get the bit position to check based on clock select
+    /// The TIMA clock line: TAC's enable bit ANDed with the divider bit its
+    /// clock select picks.
+    #[inline]
+    fn signal(&self) -> bool {
         let bit = match self.tac & 0x03 {
-            0 => 9, // 4096 Hz (DIV bit 9)
-            1 => 3, // 262144 Hz (DIV bit 3)
-            2 => 5, // 65536 Hz (DIV bit 5)
-            3 => 7, // 16384 Hz (DIV bit 7)
+            0 => 9, // 4096 Hz
+            1 => 3, // 262144 Hz
+            2 => 5, // 65536 Hz
+            3 => 7, // 16384 Hz
             _ => unreachable!(),
         };
+        self.tac & 0x04 != 0 && (self.div_counter >> bit) & 1 != 0
+    }
 
-        // Falling edge detection
-        let old_bit = (old_div >> bit) & 1;
-        let new_bit = (self.div_counter >> bit) & 1;
-
-        if old_bit == 1 && new_bit == 0 {
+    /// TIMA counts on every high → low transition of `signal`, whether the
+    /// divider ticked, was reset, or TAC moved the line.
+    #[inline]
+    fn detect_falling_edge(&mut self, old_signal: bool) {
+        if old_signal && !self.signal() {
             self.tima = self.tima.wrapping_add(1);
             if self.tima == 0 {
                 // Overflow - delay reload by 4 cycles
@@ -73,6 +87,25 @@ impl Timer {
         }
     }
 
+    /// Feed the counter and register state into `h` (see `GameBoyCore::state_checksum`).
+    pub fn hash_state<H: Hasher>(&self, h: &mut H) {
+        self.div_counter.hash(h);
+        [
+            self.tima,
+            self.tma,
+            self.tac,
+            self.overflow_cycles,
+            self.reload_cycles,
+        ]
+        .hash(h);
+    }
+
+    /// The full 16-bit divider counter. DIV is its upper byte; its lower
+    /// bits clock TIMA via the falling-edge detector.
+    pub fn internal_counter(&self) -> u16 {
+        self.div_counter
+    }
+
     /// Read timer registers (0xFF04-0xFF07).
     pub fn read(&self, addr: u16) -> u8 {
         match addr {
@@ -85,20 +118,42 @@ impl Timer {
     }
 
     /// Write timer registers (0xFF04-0xFF07).
+    ///
+    /// After an overflow TIMA reads 0 for 4 cycles, then is reloaded from
+    /// TMA. A TMA write in that delay is what gets reloaded; during the
+    /// reload M-cycle itself TMA writes also land in TIMA and TIMA writes
+    /// are ignored (mooneye `tma_write_reloading`, `tima_write_reloading`).
+    ///
+    /// Resetting DIV or rewriting TAC can drop the TIMA clock line, which
+    /// counts as a falling edge and ticks TIMA once (mooneye `div_write`,
+    /// `rapid_toggle`, `timXX_div_trigger`). CGB's extra TAC glitches are
+    /// not modelled.
     pub fn write(&mut self, addr: u16, value: u8) {
+        let old_signal = self.signal();
         match addr {
             0xFF04 => self.div_counter = 0, // Writing any value resets DIV
             0xFF05 => {
+                if self.reload_cycles > 0 {
+                    return;
+                }
                 // Writing to TIMA during overflow delay cancels the interrupt
                 if self.overflow_cycles > 0 {
                     self.overflow_cycles = 0;
                 }
                 self.tima = value;
             }
-            0xFF06 => self.tma = value,
+            0xFF06 => {
+                self.tma = value;
+                if self.reload_cycles > 0 {
+                    self.tima = value;
+                }
+            }
             0xFF07 => self.tac = value,
             _ => {}
         }
+        if matches!(addr, 0xFF04 | 0xFF07) {
+            self.detect_falling_edge(old_signal);
+        }
     }
 }
 
@@ -111,6 +166,7 @@ impl Default for Timer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::memory::io;
 
     #[test]
     fn test_div_increment() {
@@ -134,6 +190,155 @@ mod tests {
         assert_eq!(timer.div_counter, 0);
     }
 
+    #[test]
+    fn test_div_is_counter_high_byte() {
+        let mut timer = Timer::new();
+        let mut mem = Memory::new();
+        let ic = InterruptController::new();
+
+        timer.write(0xFF04, 0x12);
+        assert_eq!(timer.internal_counter(), 0);
+
+        timer.tick(255, &mut mem, &ic);
+        assert_eq!(timer.internal_counter(), 0x00FF);
+        assert_eq!(timer.read(0xFF04), 0x00);
+
+        timer.tick(1, &mut mem, &ic);
+        assert_eq!(timer.read(0xFF04), 0x01);
+
+        timer.tick(0x1234 - 0x100, &mut mem, &ic);
+        assert_eq!(timer.internal_counter(), 0x1234);
+        assert_eq!(timer.read(0xFF04), 0x12);
+
+        // A DIV write clears the low bits as well as the visible high byte
+        timer.write(0xFF04, 0xFF);
+        assert_eq!(timer.internal_counter(), 0);
+        timer.tick(0xFF, &mut mem, &ic);
+        assert_eq!(timer.read(0xFF04), 0x00);
+    }
+
+    /// Timer at 262144 Hz with TIMA one tick from overflowing; returns after
+    /// the overflow, at the start of the 4-cycle reload delay.
+    fn overflowed_timer(mem: &mut Memory, ic: &InterruptController) -> Timer {
+        let mut timer = Timer::new();
+        timer.write(0xFF04, 0);
+        timer.write(0xFF07, 0x05);
+        timer.write(0xFF06, 0x10);
+        timer.write(0xFF05, 0xFF);
+        timer.tick(16, mem, ic);
+        assert_eq!(timer.read(0xFF05), 0x00);
+        timer
+    }
+
+    #[test]
+    fn test_tima_reload_delay_and_interrupt_timing() {
+        let mut mem = Memory::new();
+        let ic = InterruptController::new();
+        mem.write_io_direct(io::IF, 0x00);
+
+        // TIMA reads 0 and no interrupt is pending for the whole M-cycle
+        // after the overflow...
+        let mut timer = overflowed_timer(&mut mem, &ic);
+        for _ in 0..3 {
+            timer.tick(1, &mut mem, &ic);
+            assert_eq!(timer.read(0xFF05), 0x00);
+            assert_eq!(mem.read_io_direct(io::IF) & 0x04, 0);
+        }
+        // ...then TMA lands and the interrupt is requested together
+        timer.tick(1, &mut mem, &ic);
+        assert_eq!(timer.read(0xFF05), 0x10);
+        assert_ne!(mem.read_io_direct(io::IF) & 0x04, 0);
+
+        // Writing TIMA inside the delay cancels both the reload and the IRQ
+        mem.write_io_direct(io::IF, 0x00);
+        let mut timer = overflowed_timer(&mut mem, &ic);
+        timer.tick(2, &mut mem, &ic);
+        timer.write(0xFF05, 0x80);
+        timer.tick(4, &mut mem, &ic);
+        assert_eq!(timer.read(0xFF05), 0x80);
+        assert_eq!(mem.read_io_direct(io::IF) & 0x04, 0);
+    }
+
+    #[test]
+    fn test_tma_write_during_reload_delay_is_reloaded() {
+        let mut mem = Memory::new();
+        let ic = InterruptController::new();
+
+        let mut timer = overflowed_timer(&mut mem, &ic);
+        timer.tick(2, &mut mem, &ic);
+        timer.write(0xFF06, 0x42);
+        timer.tick(2, &mut mem, &ic);
+        assert_eq!(timer.read(0xFF05), 0x42);
+    }
+
+    #[test]
+    fn test_tma_write_after_reload_keeps_old_value() {
+        let mut mem = Memory::new();
+        let ic = InterruptController::new();
+
+        // In the reload M-cycle the write still reaches TIMA...
+        let mut timer = overflowed_timer(&mut mem, &ic);
+        timer.tick(4, &mut mem, &ic);
+        assert_eq!(timer.read(0xFF05), 0x10);
+        timer.write(0xFF05, 0x99); // ignored while reloading
+        assert_eq!(timer.read(0xFF05), 0x10);
+        timer.write(0xFF06, 0x42);
+        assert_eq!(timer.read(0xFF05), 0x42);
+
+        // ...but once it is over, TIMA keeps what was reloaded
+        let mut timer = overflowed_timer(&mut mem, &ic);
+        timer.tick(8, &mut mem, &ic);
+        timer.write(0xFF06, 0x42);
+        assert_eq!(timer.read(0xFF05), 0x10);
+        assert_eq!(timer.read(0xFF06), 0x42);
+    }
+
+    #[test]
+    fn test_div_write_ticks_tima_when_selected_bit_set() {
+        let mut mem = Memory::new();
+        let ic = InterruptController::new();
+
+        // timXX_div_trigger: the selected bit is high, so the reset is an edge
+        for (tac, bit) in [(0x04, 9), (0x05, 3), (0x06, 5), (0x07, 7)] {
+            let mut timer = Timer::new();
+            timer.write(0xFF04, 0);
+            timer.write(0xFF07, tac);
+            timer.write(0xFF05, 0x00);
+            timer.tick(1 << bit, &mut mem, &ic);
+            assert_eq!(timer.read(0xFF05), 0x00, "TAC {:02X}", tac);
+            timer.write(0xFF04, 0);
+            assert_eq!(timer.read(0xFF05), 0x01, "TAC {:02X}", tac);
+
+            // With the bit low the reset is silent
+            timer.write(0xFF04, 0);
+            assert_eq!(timer.read(0xFF05), 0x01, "TAC {:02X}", tac);
+        }
+    }
+
+    #[test]
+    fn test_tac_write_ticks_tima_on_falling_signal() {
+        let mut mem = Memory::new();
+        let ic = InterruptController::new();
+        let mut timer = Timer::new();
+        timer.write(0xFF04, 0);
+        timer.write(0xFF07, 0x05); // 262144 Hz: DIV bit 3
+        timer.write(0xFF05, 0x00);
+        timer.tick(8, &mut mem, &ic); // bit 3 high, bit 5 low
+
+        // rapid_toggle: disabling with the selected bit high ticks TIMA
+        timer.write(0xFF07, 0x01);
+        assert_eq!(timer.read(0xFF05), 0x01);
+        // Re-enabling is a rising edge: nothing
+        timer.write(0xFF07, 0x05);
+        assert_eq!(timer.read(0xFF05), 0x01);
+        // Selecting a bit that is low drops the line too
+        timer.write(0xFF07, 0x06);
+        assert_eq!(timer.read(0xFF05), 0x02);
+        // Low → high on a frequency change: nothing
+        timer.write(0xFF07, 0x05);
+        assert_eq!(timer.read(0xFF05), 0x02);
+    }
+
     #[test]
     fn test_timer_disabled() {
         let mut timer = Timer::new();
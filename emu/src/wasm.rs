@@ -3,11 +3,19 @@
 //! All emulation logic lives in [`GameBoyCore`]; this module is a thin
 //! adapter that exposes it to JavaScript via `wasm-bindgen`.
 
+use std::collections::HashMap;
+
 use wasm_bindgen::prelude::*;
 
-use crate::core::GameBoyCore;
-use crate::log::LogCategory;
+use crate::color::{ColorCorrection, rgb555_to_rgba8888};
+use crate::core::{CaptureCallback, FrameProgress, GameBoyCore, MemRegion, StepResult};
+use crate::cpu::CpuState;
+use crate::debug::DebugServer;
+use crate::joypad::Button;
+use crate::log::{LogCategory, LogLevel};
 use crate::memory::io;
+use crate::ppu::{Ppu, ScanlineCallback};
+use crate::serial::{RecordingSerialLink, ReplaySerialLink, SerialExchange};
 use crate::{log_info, log_warn};
 
 /// Initialize panic hook for better error messages in WASM.
@@ -22,6 +30,57 @@ pub struct GameBoy {
     core: GameBoyCore,
 }
 
+/// Summary of a completed chunked ROM load.
+#[wasm_bindgen]
+pub struct LoadReport {
+    pub rom_bank_count: usize,
+    pub cgb_mode: bool,
+    mbc_type: String,
+}
+
+#[wasm_bindgen]
+impl LoadReport {
+    #[wasm_bindgen(getter)]
+    pub fn mbc_type(&self) -> String {
+        self.mbc_type.clone()
+    }
+}
+
+/// A text-command debugger (see `debug::DebugServer`) that owns a
+/// `GameBoy` until `detach` hands it back.
+#[wasm_bindgen]
+pub struct DebugSession {
+    server: DebugServer<GameBoyCore>,
+}
+
+#[wasm_bindgen]
+impl DebugSession {
+    /// Take over `gb`; its JS handle is consumed.
+    #[wasm_bindgen(constructor)]
+    pub fn new(gb: GameBoy) -> DebugSession {
+        DebugSession {
+            server: DebugServer::new(gb.core),
+        }
+    }
+
+    /// Run one command (`regs`, `mem c000 10`, `break 0150`, `continue`,
+    /// …) and return the reply. Errors start with `E `.
+    pub fn handle_command(&mut self, line: &str) -> String {
+        self.server.handle_command(line)
+    }
+
+    /// End the session and return the emulator. Breakpoints are dropped.
+    pub fn detach(self) -> GameBoy {
+        GameBoy {
+            core: self.server.into_target(),
+        }
+    }
+}
+
+/// Opaque CPU register file and execution state, from `cpu_snapshot`.
+#[wasm_bindgen]
+pub struct CpuSnapshot(CpuState);
+
 #[wasm_bindgen]
 impl GameBoy {
     #[wasm_bindgen(constructor)]
@@ -73,7 +132,9 @@ impl GameBoy {
             );
         }
 
-        self.core.load_rom(rom_data, cgb_mode).map_err(JsValue::from_str)?;
+        self.core
+            .load_rom(rom_data, cgb_mode)
+            .map_err(JsValue::from_str)?;
 
         log_info!(
             LogCategory::General,
@@ -89,12 +150,106 @@ impl GameBoy {
         Ok(())
     }
 
+    /// Install a DMG (256-byte) or CGB (2304-byte) boot ROM to run before
+    /// the cartridge on every subsequent `load_rom`.
+    pub fn load_boot_rom(&mut self, data: &[u8]) -> Result<(), JsValue> {
+        self.core.load_boot_rom(data).map_err(JsValue::from_str)
+    }
+
+    /// Start at `pc` instead of 0x0100 on every later `load_rom` (ignored
+    /// while a boot ROM is installed).
+    pub fn set_entry_pc(&mut self, pc: u16) {
+        self.core.set_entry_pc(pc);
+    }
+
+    /// Start later DMG-mode `load_rom`s with Game Boy Pocket registers.
+    pub fn set_pocket_model(&mut self, pocket: bool) {
+        self.core.set_pocket_model(pocket);
+    }
+
+    /// Begin a chunked ROM load of `total_len` bytes. Use with
+    /// `push_rom_chunk` and `finish_rom_load` to avoid one large copy.
+    pub fn begin_rom_load(&mut self, total_len: usize) {
+        self.core.begin_rom_load(total_len);
+    }
+
+    /// Copy `bytes` into the pending ROM at `offset`.
+    pub fn push_rom_chunk(&mut self, offset: usize, bytes: &[u8]) -> Result<(), JsValue> {
+        self.core
+            .push_rom_chunk(offset, bytes)
+            .map_err(JsValue::from_str)
+    }
+
+    /// Validate and load the assembled ROM. Fails if the pushed length
+    /// doesn't match the length passed to `begin_rom_load`.
+    pub fn finish_rom_load(&mut self, cgb_mode: bool) -> Result<LoadReport, JsValue> {
+        self.core
+            .finish_rom_load(cgb_mode)
+            .map_err(JsValue::from_str)?;
+
+        log_info!(
+            LogCategory::General,
+            "ROM loaded from chunks: MBC: {:?}, Banks: {}",
+            self.core.memory.get_mbc_type(),
+            self.core.memory.get_rom_bank_count()
+        );
+
+        Ok(LoadReport {
+            rom_bank_count: self.core.memory.get_rom_bank_count(),
+            cgb_mode,
+            mbc_type: format!("{:?}", self.core.memory.get_mbc_type()),
+        })
+    }
+
+    /// Enable sub-instruction timing (e.g. interrupt dispatch interleaved with
+    /// timer ticks) and PPU-mode VRAM/OAM write blocking at some speed cost.
+    /// Persists across ROM loads.
+    pub fn set_accurate(&mut self, accurate: bool) {
+        self.core.set_accurate(accurate);
+    }
+
+    /// Pause or resume; freezes the cartridge RTC while paused.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.core.set_paused(paused);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.core.is_paused()
+    }
+
+    /// Advance the cartridge RTC one second every `fps` frames instead of by
+    /// wall-clock time, for reproducible runs; `None` restores the wall clock.
+    /// Persists across ROM loads.
+    pub fn set_rtc_frames_per_second(&mut self, fps: Option<u32>) {
+        self.core.set_rtc_frames_per_second(fps);
+    }
+
+    /// Run several frames in one call (e.g. fast-forward or headless demos).
+    pub fn run_frames(&mut self, frames: u32) {
+        self.core.run_frames(frames);
+    }
+
+    /// Script input for demos: hold `buttons` (bits: A, B, Select, Start) and
+    /// `dpad` (bits: Right, Left, Up, Down) for `frames` frames after any
+    /// already-queued segments. Buttons release when the queue empties.
+    pub fn queue_input(&mut self, buttons: u8, dpad: u8, frames: u32) {
+        self.core.queue_input(buttons, dpad, frames);
+    }
+
+    /// Run up to `max_instructions` of the current frame. Returns true once
+    /// the frame completes; otherwise call again next tick to resume.
+    pub fn run_frame_budgeted(&mut self, max_instructions: u32) -> bool {
+        self.core.run_frame_budgeted(max_instructions) == FrameProgress::Complete
+    }
+
+    /// Run one frame, or only the rest of one `run_frame_budgeted` left
+    /// partial (the debug log then counts just that remainder).
     pub fn step_frame(&mut self) {
-        let instructions_this_frame = self.core.step_frame();
+        let instructions_this_call = self.core.step_frame();
 
         // Log state every 60 frames (approximately once per second)
         if self.core.frame_count % 60 == 1 {
-            self.log_frame_debug(instructions_this_frame);
+            self.log_frame_debug(instructions_this_call);
         }
     }
 
@@ -110,6 +265,43 @@ impl GameBoy {
         self.core.set_button(button, pressed);
     }
 
+    /// Whether `button` (0=A .. 7=Down) currently reads as pressed.
+    pub fn is_button_pressed(&self, button: u8) -> bool {
+        Button::from_u8(button).is_some_and(|b| self.core.joypad.is_pressed(b))
+    }
+
+    /// Autofire `button` (0=A .. 7=Down) at `rate_hz` presses per second
+    /// while it is held; 0 turns it off.
+    pub fn set_turbo(&mut self, button: u8, rate_hz: f32) {
+        if let Some(button) = Button::from_u8(button) {
+            self.core.set_turbo(button, rate_hz);
+        }
+    }
+
+    /// Map an analog stick (-1.0..=1.0, +y down) onto the D-pad.
+    pub fn set_analog_dpad(&mut self, x: f32, y: f32, deadzone: f32) {
+        self.core.set_analog_dpad(x, y, deadzone);
+    }
+
+    /// Allocate this many bytes of cartridge RAM on the next `load_rom`,
+    /// for homebrew with a wrong header RAM size. `undefined` restores the
+    /// header size. Has no effect on the camera's 128KB.
+    pub fn override_ram_size(&mut self, bytes: Option<usize>) {
+        self.core.override_ram_size(bytes);
+    }
+
+    /// Whether the cartridge has a battery; frontends should only write
+    /// `.sav` files when this is true.
+    pub fn has_battery(&self) -> bool {
+        self.core.has_battery()
+    }
+
+    /// SHA-1 of the loaded ROM (lowercase hex) for No-Intro matching and
+    /// keying saves.
+    pub fn rom_sha1(&self) -> String {
+        self.core.rom_sha1_hex()
+    }
+
     pub fn get_cartridge_ram(&self) -> Vec<u8> {
         self.core.memory.get_cartridge_ram().to_vec()
     }
@@ -118,12 +310,105 @@ impl GameBoy {
         self.core.memory.load_cartridge_ram(data);
     }
 
+    /// Save data with the MBC3 RTC footer appended (standard `.sav` layout).
+    pub fn export_ram_with_rtc(&self) -> Vec<u8> {
+        self.core.export_ram_with_rtc()
+    }
+
+    /// Load a `.sav` that may include an MBC3 RTC footer.
+    pub fn load_ram_with_rtc(&mut self, data: &[u8]) {
+        self.core.load_ram_with_rtc(data);
+    }
+
+    /// Whether the game is driving the cartridge rumble motor; poll once
+    /// per frame to drive gamepad vibration.
+    pub fn is_rumbling(&self) -> bool {
+        self.core.memory.is_rumbling()
+    }
+
+    /// Plug a Game Boy Printer into the link port (`false` unplugs it).
+    pub fn enable_printer(&mut self, enable: bool) {
+        self.core.enable_printer(enable);
+    }
+
+    /// The current printout as RGBA, 160 pixels wide and
+    /// `printer_image_height()` tall.
+    pub fn printer_image(&self) -> Vec<u8> {
+        self.core.printer_image()
+    }
+
+    pub fn printer_image_height(&self) -> usize {
+        self.core.printer_image_height()
+    }
+
+    /// Log every link cable exchange with nothing attached (each received
+    /// byte is 0xFF), replacing any peer. Read it with `serial_transcript`.
+    pub fn record_serial_link(&mut self) {
+        self.core
+            .set_serial_link(Some(Box::new(RecordingSerialLink::disconnected())));
+    }
+
+    /// Play back the peer's side of a `serial_transcript` from another run,
+    /// replacing any peer.
+    pub fn replay_serial_link(&mut self, transcript: &[u8]) {
+        let exchanges: Vec<SerialExchange> = transcript
+            .chunks_exact(2)
+            .map(|pair| (pair[0], pair[1]))
+            .collect();
+        self.core
+            .set_serial_link(Some(Box::new(ReplaySerialLink::from_transcript(
+                &exchanges,
+            ))));
+    }
+
+    /// Unplug whatever is on the link cable.
+    pub fn disconnect_serial_link(&mut self) {
+        self.core.set_serial_link(None);
+    }
+
+    /// Bytes exchanged by a recording or replaying link, as (sent,
+    /// received) pairs. Empty for other peers.
+    pub fn serial_transcript(&self) -> Vec<u8> {
+        let Some(link) = self.core.memory.serial_link() else {
+            return Vec::new();
+        };
+        link.transcript()
+            .iter()
+            .flat_map(|&(out, incoming)| [out, incoming])
+            .collect()
+    }
+
+    /// The MBC3 clock alone (48-byte BGB/VBA RTC block), for saving it
+    /// next to plain RAM. Empty without an RTC.
+    pub fn get_rtc_data(&self) -> Vec<u8> {
+        self.core.memory.get_rtc_data()
+    }
+
+    /// Restore a `get_rtc_data` block; the clock then catches up on the
+    /// time since it was saved. Returns false without an RTC.
+    pub fn load_rtc_data(&mut self, data: &[u8]) -> bool {
+        self.core.memory.load_rtc_data(data)
+    }
+
     /// Set camera image data from webcam.
     /// Expects 128x112 pixels as raw 8-bit grayscale (0=black, 255=white).
     pub fn set_camera_image(&mut self, data: &[u8]) {
         self.core.set_camera_image(data);
     }
 
+    /// Set the camera image from an RGBA frame of any size (e.g. canvas
+    /// `getImageData`); grayscale conversion and scaling happen in Rust.
+    pub fn set_camera_image_rgba(
+        &mut self,
+        rgba: &[u8],
+        width: usize,
+        height: usize,
+    ) -> Result<(), JsValue> {
+        self.core
+            .set_camera_image_rgba(rgba, width, height)
+            .map_err(JsValue::from_str)
+    }
+
     /// Check if camera image is ready for capture.
     pub fn is_camera_ready(&self) -> bool {
         self.core.is_camera_ready()
@@ -140,6 +425,17 @@ impl GameBoy {
         self.core.update_camera_live()
     }
 
+    /// Call `callback(slot)` each time the ROM completes a camera capture,
+    /// instead of polling every frame. Pass `undefined` to remove it.
+    pub fn set_capture_callback(&mut self, callback: Option<js_sys::Function>) {
+        let callback = callback.map(|f| -> CaptureCallback {
+            Box::new(move |slot| {
+                let _ = f.call1(&JsValue::NULL, &JsValue::from(slot));
+            })
+        });
+        self.core.set_capture_callback(callback);
+    }
+
     /// Pointer to the camera live view RGBA buffer (128x112x4 bytes).
     pub fn camera_live_ptr(&self) -> *const u8 {
         self.core.camera_live_buffer.front().as_ptr()
@@ -150,12 +446,44 @@ impl GameBoy {
         self.core.camera_live_buffer.front().len()
     }
 
+    /// Occupied photo slots for a gallery grid: bit i set = slot i+1.
+    pub fn camera_occupancy(&self) -> u32 {
+        self.core.camera_occupancy()
+    }
+
     /// Decode a GB Camera saved photo slot to RGBA pixel data.
     /// Slots 1-30 = saved photos. Returns empty if slot is unoccupied.
     pub fn decode_camera_photo(&self, slot: u8) -> Vec<u8> {
         self.core.decode_camera_photo(slot)
     }
 
+    /// Like `decode_camera_photo`, but throws a numeric error code instead
+    /// of returning empty: 1 = invalid slot, 2 = empty slot, 4 = not a camera.
+    pub fn try_decode_camera_photo(&self, slot: u8) -> Result<Vec<u8>, JsValue> {
+        self.core
+            .try_decode_camera_photo(slot)
+            .map_err(|e| JsValue::from(e.code()))
+    }
+
+    /// Encode 128×112 RGBA into saved slot 1-30. Throws a numeric error code:
+    /// 1 = invalid slot, 3 = wrong data length, 4 = not a camera.
+    pub fn try_encode_camera_photo(&mut self, slot: u8, rgba: &[u8]) -> Result<(), JsValue> {
+        self.core
+            .try_encode_camera_photo(slot, rgba)
+            .map_err(|e| JsValue::from(e.code()))
+    }
+
+    /// Erase every saved photo (slots 1-30). The active capture is kept.
+    pub fn clear_camera_roll(&mut self) {
+        self.core.clear_camera_roll();
+    }
+
+    /// Move the photo in saved slot `from` to slot `to` (1-30); an occupied
+    /// target swaps places with it. Returns false for an empty or invalid slot.
+    pub fn move_camera_photo(&mut self, from: u8, to: u8) -> bool {
+        self.core.memory.move_camera_photo(from, to)
+    }
+
     /// Read a camera hardware register (0x00-0x7F, corresponding to A000-A07F).
     pub fn camera_reg(&self, index: u8) -> u8 {
         self.core.memory.camera_reg(index)
@@ -166,11 +494,158 @@ impl GameBoy {
         self.core.memory.camera_contrast()
     }
 
+    /// Raw 2bpp tile bytes of the active capture (16×14 tiles, 3584 bytes)
+    /// for the tile viewer. Empty for non-camera cartridges.
+    pub fn get_capture_tiles(&self) -> Vec<u8> {
+        self.core.memory.camera_capture_tiles().to_vec()
+    }
+
+    /// Preview the current dither matrix as a 64×64 RGBA swatch of a
+    /// black-to-white ramp. Returns empty for non-camera cartridges.
+    pub fn camera_dither_preview(&self) -> Vec<u8> {
+        self.core.memory.camera_dither_preview()
+    }
+
+    /// Focus metric of the current webcam frame, 0.0 (flat) to 1.0 (sharpest),
+    /// for a viewfinder focus bar. Returns 0 for non-camera cartridges.
+    pub fn camera_sharpness(&self) -> f32 {
+        self.core.memory.camera_sharpness()
+    }
+
+    /// Adjust brightness/contrast of captures without touching the sensor registers.
+    /// `brightness` is added to each pixel (0–255 scale); `contrast` scales around
+    /// mid-gray. Pass `(0, 1.0)` to reset.
+    pub fn set_camera_post_adjust(&mut self, brightness: i16, contrast: f32) {
+        self.core
+            .memory
+            .set_camera_post_adjust(brightness, contrast);
+    }
+
+    /// Correct webcam orientation before frames reach the sensor. `rotate_180`
+    /// flips both axes; combining it with a flip cancels that axis.
+    pub fn set_camera_orientation(&mut self, flip_h: bool, flip_v: bool, rotate_180: bool) {
+        self.core
+            .memory
+            .set_camera_orientation(flip_h, flip_v, rotate_180);
+    }
+
+    /// Process the live preview at 1/`factor` resolution per axis (1, 2, 4
+    /// or 8) on slow devices. Captures the ROM takes stay full resolution.
+    pub fn set_camera_preview_downscale(&mut self, factor: u8) {
+        self.core.memory.set_camera_preview_downscale(factor);
+    }
+
+    /// 128×112 RGBA preview of what the sensor would capture now, without
+    /// touching cartridge RAM. Empty when no camera cartridge is loaded.
+    pub fn camera_preview(&self) -> Vec<u8> {
+        self.core.memory.camera_preview_rgba()
+    }
+
+    /// Stereo frames per second produced by the APU (default 48000).
+    pub fn audio_sample_rate(&self) -> u32 {
+        self.core.memory.audio_sample_rate()
+    }
+
+    /// Match the APU output to the `AudioContext` rate.
+    pub fn set_audio_sample_rate(&mut self, rate: u32) {
+        self.core.memory.set_audio_sample_rate(rate);
+    }
+
+    /// Move buffered audio into `out` as interleaved stereo (L, R, L, R…)
+    /// and return the number of frames (sample pairs) written. Roughly a
+    /// third of a second is buffered; past that the oldest audio is dropped.
+    pub fn drain_audio(&mut self, out: &mut [f32]) -> usize {
+        self.core.memory.drain_audio(out)
+    }
+
+    /// Palette-index frame (160×144, 1 byte/pixel) for compact recording:
+    /// 0-3 = gray shade; otherwise 0x80 | obj << 5 | palette << 2 | colour
+    /// into CGB palette RAM.
+    pub fn get_index_buffer(&self) -> Vec<u8> {
+        self.core.ppu.get_index_buffer().to_vec()
+    }
+
+    /// Expand one `get_index_buffer` byte to `[r, g, b, a]` with the
+    /// current palettes (no output LUT or colour correction).
+    pub fn index_to_rgba(&self, index: u8) -> Vec<u8> {
+        Ppu::index_to_rgba(&self.core.memory, index).to_vec()
+    }
+
+    /// Sprites found on each of the 144 lines in the last frame, before the
+    /// 10-per-line limit. Lines above 10 are dropping sprites (flicker).
+    pub fn get_sprites_per_line(&self) -> Vec<u8> {
+        self.core.ppu.sprites_per_line().to_vec()
+    }
+
+    /// Sprites drawn on each of the 144 lines (at most 10).
+    pub fn get_sprites_drawn_per_line(&self) -> Vec<u8> {
+        self.core.ppu.sprites_drawn_per_line().to_vec()
+    }
+
+    /// Set a 256-entry RGBA colour LUT (1024 bytes) applied to the display
+    /// output, or clear it with `undefined`.
+    pub fn set_output_lut(&mut self, lut: Option<Vec<u8>>) -> Result<(), JsValue> {
+        self.core
+            .set_output_lut(lut.as_deref())
+            .map_err(JsValue::from_str)
+    }
+
+    /// Render DMG games run in CGB mode in grayscale via BGP/OBP0/OBP1
+    /// until they write colour palettes, instead of black.
+    pub fn set_cgb_dmg_fallback(&mut self, enabled: bool) {
+        self.core.set_cgb_dmg_fallback(enabled);
+    }
+
+    /// Emulate DMG LCD ghosting: `factor` (0.0-0.95) of each pixel's previous
+    /// level is kept every frame. 0 turns it off.
+    pub fn set_lcd_ghosting(&mut self, factor: f32) {
+        self.core.set_lcd_ghosting(factor);
+    }
+
+    /// Show CGB colours as the CGB LCD would (dimmer, less saturated)
+    /// instead of the raw RGB555 values.
+    pub fn set_color_correction(&mut self, enabled: bool) {
+        let correction = if enabled {
+            ColorCorrection::Cgb
+        } else {
+            ColorCorrection::None
+        };
+        self.core.set_color_correction(correction);
+    }
+
+    /// Enable or disable a log category ("camera", "memory", "cpu", "ppu",
+    /// "general") at runtime.
+    pub fn set_log_enabled(&mut self, category: &str, enabled: bool) -> Result<(), JsValue> {
+        let category = LogCategory::from_name(category)
+            .ok_or_else(|| JsValue::from_str("unknown log category"))?;
+        self.core.set_log_category_enabled(category, enabled);
+        Ok(())
+    }
+
+    /// Set the log level: "off", "error", "warn" or "info".
+    pub fn set_log_level(&mut self, level: &str) -> Result<(), JsValue> {
+        let level =
+            LogLevel::from_name(level).ok_or_else(|| JsValue::from_str("unknown log level"))?;
+        self.core.set_log_level(level);
+        Ok(())
+    }
+
     /// Get serial output as a string (for test ROM debugging).
     pub fn get_serial_output(&self) -> String {
         self.core.memory.get_serial_output_string()
     }
 
+    /// Serial bytes appended since `cursor` (start at 0). Read the cursor
+    /// for the next call from `serial_cursor()` afterwards.
+    pub fn take_serial_since(&self, cursor: usize) -> Vec<u8> {
+        self.core.take_serial_since(cursor).0
+    }
+
+    /// Current end of the serial output buffer, for `take_serial_since`.
+    pub fn serial_cursor(&self) -> usize {
+        self.core.memory.serial_output().len()
+    }
+
     /// Clear the serial output buffer.
     pub fn clear_serial_output(&mut self) {
         self.core.memory.clear_serial_output();
@@ -189,20 +664,32 @@ impl GameBoy {
         info
     }
 
+    /// CPU/IO/PPU/timer/cartridge state as JSON, for inspection (not reload).
+    pub fn export_state_json(&self) -> String {
+        self.core.export_state_json()
+    }
+
+    /// Combined CPU/PPU/IO/cartridge/serial state snapshot for bug reports.
+    pub fn debug_dump(&self) -> String {
+        self.core.debug_dump()
+    }
+
     /// Log a message to the browser console.
     pub fn log(&self, msg: &str) {
         log_info!(LogCategory::General, "{}", msg);
     }
 
     /// Log frame debug info.
-    fn log_frame_debug(&self, instructions_this_frame: u32) {
+    /// `instructions_this_call` is what the last `step_frame` ran: a whole
+    /// frame, or only the remainder of a budgeted one.
+    fn log_frame_debug(&self, instructions_this_call: u32) {
         log_info!(
             LogCategory::General,
-            "=== Frame {} | cycles: {} | instrs: {} (frame: {}) ===",
+            "=== Frame {} | cycles: {} | instrs: {} (this step_frame: {}) ===",
             self.core.frame_count,
             self.core.total_cycles,
             self.core.instruction_count,
-            instructions_this_frame
+            instructions_this_call
         );
 
         log_info!(LogCategory::Cpu, "{}", self.core.cpu.get_debug_state());
@@ -225,6 +712,11 @@ impl GameBoy {
         );
     }
 
+    /// Deterministic hash of the full machine state, for lockstep netplay.
+    pub fn state_checksum(&self) -> u64 {
+        self.core.state_checksum()
+    }
+
     /// Get frame count for debugging.
     pub fn get_frame_count(&self) -> u32 {
         self.core.frame_count
@@ -309,6 +801,27 @@ impl GameBoy {
         self.core.cpu.get_debug_state().halted
     }
 
+    /// Address of the illegal opcode that locked the CPU, if it is locked.
+    pub fn cpu_fault_pc(&self) -> Option<u16> {
+        self.core.cpu.fault().map(|f| f.pc)
+    }
+
+    /// The illegal opcode that locked the CPU, if it is locked.
+    pub fn cpu_fault_opcode(&self) -> Option<u8> {
+        self.core.cpu.fault().map(|f| f.opcode)
+    }
+
+    /// Capture the CPU state, e.g. for a rewind buffer (see `cpu_restore`).
+    pub fn cpu_snapshot(&self) -> CpuSnapshot {
+        CpuSnapshot(self.core.cpu.snapshot())
+    }
+
+    /// Return the CPU to a `cpu_snapshot`. Memory and other components are
+    /// not part of it.
+    pub fn cpu_restore(&mut self, snapshot: &CpuSnapshot) {
+        self.core.cpu.restore(&snapshot.0);
+    }
+
     // PPU state
 
     pub fn ppu_mode(&self) -> u8 {
@@ -323,20 +836,132 @@ impl GameBoy {
         self.core.ppu.get_debug_state().cycles
     }
 
+    /// Call `callback(ly, regs)` after every rendered scanline, where `regs`
+    /// is `[lcdc, scy, scx, wy, wx, bgp]` as they were for that line. Pass
+    /// `undefined` to remove it.
+    pub fn set_scanline_callback(&mut self, callback: Option<js_sys::Function>) {
+        let callback = callback.map(|f| -> ScanlineCallback {
+            Box::new(move |ly, io| {
+                let regs = [io.lcdc, io.scy, io.scx, io.wy, io.wx, io.bgp];
+                let regs = js_sys::Uint8Array::from(&regs[..]);
+                let _ = f.call2(&JsValue::NULL, &JsValue::from(ly), &regs);
+            })
+        });
+        self.core.set_scanline_callback(callback);
+    }
+
+    /// Start (clearing the previous trace) or stop recording PPU mode changes.
+    pub fn set_ppu_mode_trace(&mut self, enabled: bool) {
+        self.core.set_ppu_mode_trace(enabled);
+    }
+
+    /// Recorded PPU mode changes, oldest first, flattened as `[dot, line,
+    /// mode, dot, line, mode, …]` with `dot` counted from when tracing began.
+    pub fn ppu_mode_trace(&self) -> Vec<f64> {
+        self.core
+            .ppu_mode_trace()
+            .iter()
+            .flat_map(|e| [e.dot as f64, e.line as f64, e.mode as f64])
+            .collect()
+    }
+
     // Memory access
 
     pub fn read_byte(&self, addr: u16) -> u8 {
-        self.core.memory.read(addr)
+        self.core.memory.peek(addr)
+    }
+
+    /// Debugger write; VRAM and OAM are written even while the PPU owns them.
+    pub fn write_byte(&mut self, addr: u16, value: u8) {
+        self.core.poke(addr, value);
     }
 
     pub fn read_range(&self, addr: u16, len: u16) -> Vec<u8> {
         let mut data = Vec::with_capacity(len as usize);
         for i in 0..len {
-            data.push(self.core.memory.read(addr.wrapping_add(i)));
+            data.push(self.core.memory.peek(addr.wrapping_add(i)));
         }
         data
     }
 
+    /// Cheat finder: addresses in `region` ("wram", "hram" or "all")
+    /// holding `value`.
+    pub fn memory_search(&self, value: u8, region: &str) -> Result<Vec<u16>, JsValue> {
+        let region = MemRegion::from_name(region)
+            .ok_or_else(|| JsValue::from_str("unknown memory region"))?;
+        Ok(self.core.memory_search(value, region))
+    }
+
+    /// Keep only the addresses from a previous search that now hold `value`.
+    pub fn memory_search_refine(&self, prev: Vec<u16>, value: u8) -> Vec<u16> {
+        self.core.memory_search_refine(&prev, value)
+    }
+
+    /// Stop `step_until_break` when an instruction writes exactly `value`
+    /// to `addr`.
+    pub fn add_value_watchpoint(&mut self, addr: u16, value: u8) {
+        self.core.add_value_watchpoint(addr, value);
+    }
+
+    pub fn clear_value_watchpoints(&mut self) {
+        self.core.clear_value_watchpoints();
+    }
+
+    /// Execute up to `max_instructions` instructions. Returns `[addr,
+    /// value]` of the watched write that stopped it, or `undefined` if the
+    /// budget ran out first.
+    pub fn step_until_break(&mut self, max_instructions: u32) -> Option<Vec<u16>> {
+        match self.core.step_until_break(max_instructions) {
+            StepResult::Budget => None,
+            StepResult::ValueWatchpoint { addr, value } => Some(vec![addr, value as u16]),
+        }
+    }
+
+    /// Make `read_byte`/`read_range` see cartridge RAM even while the game
+    /// has it disabled (`true`), force it hidden (`false`), or follow the
+    /// cart's enable register (`undefined`).
+    pub fn set_ram_enable_override(&mut self, value: Option<bool>) {
+        self.core.set_ram_enable_override(value);
+    }
+
+    /// Read BG tilemap tiles `x..x+width`, `y..y+height` as text, where
+    /// tile `tiles[i]` reads as the `i`th character of `chars` (others as
+    /// spaces). Rows are joined with `\n`.
+    pub fn read_text_region(
+        &self,
+        tiles: &[u8],
+        chars: &str,
+        x: u8,
+        y: u8,
+        width: u8,
+        height: u8,
+    ) -> String {
+        let font: HashMap<u8, char> = tiles.iter().copied().zip(chars.chars()).collect();
+        let (cols, rows) = (x..x.saturating_add(width), y..y.saturating_add(height));
+        self.core
+            .ppu
+            .read_text_region(&self.core.memory, &font, cols, rows)
+    }
+
+    /// The on-screen background as GB assets: the 256 BG-addressable tiles
+    /// (4096 bytes, in tile-index order) followed by the 20×18 tilemap at
+    /// SCX/SCY (360 bytes, row-major). VRAM bank 0 only.
+    pub fn export_frame_as_tiles(&self) -> Vec<u8> {
+        let (mut tiles, map) = self.core.ppu.export_frame_as_tiles(&self.core.memory);
+        tiles.extend(map);
+        tiles
+    }
+
+    /// Write OAM entry `index` (0–39) with raw Y/X (screen position + 16/+8), tile and attributes.
+    pub fn set_sprite(&mut self, index: u8, y: u8, x: u8, tile: u8, attr: u8) {
+        self.core.set_sprite(index, y, x, tile, attr);
+    }
+
+    /// Read OAM entry `index` as `[y, x, tile, attr]`.
+    pub fn get_sprite(&self, index: u8) -> Vec<u8> {
+        self.core.memory.get_sprite(index).to_vec()
+    }
+
     /// Read bytes from VRAM at address `addr` (0x8000–0x9FFF) from an explicit bank (0 or 1).
     /// Does not modify the emulator's VBK register — safe to call at any time.
     pub fn read_vram_bank(&self, bank: u8, addr: u16, len: u16) -> Vec<u8> {
@@ -485,28 +1110,28 @@ impl GameBoy {
 
     /// Colour of `color` (0–3) in BG `palette` (0–7) as 0xRRGGBB.
     pub fn get_bg_palette_color(&self, palette: u8, color: u8) -> u32 {
-        let (lo, hi) = self.core.memory.read_bg_palette(palette as usize, color as usize);
-        rgb555_to_rgb888(lo, hi)
+        let (lo, hi) = self
+            .core
+            .memory
+            .read_bg_palette(palette as usize, color as usize);
+        self.palette_rgb888(lo, hi)
     }
 
     /// Colour of `color` (0–3) in OBJ `palette` (0–7) as 0xRRGGBB.
     pub fn get_obj_palette_color(&self, palette: u8, color: u8) -> u32 {
-        let (lo, hi) = self.core.memory.read_obj_palette(palette as usize, color as usize);
-        rgb555_to_rgb888(lo, hi)
+        let (lo, hi) = self
+            .core
+            .memory
+            .read_obj_palette(palette as usize, color as usize);
+        self.palette_rgb888(lo, hi)
     }
-}
 
-/// Convert RGB555 (lo byte, hi byte) to 0xRRGGBB.
-fn rgb555_to_rgb888(lo: u8, hi: u8) -> u32 {
-    let raw = (lo as u16) | ((hi as u16) << 8);
-    let r5 = (raw & 0x1F) as u32;
-    let g5 = ((raw >> 5) & 0x1F) as u32;
-    let b5 = ((raw >> 10) & 0x1F) as u32;
-    // Expand 5→8 bits: multiply by 255/31 ≈ 8.226; shifting left 3 is a fast approximation.
-    let r8 = (r5 << 3) | (r5 >> 2);
-    let g8 = (g5 << 3) | (g5 >> 2);
-    let b8 = (b5 << 3) | (b5 >> 2);
-    (r8 << 16) | (g8 << 8) | b8
+    /// Convert RGB555 (lo byte, hi byte) to 0xRRGGBB with the display's
+    /// colour correction.
+    fn palette_rgb888(&self, lo: u8, hi: u8) -> u32 {
+        let [r, g, b, _] = rgb555_to_rgba8888(lo, hi, self.core.ppu.color_correction());
+        (r as u32) << 16 | (g as u32) << 8 | b as u32
+    }
 }
 
 impl Default for GameBoy {
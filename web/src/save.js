@@ -3,6 +3,10 @@
 export function createSave(state) {
     function download() {
         if (!state.emulator) return;
+        if (!state.emulator.has_battery()) {
+            console.info('Cartridge has no battery; nothing to save');
+            return;
+        }
         const saveData = state.emulator.get_cartridge_ram();
         const blob = new Blob([saveData], { type: 'application/octet-stream' });
         const url = URL.createObjectURL(blob);